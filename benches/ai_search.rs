@@ -0,0 +1,46 @@
+//! AI 搜索基准：各难度在典型局面下选择走法的耗时
+//!
+//! 运行：`cargo bench --no-default-features --bench ai_search`（不编译图形界面）
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use six_rush::game::ai::AiPlayer;
+use six_rush::game::board::Board;
+use six_rush::game::piece::Side;
+use six_rush::game::rules::check_game_end;
+use std::hint::black_box;
+
+/// 典型局面：名称、记谱与行棋方
+const POSITIONS: [(&str, &str, Side); 3] = [
+    // 初始局面
+    ("opening", "WWWW/W..W/B..B/BBBB", Side::Black),
+    // 中局：双方各有吃子
+    ("midgame", "W.WW/.W../B..B/B.B.", Side::White),
+    // 残局：白方只剩一枚棋子（担吃生效）
+    ("single_piece", "..W./..../.B../B..B", Side::White),
+];
+
+/// AI 难度范围
+const LEVELS: std::ops::RangeInclusive<u8> = 1..=5;
+
+/// 固定随机种子，使随机走法与同分走法的选择可复现
+const SEED: u64 = 42;
+
+fn select_move(c: &mut Criterion) {
+    for (name, notation, side) in POSITIONS {
+        let board = Board::from_notation(notation).expect("基准局面记谱错误");
+        assert!(check_game_end(&board, side, side).is_none(), "基准局面已分胜负: {}", name);
+
+        let mut group = c.benchmark_group(format!("select_move/{}", name));
+        group.sample_size(10);
+        for level in LEVELS {
+            let ai = AiPlayer::with_seed(level, SEED);
+            group.bench_with_input(BenchmarkId::from_parameter(level), &board, |b, board| {
+                b.iter(|| ai.select_move(black_box(board), side).expect("无合法移动"))
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, select_move);
+criterion_main!(benches);
@@ -0,0 +1,23 @@
+//! 构建脚本：检测内嵌的中文后备字体
+//!
+//! `src/assets/fonts/fallback_cjk.ttf` 存在时设置 `embedded_cjk_font`，
+//! 由 `main.rs` 以 `include_bytes!` 编译进程序；缺少字体时 release 构建给出警告
+
+use std::path::Path;
+
+/// 内嵌的中文后备字体路径（相对于 crate 根目录）
+const FALLBACK_CJK_FONT: &str = "src/assets/fonts/fallback_cjk.ttf";
+
+fn main() {
+    println!("cargo::rustc-check-cfg=cfg(embedded_cjk_font)");
+    // 监视目录，放入或替换字体文件后重新检测
+    println!("cargo::rerun-if-changed=src/assets/fonts");
+
+    if Path::new(FALLBACK_CJK_FONT).is_file() {
+        println!("cargo::rustc-cfg=embedded_cjk_font");
+    } else if std::env::var("PROFILE").as_deref() == Ok("release") {
+        println!(
+            "cargo::warning=缺少 {FALLBACK_CJK_FONT}，没有中文字体的系统上界面文字会显示为方块（见 src/assets/fonts/README.md）"
+        );
+    }
+}
@@ -0,0 +1,253 @@
+//! 命令行模式：在终端中与电脑对弈
+//!
+//! 以 `--cli` 启动时使用，不创建窗口；棋盘以字符画显示，
+//! 走法以棋盘坐标输入（如 `a1 a2`），规则与 AI 与图形界面完全相同
+
+use crate::game::board::Board;
+use crate::game::piece::Side;
+use crate::game::rules::Move;
+use crate::game::state::GameEvent;
+use crate::game::Game;
+use anyhow::{Context, Result};
+use rust_i18n::t;
+use std::io::{BufRead, Write};
+
+/// 命令行模式的默认电脑难度
+const DEFAULT_CLI_LEVEL: u8 = 3;
+
+/// 退出命令
+const QUIT_COMMANDS: [&str; 3] = ["quit", "exit", "q"];
+
+/// 命令行模式选项
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CliOptions {
+    /// 电脑难度等级（1-5）
+    pub ai_level: u8,
+    /// 玩家是否执黑先行
+    pub player_first: bool,
+}
+
+impl Default for CliOptions {
+    fn default() -> Self {
+        Self { ai_level: DEFAULT_CLI_LEVEL, player_first: true }
+    }
+}
+
+impl CliOptions {
+    /// 解析命令行参数（不含程序名），未指定 `--cli` 时返回 None
+    ///
+    /// 支持 `--level N`（电脑难度）与 `--white`（玩家执白后行）。只有指定 `--cli` 时才校验其余参数，
+    /// 图形界面启动时的参数（如文件关联传入的路径）不会导致启动失败
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Option<Self>> {
+        let args: Vec<String> = args.into_iter().collect();
+        if !args.iter().any(|arg| arg == "--cli") {
+            return Ok(None);
+        }
+
+        let mut options = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--cli" => {}
+                "--white" => options.player_first = false,
+                "--level" => {
+                    let level = args.next().with_context(|| t!("cli.missing_level").to_string())?;
+                    options.ai_level = match level.parse() {
+                        Ok(level @ 1..=5) => level,
+                        _ => anyhow::bail!("{}", t!("cli.invalid_level", level = level)),
+                    };
+                }
+                _ => anyhow::bail!("{}", t!("cli.unknown_arg", arg = arg)),
+            }
+        }
+        Ok(Some(options))
+    }
+}
+
+/// 交叉点的坐标名称：列字母加行号，如 `(0, 0)` 为 `a1`
+pub fn format_square((x, y): (u8, u8)) -> String {
+    format!("{}{}", char::from(b'a' + x), y + 1)
+}
+
+/// 走法的坐标名称，如 `a1 a2`
+pub fn format_move((from, to): Move) -> String {
+    format!("{} {}", format_square(from), format_square(to))
+}
+
+/// 解析交叉点坐标（列字母不区分大小写，行号从1开始），超出棋盘时报错
+pub fn parse_square(text: &str, size: u8) -> Result<(u8, u8)> {
+    let mut chars = text.chars();
+    let column = chars.next().map(|c| c.to_ascii_lowercase());
+    let digits = chars.as_str();
+    // 行号只接受数字（`str::parse` 还会接受 `+1` 之类的写法）
+    let row: Option<u8> = digits
+        .bytes()
+        .all(|b| b.is_ascii_digit())
+        .then(|| digits.parse().ok())
+        .flatten();
+    match (column, row) {
+        (Some(column @ 'a'..='z'), Some(row)) if row >= 1 => {
+            let (x, y) = (column as u8 - b'a', row - 1);
+            if x >= size || y >= size {
+                anyhow::bail!("{}", t!("cli.square_off_board", square = text));
+            }
+            Ok((x, y))
+        }
+        _ => anyhow::bail!("{}", t!("cli.bad_square", square = text)),
+    }
+}
+
+/// 解析走法：起点与终点坐标，以空格或 `-` 分隔，如 `a1 a2`、`a1-a2`
+pub fn parse_move(text: &str, size: u8) -> Result<Move> {
+    let squares: Vec<&str> = text
+        .split(|c: char| c.is_whitespace() || c == '-')
+        .filter(|part| !part.is_empty())
+        .collect();
+    let [from, to] = squares[..] else {
+        anyhow::bail!("{}", t!("cli.bad_move", text = text.trim()));
+    };
+    Ok((parse_square(from, size)?, parse_square(to, size)?))
+}
+
+/// 以字符画显示棋盘：`B` 为黑棋，`W` 为白棋，`.` 为空点，左侧为行号，底部为列字母
+pub fn render_board(board: &Board) -> String {
+    let mut text = String::new();
+    for y in (0..board.size).rev() {
+        let cells: Vec<String> = (0..board.size)
+            .map(|x| match board.piece_at(x, y).map(|p| p.side) {
+                Some(Side::Black) => "B".to_string(),
+                Some(Side::White) => "W".to_string(),
+                None => ".".to_string(),
+            })
+            .collect();
+        text.push_str(&format!("{:>2} {}\n", y + 1, cells.join(" ")));
+    }
+    let columns: Vec<String> = (0..board.size).map(|x| char::from(b'a' + x).to_string()).collect();
+    text.push_str(&format!("   {}\n", columns.join(" ")));
+    text
+}
+
+/// 在终端中进行一局对弈，直到分出胜负、输入退出命令或输入结束
+pub fn run(options: CliOptions) -> Result<()> {
+    let mut game = Game::new();
+    game.handle_event(GameEvent::StartNewGame {
+        player_first: options.player_first,
+        ai_level: options.ai_level,
+    })?;
+
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut stdout = std::io::stdout();
+
+    loop {
+        println!("\n{}", render_board(&game.board));
+
+        if let Some(result) = game.last_result {
+            println!("{}", result.display_text());
+            return Ok(());
+        }
+
+        if game.is_ai_turn() {
+            let result = game.ai_turn()?;
+            if let Some(record) = game.move_history.last() {
+                println!("{}", t!("cli.ai_move", mv = format_move((record.from, record.to))));
+            }
+            if !result.captured.is_empty() {
+                println!("{}", t!("cli.captured", count = result.captured.len()));
+            }
+            continue;
+        }
+
+        print!("{}", t!("cli.prompt"));
+        stdout.flush()?;
+        let Some(line) = lines.next() else {
+            return Ok(());
+        };
+        let line = line?;
+        let input = line.trim();
+        if QUIT_COMMANDS.iter().any(|quit| input.eq_ignore_ascii_case(quit)) {
+            return Ok(());
+        }
+
+        match parse_move(input, game.board.size).and_then(|(from, to)| game.play_move(from, to)) {
+            Ok(result) if !result.captured.is_empty() => {
+                println!("{}", t!("cli.captured", count = result.captured.len()));
+            }
+            Ok(_) => {}
+            Err(e) => println!("{}", t!("cli.invalid_move", reason = format!("{:#}", e))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Result<Option<CliOptions>> {
+        CliOptions::from_args(list.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parses_cli_options() {
+        assert_eq!(args(&["--cli"]).unwrap(), Some(CliOptions::default()));
+        assert_eq!(
+            args(&["--level", "5", "--cli", "--white"]).unwrap(),
+            Some(CliOptions { ai_level: 5, player_first: false })
+        );
+        for bad in [&["--cli", "--level"][..], &["--cli", "--level", "6"], &["--cli", "--level", "x"], &["--cli", "--bogus"]] {
+            assert!(args(bad).is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn gui_launch_ignores_other_arguments() {
+        assert_eq!(args(&[]).unwrap(), None);
+        // 文件关联或桌面启动器传入的参数不影响图形界面启动
+        assert_eq!(args(&["/home/user/game.6zc"]).unwrap(), None);
+        assert_eq!(args(&["--level", "9", "--unknown"]).unwrap(), None);
+    }
+
+    #[test]
+    fn parses_squares_and_moves() {
+        assert_eq!(parse_square("a1", 4).unwrap(), (0, 0));
+        assert_eq!(parse_square("D4", 4).unwrap(), (3, 3));
+        assert_eq!(parse_square("e5", 5).unwrap(), (4, 4));
+        assert_eq!(parse_move("a1 a2", 4).unwrap(), ((0, 0), (0, 1)));
+        assert_eq!(parse_move("  b2-c2 ", 4).unwrap(), ((1, 1), (2, 1)));
+        assert_eq!(parse_move("b2 - c2", 4).unwrap(), ((1, 1), (2, 1)));
+        for mv in [((0, 0), (0, 1)), ((3, 2), (2, 2))] {
+            assert_eq!(parse_move(&format_move(mv), 4).unwrap(), mv);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_squares() {
+        for square in ["", "a", "1", "11", "aa", "a0", "a-1", "a+1", "a1x", "a 1", "é1", "a256", "a99999"] {
+            assert!(parse_square(square, 4).is_err(), "{square:?}");
+        }
+    }
+
+    #[test]
+    fn rejects_out_of_range_squares() {
+        for square in ["e1", "a5", "z26", "d5"] {
+            assert!(parse_square(square, 4).is_err(), "{square:?}");
+        }
+        assert!(parse_square("e5", 5).is_ok());
+        assert!(parse_square("f1", 5).is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_moves() {
+        for text in ["", "a1", "a1 a2 a3", "a1--", "a1 e1", "x1 a2", "a1a2"] {
+            assert!(parse_move(text, 4).is_err(), "{text:?}");
+        }
+    }
+
+    #[test]
+    fn renders_board_with_coordinates() {
+        let board = Board::from_notation("W..W/..../.B../B..B").unwrap();
+        let expected = " 4 W . . W\n 3 . . . .\n 2 . B . .\n 1 B . . B\n   a b c d\n";
+        assert_eq!(render_board(&board), expected);
+        assert_eq!(render_board(&Board::initial()).lines().count(), 5);
+    }
+}
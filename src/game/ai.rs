@@ -1,44 +1,280 @@
 //! AI算法实现
 
 use crate::game::board::Board;
+use crate::game::opening::book_replies;
 use crate::game::piece::Side;
-use crate::game::rules::{get_valid_moves, is_stalemated};
+use crate::game::rules::{get_capturing_moves, get_valid_moves, Move};
+use crate::game::state::GameResult;
 use anyhow::Result;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+
+/// 自适应难度：连续取得相同结果多少局后调整一次等级
+pub const ADAPTIVE_STREAK: usize = 2;
+
+/// 接受提和的评估分上限：评估分绝对值（任一方的优势）不到半子时视为均势
+pub const DRAW_ACCEPT_THRESHOLD: i32 = 50;
+
+/// 接受提和时双方剩余棋子总数上限（只在残局中考虑和棋）
+pub const DRAW_OFFER_MAX_PIECES: usize = 6;
+
+/// 评估条两端对应的评估分（约两子优势），超出部分按满格显示
+pub const EVAL_BAR_RANGE: i32 = 200;
+
+/// 剩余棋子总数与评估分是否满足接受提和的条件
+fn draw_acceptable(pieces: usize, score: i32) -> bool {
+    pieces <= DRAW_OFFER_MAX_PIECES && score.abs() < DRAW_ACCEPT_THRESHOLD
+}
+
+/// 将评估分映射为评估条位置（0.0 到 1.0，均势为 0.5，大于 0.5 表示评估方占优）
+pub fn evaluation_bar(score: i32) -> f32 {
+    0.5 + score.clamp(-EVAL_BAR_RANGE, EVAL_BAR_RANGE) as f32 / (2 * EVAL_BAR_RANGE) as f32
+}
+
+/// 根据玩家最近的对局结果计算自适应难度下的AI等级
+///
+/// 玩家连胜 [`ADAPTIVE_STREAK`] 局则升一级，连败则降一级，平局中断连胜/连败；
+/// 结果限制在 1-5 之间
+pub fn adapt_level(level: u8, recent: &[GameResult]) -> u8 {
+    let Some(&last) = recent.last() else {
+        return level.clamp(1, 5);
+    };
+    let streak = recent.iter().rev().take_while(|&&r| r == last).count();
+    let adjusted = match last {
+        GameResult::PlayerWin if streak >= ADAPTIVE_STREAK => level.saturating_add(1),
+        GameResult::AiWin if streak >= ADAPTIVE_STREAK => level.saturating_sub(1),
+        _ => level,
+    };
+    adjusted.clamp(1, 5)
+}
+
+/// 电脑的行棋风格
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AiStyle {
+    /// 按难度等级选择走法（默认）
+    #[default]
+    Standard,
+    /// 模仿：把对方上一步关于棋盘中心对称地走一遍，走不了时按难度等级选择
+    Mirror,
+}
+
+impl AiStyle {
+    /// 所有行棋风格
+    pub const ALL: [AiStyle; 2] = [AiStyle::Standard, AiStyle::Mirror];
+
+    /// 获取本地化的显示文本
+    pub fn display_text(&self) -> String {
+        match self {
+            AiStyle::Standard => crate::t!("ai_style.standard"),
+            AiStyle::Mirror => crate::t!("ai_style.mirror"),
+        }
+    }
+}
 
 /// AI玩家
 pub struct AiPlayer {
     level: u8,
+    /// 随机数生成器（随机走法与同分走法的选择）
+    rng: RefCell<StdRng>,
+    /// 开局变化：在开局库的多个应着中随机选择，否则总是选第一个
+    opening_variety: bool,
+    /// 行棋风格
+    style: AiStyle,
+    /// 对方上一步（模仿风格使用）
+    last_opponent_move: Option<Move>,
+    /// 本局中己方走完后出现过的局面（Zobrist 哈希），占优时避免走回，以免重复局面判和
+    pub(crate) seen_positions: Vec<u64>,
 }
 
 impl AiPlayer {
     /// 创建AI玩家
     pub fn new(level: u8) -> Self {
-        Self { level: level.clamp(1, 5) }
+        Self {
+            level: level.clamp(1, 5),
+            rng: RefCell::new(StdRng::from_entropy()),
+            opening_variety: false,
+            style: AiStyle::Standard,
+            last_opponent_move: None,
+            seen_positions: Vec::new(),
+        }
+    }
+
+    /// 创建使用固定随机种子的AI玩家，相同种子与局面下走法可复现
+    pub fn with_seed(level: u8, seed: u64) -> Self {
+        Self {
+            level: level.clamp(1, 5),
+            rng: RefCell::new(StdRng::seed_from_u64(seed)),
+            opening_variety: false,
+            style: AiStyle::Standard,
+            last_opponent_move: None,
+            seen_positions: Vec::new(),
+        }
+    }
+
+    /// 设置开局变化：开局库中有多个同样强的应着时随机选择（使用本AI的随机数生成器）
+    pub fn with_opening_variety(mut self, opening_variety: bool) -> Self {
+        self.opening_variety = opening_variety;
+        self
+    }
+
+    /// 设置行棋风格，模仿风格需要对方上一步（没有时按难度等级选择）
+    pub fn with_style(mut self, style: AiStyle, last_opponent_move: Option<Move>) -> Self {
+        self.style = style;
+        self.last_opponent_move = last_opponent_move;
+        self
+    }
+
+    /// 设置本局中己方走完后出现过的局面（Zobrist 哈希），3级以上占优时不再走回这些局面
+    pub fn with_seen_positions(mut self, seen_positions: Vec<u64>) -> Self {
+        self.seen_positions = seen_positions;
+        self
     }
 
     /// 选择走法
-    pub fn select_move(&self, board: &Board, side: Side) -> Result<((u8, u8), (u8, u8))> {
-        let valid_moves = get_valid_moves(board, side);
+    pub fn select_move(&self, board: &Board, side: Side) -> Result<Move> {
+        let valid_moves = self.candidate_moves(board, side);
         
         if valid_moves.is_empty() {
             return Err(anyhow::anyhow!("无合法移动"));
         }
 
-        match self.level {
-            1 => Self::random_move(&valid_moves),
+        if self.style == AiStyle::Mirror
+            && let Some(mv) = self.mirror_move(board, &valid_moves)
+        {
+            crate::log_debug!("AI（模仿）对称走出 {:?}", mv);
+            return Ok(mv);
+        }
+
+        // 搜索难度下优先使用开局库
+        if self.level >= 3
+            && let Some(mv) = self.book_move(board, side, &valid_moves)
+        {
+            crate::log_debug!("AI（{}级，{:?}）使用开局库走法 {:?}", self.level, side, mv);
+            return Ok(mv);
+        }
+
+        let selected = match self.level {
+            1 => self.random_move(&valid_moves),
             2 => self.simple_eval_move(board, &valid_moves, side),
-            3 => self.minimax_move(board, &valid_moves, side, 4),
-            4 => self.minimax_move(board, &valid_moves, side, 6),
+            3 | 4 => self.minimax_move(board, &valid_moves, side),
             5 => self.optimal_move(board, &valid_moves, side),
-            _ => Self::random_move(&valid_moves),
+            _ => self.random_move(&valid_moves),
+        };
+        if let Ok(mv) = &selected {
+            crate::log_debug!("AI（{}级，{:?}）在{}个候选走法中选择 {:?}", self.level, side, valid_moves.len(), mv);
+        }
+        selected
+    }
+
+    /// 候选走法：合法走法中，非随机难度排除会被对方一步吃光的走法，
+    /// 搜索难度在占优时再排除走回已出现局面的走法（均在别无选择时保留）
+    fn candidate_moves(&self, board: &Board, side: Side) -> Vec<Move> {
+        let moves = get_valid_moves(board, side);
+        if self.level < 2 {
+            return moves;
+        }
+        let moves = Self::filter_losing_moves(board, moves, side);
+        if self.level < 3 || self.seen_positions.is_empty() || self.evaluate(board, side) <= 0 {
+            return moves;
+        }
+        let fresh: Vec<Move> = moves
+            .iter()
+            .copied()
+            .filter(|&(from, to)| {
+                let mut test_board = board.clone();
+                test_board.execute_move(from, to, side).is_ok()
+                    && !self.seen_positions.contains(&test_board.zobrist_hash())
+            })
+            .collect();
+        if fresh.is_empty() { moves } else { fresh }
+    }
+
+    /// 主要变例：搜索认为双方此后都走最佳着法时的预计走法序列，第一步即搜索选出的走法
+    ///
+    /// 长度不超过搜索深度（提前分出胜负时更短）；1、2级不搜索，返回空。
+    /// 只反映搜索结果，不考虑开局库与模仿风格
+    pub fn principal_variation(&self, board: &Board, side: Side) -> Vec<Move> {
+        let Some(depth) = self.search_depth() else {
+            return Vec::new();
+        };
+        let moves = self.candidate_moves(board, side);
+        self.search(board, &moves, side, depth)
+    }
+
+    /// 搜索深度（半回合），不搜索的难度返回 None
+    fn search_depth(&self) -> Option<i32> {
+        match self.level {
+            3 => Some(4),
+            4 => Some(6),
+            5 => Some(8),
+            _ => None,
+        }
+    }
+
+    /// 是否接受对方的提和
+    ///
+    /// 只在残局（剩余棋子不超过 [`DRAW_OFFER_MAX_PIECES`]）中考虑，
+    /// 评估分绝对值低于 [`DRAW_ACCEPT_THRESHOLD`]（双方均无明显优势）时接受
+    pub fn accepts_draw(&self, board: &Board, ai_side: Side) -> bool {
+        let pieces = board.count_active(Side::Black) + board.count_active(Side::White);
+        draw_acceptable(pieces, self.evaluate(board, ai_side))
+    }
+
+    /// 排除走完后对方可立即吃光己方所有棋子的走法（如单子"担"吃）
+    ///
+    /// 若所有走法都会导致被吃光，则原样返回
+    fn filter_losing_moves(board: &Board, moves: Vec<Move>, side: Side) -> Vec<Move> {
+        let safe_moves: Vec<Move> = moves
+            .iter()
+            .copied()
+            .filter(|&(from, to)| {
+                let mut test_board = board.clone();
+                test_board.execute_move(from, to, side).is_ok()
+                    && !Self::can_capture_all(&test_board, side.opposite())
+            })
+            .collect();
+
+        if safe_moves.is_empty() { moves } else { safe_moves }
+    }
+
+    /// 检查 attacker 是否存在一步走法能吃光对方所有棋子
+    fn can_capture_all(board: &Board, attacker: Side) -> bool {
+        let defender_count = board.count_active(attacker.opposite());
+        get_valid_moves(board, attacker).into_iter().any(|(from, to)| {
+            let mut test_board = board.clone();
+            test_board
+                .execute_move(from, to, attacker)
+                .is_ok_and(|record| record.captured.len() >= defender_count)
+        })
+    }
+
+    /// 模仿风格：对方上一步关于棋盘中心的对称走法，不在候选走法中时返回 None
+    fn mirror_move(&self, board: &Board, moves: &[Move]) -> Option<Move> {
+        let (from, to) = self.last_opponent_move?;
+        let mirrored = (board.center_mirror(from), board.center_mirror(to));
+        moves.contains(&mirrored).then_some(mirrored)
+    }
+
+    /// 从开局库中选择应着（只考虑候选走法），局面未收录时返回 None
+    fn book_move(&self, board: &Board, side: Side, moves: &[Move]) -> Option<Move> {
+        // 按走法生成顺序排列，换算自对称局面的应着也与直接收录时一致
+        let book = book_replies(board, side);
+        let replies: Vec<Move> = moves.iter().copied().filter(|mv| book.contains(mv)).collect();
+        if self.opening_variety {
+            self.random_move(&replies).ok()
+        } else {
+            replies.first().copied()
         }
     }
 
     /// Level 1: 完全随机
-    fn random_move(moves: &[((u8, u8), (u8, u8))]) -> Result<((u8, u8), (u8, u8))> {
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
-        let idx = rng.gen_range(0..moves.len());
+    fn random_move(&self, moves: &[Move]) -> Result<Move> {
+        if moves.is_empty() {
+            return Err(anyhow::anyhow!("无可用移动"));
+        }
+        let idx = self.rng.borrow_mut().gen_range(0..moves.len());
         moves.get(idx).copied()
             .ok_or_else(|| anyhow::anyhow!("无可用移动"))
     }
@@ -47,28 +283,20 @@ impl AiPlayer {
     fn simple_eval_move(
         &self,
         board: &Board,
-        moves: &[((u8, u8), (u8, u8))],
-        _side: Side,
-    ) -> Result<((u8, u8), (u8, u8))> {
-        // 优先选择能吃子的走法
-        let capturing_moves: Vec<_> = moves
-            .iter()
-            .filter(|(from, to)| {
-                // 模拟移动并检查是否能吃子
-                let mut test_board = board.clone();
-                if let Ok(record) = test_board.execute_move(*from, *to, _side) {
-                    !record.captured.is_empty()
-                } else {
-                    false
-                }
-            })
-            .copied()
+        moves: &[Move],
+        side: Side,
+    ) -> Result<Move> {
+        // 优先选择能吃子的走法（仅限候选走法）
+        let capturing_moves: Vec<Move> = get_capturing_moves(board, side)
+            .into_iter()
+            .map(|(mv, _)| mv)
+            .filter(|mv| moves.contains(mv))
             .collect();
 
         if !capturing_moves.is_empty() {
-            Self::random_move(&capturing_moves)
+            self.random_move(&capturing_moves)
         } else {
-            Self::random_move(moves)
+            self.random_move(moves)
         }
     }
 
@@ -76,28 +304,40 @@ impl AiPlayer {
     fn minimax_move(
         &self,
         board: &Board,
-        moves: &[((u8, u8), (u8, u8))],
+        moves: &[Move],
         side: Side,
-        depth: i32,
-    ) -> Result<((u8, u8), (u8, u8))> {
-        let mut best_move = None;
+    ) -> Result<Move> {
+        let depth = self.search_depth().unwrap_or(1);
+        self.search(board, moves, side, depth)
+            .first()
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("无法找到最佳移动"))
+    }
+
+    /// 在候选走法中搜索，返回主要变例（首步为最佳走法，无可走时为空）
+    fn search(&self, board: &Board, moves: &[Move], side: Side, depth: i32) -> Vec<Move> {
+        let mut best_line = Vec::new();
         let mut best_score = i32::MIN;
 
         for (from, to) in moves.iter().copied() {
             let mut test_board = board.clone();
             if test_board.execute_move(from, to, side).is_ok() {
-                let score = self.minimax(&test_board, depth - 1, false, side, i32::MIN, i32::MAX);
-                if score > best_score {
+                let (score, mut line) = self.minimax(&test_board, depth - 1, false, side, i32::MIN, i32::MAX);
+                if score > best_score || best_line.is_empty() {
                     best_score = score;
-                    best_move = Some((from, to));
+                    best_line.clear();
+                    best_line.push((from, to));
+                    best_line.append(&mut line);
                 }
             }
         }
 
-        best_move.ok_or_else(|| anyhow::anyhow!("无法找到最佳移动"))
+        best_line
     }
 
     /// Minimax算法（带Alpha-Beta剪枝）
+    ///
+    /// 返回局面分数和本节点之后的主要变例（最佳子节点的走法加其主要变例）
     fn minimax(
         &self,
         board: &Board,
@@ -106,9 +346,10 @@ impl AiPlayer {
         ai_side: Side,
         mut alpha: i32,
         mut beta: i32,
-    ) -> i32 {
+    ) -> (i32, Vec<Move>) {
+        let mut pv = Vec::new();
         if depth == 0 {
-            return self.evaluate(board, ai_side);
+            return (self.evaluate(board, ai_side), pv);
         }
 
         let current_side = if is_maximizing { ai_side } else { ai_side.opposite() };
@@ -116,7 +357,8 @@ impl AiPlayer {
 
         if moves.is_empty() {
             // 无合法移动，困毙
-            return if is_maximizing { i32::MIN + 100 } else { i32::MAX - 100 };
+            let score = if is_maximizing { i32::MIN + 100 } else { i32::MAX - 100 };
+            return (score, pv);
         }
 
         if is_maximizing {
@@ -124,52 +366,63 @@ impl AiPlayer {
             for (from, to) in moves {
                 let mut test_board = board.clone();
                 if test_board.execute_move(from, to, current_side).is_ok() {
-                    let eval = self.minimax(&test_board, depth - 1, false, ai_side, alpha, beta);
-                    max_eval = max_eval.max(eval);
+                    let (eval, mut line) = self.minimax(&test_board, depth - 1, false, ai_side, alpha, beta);
+                    if eval > max_eval || pv.is_empty() {
+                        max_eval = eval;
+                        pv.clear();
+                        pv.push((from, to));
+                        pv.append(&mut line);
+                    }
                     alpha = alpha.max(eval);
                     if beta <= alpha {
                         break; // Beta剪枝
                     }
                 }
             }
-            max_eval
+            (max_eval, pv)
         } else {
             let mut min_eval = i32::MAX;
             for (from, to) in moves {
                 let mut test_board = board.clone();
                 if test_board.execute_move(from, to, ai_side).is_ok() {
-                    let eval = self.minimax(&test_board, depth - 1, true, ai_side, alpha, beta);
-                    min_eval = min_eval.min(eval);
+                    let (eval, mut line) = self.minimax(&test_board, depth - 1, true, ai_side, alpha, beta);
+                    if eval < min_eval || pv.is_empty() {
+                        min_eval = eval;
+                        pv.clear();
+                        pv.push((from, to));
+                        pv.append(&mut line);
+                    }
                     beta = beta.min(eval);
                     if beta <= alpha {
                         break; // Alpha剪枝
                     }
                 }
             }
-            min_eval
+            (min_eval, pv)
         }
     }
 
-    /// 评估函数
-    fn evaluate(&self, board: &Board, ai_side: Side) -> i32 {
+    /// 评估函数：从 `ai_side` 一方的视角给局面打分，正值表示该方占优
+    pub fn evaluate(&self, board: &Board, ai_side: Side) -> i32 {
         let player_side = ai_side.opposite();
-        let ai_count = board.count_active(ai_side) as i32;
-        let player_count = board.count_active(player_side) as i32;
+        let summary = board.summary();
+        let ai_count = summary.count(ai_side) as i32;
+        let player_count = summary.count(player_side) as i32;
 
         // 基础评估：棋子数差值 * 100
         let mut score = (ai_count - player_count) * 100;
 
         // 灵活性评估：可移动方向数
-        let ai_moves = get_valid_moves(board, ai_side).len() as i32;
-        let player_moves = get_valid_moves(board, player_side).len() as i32;
+        let ai_moves = summary.moves(ai_side) as i32;
+        let player_moves = summary.moves(player_side) as i32;
         score += (ai_moves - player_moves) * 5;
 
         // 困毙评估 - 这是最重要的
-        if is_stalemated(board, player_side) {
+        if player_moves == 0 {
             // 玩家被困毙，AI大胜
             score += 10000;
         }
-        if is_stalemated(board, ai_side) {
+        if ai_moves == 0 {
             // AI被困毙，AI大败
             score -= 10000;
         }
@@ -182,20 +435,15 @@ impl AiPlayer {
                 let (px, py) = single_piece.position;
                 
                 // 计算单子周围的空格数（移动空间）
-                let directions = [(0, 1), (0, -1), (1, 0), (-1, 0)];
-                let empty_neighbors = directions.iter().filter(|&&(dx, dy)| {
-                    let nx = px as i8 + dx;
-                    let ny = py as i8 + dy;
-                    Board::is_valid_pos(nx, ny) && board.is_empty(nx as u8, ny as u8)
-                }).count();
+                let empty_neighbors = board.legal_moves_for(single_piece.id).len();
                 
                 // 单子的移动空间越小，对AI越有利
                 score += (4 - empty_neighbors as i32) * 50;
                 
                 // 鼓励AI棋子靠近单子（围堵）
                 for ai_piece in board.active_pieces_of(ai_side) {
-                    let dist = ((ai_piece.position.0 as i32 - px as i32).abs()
-                        + (ai_piece.position.1 as i32 - py as i32).abs()) as i32;
+                    let dist = (ai_piece.position.0 as i32 - px as i32).abs()
+                        + (ai_piece.position.1 as i32 - py as i32).abs();
                     score += (6 - dist) * 10; // 距离越近分数越高
                 }
             }
@@ -213,11 +461,254 @@ impl AiPlayer {
     fn optimal_move(
         &self,
         board: &Board,
-        moves: &[((u8, u8), (u8, u8))],
+        moves: &[Move],
         side: Side,
-    ) -> Result<((u8, u8), (u8, u8))> {
+    ) -> Result<Move> {
         // 对于4x4棋盘和最多12枚棋子，游戏复杂度相对较低
         // 可以尝试完整搜索或使用较深的Minimax
-        self.minimax_move(board, moves, side, 8)
+        self.minimax_move(board, moves, side)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draw_acceptance_boundaries() {
+        let max = DRAW_OFFER_MAX_PIECES;
+        assert!(draw_acceptable(max, 0));
+        assert!(draw_acceptable(max, DRAW_ACCEPT_THRESHOLD - 1));
+        assert!(draw_acceptable(max, -(DRAW_ACCEPT_THRESHOLD - 1)));
+        assert!(!draw_acceptable(max, DRAW_ACCEPT_THRESHOLD));
+        assert!(!draw_acceptable(max, -DRAW_ACCEPT_THRESHOLD));
+        assert!(!draw_acceptable(max + 1, 0));
+    }
+
+    #[test]
+    fn evaluation_bar_is_centered_clamped_and_monotonic() {
+        assert!((evaluation_bar(0) - 0.5).abs() < 1e-6);
+        assert_eq!(evaluation_bar(EVAL_BAR_RANGE), 1.0);
+        assert_eq!(evaluation_bar(-EVAL_BAR_RANGE), 0.0);
+        // 超出范围按满格显示
+        assert_eq!(evaluation_bar(i32::MAX), 1.0);
+        assert_eq!(evaluation_bar(i32::MIN), 0.0);
+
+        let scores = [-10_000, -300, -150, -1, 0, 1, 50, 199, 200, 10_000];
+        let bars: Vec<f32> = scores.iter().map(|&score| evaluation_bar(score)).collect();
+        assert!(bars.iter().all(|bar| (0.0..=1.0).contains(bar)));
+        assert!(bars.windows(2).all(|pair| pair[0] <= pair[1]), "{bars:?}");
+        assert!(evaluation_bar(1) > evaluation_bar(0));
+    }
+
+    #[test]
+    fn opening_variety_picks_different_book_replies() {
+        use std::collections::HashSet;
+        let board = Board::initial();
+        let book: HashSet<Move> = book_replies(&board, Side::Black).into_iter().collect();
+        assert_eq!(book.len(), 2);
+
+        let seeded: HashSet<Move> = (0..20)
+            .map(|seed| AiPlayer::with_seed(3, seed).with_opening_variety(true).select_move(&board, Side::Black).unwrap())
+            .collect();
+        assert_eq!(seeded, book);
+
+        // 相同种子下走法可复现
+        let pick = |seed| AiPlayer::with_seed(4, seed).with_opening_variety(true).select_move(&board, Side::Black).unwrap();
+        assert!((0..10).all(|seed| pick(seed) == pick(seed)));
+
+        // 不固定种子时多次选择同样能见到不同的应着
+        let unseeded: HashSet<Move> = (0..50)
+            .map(|_| AiPlayer::new(5).with_opening_variety(true).select_move(&board, Side::Black).unwrap())
+            .collect();
+        assert_eq!(unseeded, book);
+
+        // 关闭开局变化时总是同一应着
+        let fixed: HashSet<Move> = (0..20)
+            .map(|seed| AiPlayer::with_seed(3, seed).select_move(&board, Side::Black).unwrap())
+            .collect();
+        assert_eq!(fixed.len(), 1);
+    }
+
+    #[test]
+    fn mirror_style_replies_symmetrically_when_legal() {
+        let mut board = Board::initial();
+        board.execute_move((0, 1), (1, 1), Side::Black).unwrap();
+
+        // 黑方 (0,1)→(1,1) 关于中心对称为 (3,2)→(2,2)
+        let mirror = |seed| AiPlayer::with_seed(1, seed).with_style(AiStyle::Mirror, Some(((0, 1), (1, 1))));
+        for seed in 0..5 {
+            assert_eq!(mirror(seed).select_move(&board, Side::White).unwrap(), ((3, 2), (2, 2)));
+        }
+
+        // 对称走法被挡住时按难度等级选择，与标准风格相同
+        board.execute_move((3, 2), (2, 2), Side::White).unwrap();
+        board.execute_move((3, 1), (2, 1), Side::Black).unwrap();
+        // 黑方 (3,1)→(2,1) 的对称走法 (0,2)→(1,2) 仍合法
+        let last = Some(((3, 1), (2, 1)));
+        let ai = AiPlayer::with_seed(1, 0).with_style(AiStyle::Mirror, last);
+        assert_eq!(ai.select_move(&board, Side::White).unwrap(), ((0, 2), (1, 2)));
+        // 对称走法 (2,2)→(2,3) 的目标点已被占：不合法，退回标准走法
+        let blocked = Some(((1, 1), (1, 0)));
+        for seed in 0..5 {
+            let fallback = AiPlayer::with_seed(1, seed).with_style(AiStyle::Mirror, blocked);
+            let standard = AiPlayer::with_seed(1, seed);
+            assert_eq!(
+                fallback.select_move(&board, Side::White).unwrap(),
+                standard.select_move(&board, Side::White).unwrap()
+            );
+        }
+        // 没有对方上一步（电脑先行）时同样按难度等级选择
+        let opening = AiPlayer::with_seed(1, 7).with_style(AiStyle::Mirror, None);
+        assert_eq!(
+            opening.select_move(&board, Side::White).unwrap(),
+            AiPlayer::with_seed(1, 7).select_move(&board, Side::White).unwrap()
+        );
+    }
+
+    #[test]
+    fn adapt_level_follows_streaks_and_clamps() {
+        use GameResult::{AiWin, Draw, PlayerWin};
+
+        assert_eq!(adapt_level(3, &[]), 3);
+        // 连胜升级、连败降级，不足连胜局数或被平局打断时不变
+        assert_eq!(adapt_level(3, &[PlayerWin; ADAPTIVE_STREAK]), 4);
+        assert_eq!(adapt_level(3, &[AiWin; ADAPTIVE_STREAK]), 2);
+        assert_eq!(adapt_level(3, &[PlayerWin; ADAPTIVE_STREAK - 1]), 3);
+        assert_eq!(adapt_level(3, &[PlayerWin, Draw]), 3);
+        assert_eq!(adapt_level(3, &[AiWin, PlayerWin]), 3);
+
+        // 两端限制在 1-5 级
+        assert_eq!(adapt_level(5, &[PlayerWin; ADAPTIVE_STREAK]), 5);
+        assert_eq!(adapt_level(1, &[AiWin; ADAPTIVE_STREAK]), 1);
+        assert_eq!(adapt_level(9, &[]), 5);
+        assert_eq!(adapt_level(0, &[AiWin; ADAPTIVE_STREAK]), 1);
+    }
+
+    #[test]
+    fn never_lets_a_lone_piece_flank_the_last_two_stones() {
+        // 白方剩两子，黑方单子在 (1,0)；白方 (2,2)→(2,1) 后黑方走到 (1,1) 即可担吃两子
+        let board = Board::from_notation("..../..W./W.../.B..").unwrap();
+        let losing = ((2, 2), (2, 1));
+        let mut after = board.clone();
+        after.execute_move(losing.0, losing.1, Side::White).unwrap();
+        assert!(AiPlayer::can_capture_all(&after, Side::Black));
+
+        let moves = get_valid_moves(&board, Side::White);
+        let safe = AiPlayer::filter_losing_moves(&board, moves.clone(), Side::White);
+        let expected: Vec<Move> = moves.into_iter().filter(|&mv| mv != losing).collect();
+        assert_eq!(safe, expected);
+
+        for level in 2..=5 {
+            for seed in 0..5 {
+                let mv = AiPlayer::with_seed(level, seed).select_move(&board, Side::White).unwrap();
+                assert_ne!(mv, losing, "{level} {seed}");
+            }
+        }
+    }
+
+    #[test]
+    fn avoids_seen_positions_only_when_ahead() {
+        // 白方多两子，除 (1,1)→(1,2) 外其余走法的结果都已出现过
+        let board = Board::from_notation("W..W/..../.W../B..W").unwrap();
+        let keep = ((1, 1), (1, 2));
+        let after = |(from, to): Move| {
+            let mut board = board.clone();
+            board.execute_move(from, to, Side::White).unwrap();
+            board.zobrist_hash()
+        };
+        let seen: Vec<u64> = get_valid_moves(&board, Side::White)
+            .into_iter()
+            .filter(|&mv| mv != keep)
+            .map(after)
+            .collect();
+        for seed in 0..5 {
+            let ai = AiPlayer::with_seed(3, seed).with_seen_positions(seen.clone());
+            assert_eq!(ai.select_move(&board, Side::White).unwrap(), keep);
+        }
+
+        // 落后的黑方不排除走回已出现局面的走法
+        let black_moves = get_valid_moves(&board, Side::Black);
+        let seen: Vec<u64> = black_moves[1..]
+            .iter()
+            .map(|&(from, to)| {
+                let mut board = board.clone();
+                board.execute_move(from, to, Side::Black).unwrap();
+                board.zobrist_hash()
+            })
+            .collect();
+        let ai = AiPlayer::with_seed(3, 0).with_seen_positions(seen);
+        assert_eq!(
+            ai.candidate_moves(&board, Side::Black),
+            AiPlayer::filter_losing_moves(&board, black_moves, Side::Black)
+        );
+    }
+
+    #[test]
+    fn principal_variation_starts_with_selected_move_and_is_legal() {
+        let positions = [
+            ("..BB/.W../W.B./B...", Side::White),
+            ("W.WW/.W../.BB./B..B", Side::Black),
+            ("W..W/..../.W../B..W", Side::White),
+            ("W..W/..../.W../B..W", Side::Black),
+        ];
+        for (notation, side) in positions {
+            let board = Board::from_notation(notation).unwrap();
+            for level in [3, 4, 5] {
+                let ai = AiPlayer::with_seed(level, 0);
+                let pv = ai.principal_variation(&board, side);
+                assert_eq!(pv.first().copied(), Some(ai.select_move(&board, side).unwrap()), "{notation} {level}");
+
+                // 双方轮流按主要变例走子，每一步都必须合法
+                let mut replay = board.clone();
+                let mut mover = side;
+                for &(from, to) in &pv {
+                    assert!(get_valid_moves(&replay, mover).contains(&(from, to)), "{notation} {level} {:?}", (from, to));
+                    replay.execute_move(from, to, mover).unwrap();
+                    mover = mover.opposite();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn accepts_draw_only_in_balanced_endgames() {
+        let ai = AiPlayer::new(3);
+        let balanced = Board::from_notation("W..W/..../..../B..B").unwrap();
+        assert!(ai.accepts_draw(&balanced, Side::White));
+
+        // 白方多两子：白方不接受，黑方同样不以和棋收场
+        let lopsided = Board::from_notation("W..W/..../.W../B..W").unwrap();
+        assert!(!ai.accepts_draw(&lopsided, Side::White));
+        assert!(!ai.accepts_draw(&lopsided, Side::Black));
+
+        // 开局棋子太多，即使均势也不接受
+        assert!(!ai.accepts_draw(&Board::initial(), Side::White));
+    }
+
+    #[test]
+    fn principal_variation_follows_a_forced_capture_line() {
+        // 白方 (2,0) 被困，只能走 (3,0)→(3,1)，随后黑方 (0,1)→(1,1) 成二打一吃掉该子
+        let board = Board::from_notation("..../..../..B./BBWW").unwrap();
+        let line = [((0, 0), (0, 1)), ((3, 0), (3, 1)), ((0, 1), (1, 1)), ((2, 0), (3, 0))];
+        assert_eq!(AiPlayer::with_seed(3, 0).principal_variation(&board, Side::Black), line);
+
+        // 白方每一步都别无选择，吃子发生在黑方第二步
+        let mut replay = board.clone();
+        let mut mover = Side::Black;
+        let mut captured = Vec::new();
+        for &(from, to) in &line {
+            if mover == Side::White {
+                assert_eq!(get_valid_moves(&replay, mover), vec![(from, to)]);
+            }
+            captured.push(replay.execute_move(from, to, mover).unwrap().captured.len());
+            mover = mover.opposite();
+        }
+        assert_eq!(captured, [0, 0, 1, 0]);
+
+        // 更深的搜索沿同一变例继续
+        let deeper = AiPlayer::with_seed(4, 0).principal_variation(&board, Side::Black);
+        assert_eq!(deeper.len(), 6);
+        assert_eq!(deeper[..4], line);
     }
 }
@@ -2,10 +2,19 @@
 //!
 //! 按照 specification.md 中的音效规格实现
 //! 音效文件存放在 src/assets/sounds/ 目录下，使用 include_bytes! 嵌入程序
+//! 用户可在配置目录的 sounds/ 子目录下放置同名WAV文件覆盖内置音效
+//! 背景音乐暂无内置文件，仅从该目录加载（music.ogg / music.mp3 / music.flac / music.wav）
 
-use rodio::{source::Source, Decoder, OutputStream, OutputStreamHandle};
+use crate::game::settings::config_dir;
+use rodio::{source::Source, Decoder, OutputStream, OutputStreamHandle, Sink};
+use std::cell::RefCell;
 use std::collections::HashMap;
-use std::io::Cursor;
+use std::fs::{self, File};
+use std::io::{BufReader, Cursor};
+use std::path::PathBuf;
+
+/// 背景音乐候选文件名（按顺序查找）
+const MUSIC_FILES: [&str; 4] = ["music.ogg", "music.mp3", "music.flac", "music.wav"];
 
 /// 音效类型
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -18,6 +27,8 @@ pub enum SoundType {
     Invalid,
     /// 吃子/担子 - 略长，有"吃掉"的感觉
     Capture,
+    /// 单子担吃（一次吃掉两枚）- 比普通吃子更厚重
+    DoubleCapture,
     /// 玩家获胜 - 胜利音效，欢快
     Win,
     /// 电脑获胜 - 失败音效，低沉
@@ -26,6 +37,48 @@ pub enum SoundType {
     Draw,
 }
 
+impl SoundType {
+    /// 所有音效类型
+    pub const ALL: [SoundType; 8] = [
+        SoundType::Click,
+        SoundType::Place,
+        SoundType::Invalid,
+        SoundType::Capture,
+        SoundType::DoubleCapture,
+        SoundType::Win,
+        SoundType::Lose,
+        SoundType::Draw,
+    ];
+
+    /// 对应的音效文件名（用于查找用户自定义音效）
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            SoundType::Click => "click.wav",
+            SoundType::Place => "place.wav",
+            SoundType::Invalid => "invalid.wav",
+            SoundType::Capture => "capture.wav",
+            SoundType::DoubleCapture => "double_capture.wav",
+            SoundType::Win => "win.wav",
+            SoundType::Lose => "lose.wav",
+            SoundType::Draw => "draw.wav",
+        }
+    }
+
+    /// 获取本地化的显示文本
+    pub fn display_text(&self) -> String {
+        match self {
+            SoundType::Click => crate::t!("sound.click"),
+            SoundType::Place => crate::t!("sound.place"),
+            SoundType::Invalid => crate::t!("sound.invalid"),
+            SoundType::Capture => crate::t!("sound.capture"),
+            SoundType::DoubleCapture => crate::t!("sound.double_capture"),
+            SoundType::Win => crate::t!("sound.win"),
+            SoundType::Lose => crate::t!("sound.lose"),
+            SoundType::Draw => crate::t!("sound.draw"),
+        }
+    }
+}
+
 /// 音效资源文件路径（相对于 src 目录）
 const CLICK_SOUND: &[u8] = include_bytes!("../assets/sounds/click.wav");
 const PLACE_SOUND: &[u8] = include_bytes!("../assets/sounds/place.wav");
@@ -35,6 +88,11 @@ const WIN_SOUND: &[u8] = include_bytes!("../assets/sounds/win.wav");
 const LOSE_SOUND: &[u8] = include_bytes!("../assets/sounds/lose.wav");
 const DRAW_SOUND: &[u8] = include_bytes!("../assets/sounds/draw.wav");
 
+/// 用户自定义音效目录（配置目录下的 sounds/）
+pub fn user_sounds_dir() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("sounds"))
+}
+
 /// 音效管理器
 pub struct AudioManager {
     /// 输出流
@@ -45,6 +103,14 @@ pub struct AudioManager {
     sounds: HashMap<SoundType, Vec<u8>>,
     /// 是否启用音效
     enabled: bool,
+    /// 音效音量（0.0 - 1.0）
+    volume: f32,
+    /// 正在播放的音效（播放结束的在下次播放时清理）
+    playing: RefCell<Vec<Sink>>,
+    /// 正在播放的背景音乐
+    music: Option<Sink>,
+    /// 背景音乐音量（0.0 - 1.0）
+    music_volume: f32,
 }
 
 impl AudioManager {
@@ -57,6 +123,10 @@ impl AudioManager {
                     stream_handle,
                     sounds: HashMap::new(),
                     enabled: true,
+                    volume: 1.0,
+                    playing: RefCell::new(Vec::new()),
+                    music: None,
+                    music_volume: 0.5,
                 };
                 
                 // 加载内置音效
@@ -65,13 +135,15 @@ impl AudioManager {
                 Some(manager)
             }
             Err(e) => {
-                eprintln!("无法初始化音频系统: {}", e);
+                crate::log_warn!("无法初始化音频系统: {}", e);
                 None
             }
         }
     }
     
     /// 加载所有音效
+    ///
+    /// 优先使用用户目录中的自定义音效，其次是内置音效，最后使用占位符
     fn load_sounds(&mut self) {
         // 尝试加载真实音效文件，如果失败则使用占位符
         let sound_files = [
@@ -79,14 +151,18 @@ impl AudioManager {
             (SoundType::Place, PLACE_SOUND),
             (SoundType::Invalid, INVALID_SOUND),
             (SoundType::Capture, CAPTURE_SOUND),
+            // 担吃音效暂无内置音效文件
+            (SoundType::DoubleCapture, &[][..]),
             (SoundType::Win, WIN_SOUND),
             (SoundType::Lose, LOSE_SOUND),
             (SoundType::Draw, DRAW_SOUND),
         ];
         
         for (sound_type, bytes) in sound_files {
-            // 检查文件是否有实际内容（至少包含有效的WAV头）
-            if bytes.len() > 44 {
+            if let Some(custom) = Self::load_user_sound(sound_type) {
+                self.sounds.insert(sound_type, custom);
+            } else if bytes.len() > 44 {
+                // 检查文件是否有实际内容（至少包含有效的WAV头）
                 self.sounds.insert(sound_type, bytes.to_vec());
             } else {
                 // 文件不存在或为空，使用占位符音效
@@ -95,6 +171,28 @@ impl AudioManager {
             }
         }
     }
+
+    /// 重新加载所有音效（用于在运行时应用用户自定义音效的变更）
+    pub fn reload_sounds(&mut self) {
+        self.sounds.clear();
+        self.load_sounds();
+    }
+
+    /// 从用户音效目录加载自定义音效，文件不存在或无法解码时返回 None
+    fn load_user_sound(sound_type: SoundType) -> Option<Vec<u8>> {
+        let path = user_sounds_dir()?.join(sound_type.file_name());
+        let bytes = fs::read(&path).ok()?;
+        if bytes.len() <= 44 {
+            return None;
+        }
+        match Decoder::new(Cursor::new(bytes.clone())) {
+            Ok(_) => Some(bytes),
+            Err(e) => {
+                crate::log_warn!("自定义音效无法解码，已忽略 {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
     
     /// 生成占位符音效（当真实文件不存在时使用）
     fn generate_placeholder_sound(sound_type: SoundType) -> Vec<u8> {
@@ -104,6 +202,7 @@ impl AudioManager {
             SoundType::Place => (400.0, 200, 0.6),
             SoundType::Invalid => (200.0, 300, 0.4),
             SoundType::Capture => (600.0, 400, 0.7),
+            SoundType::DoubleCapture => (300.0, 600, 0.8),
             SoundType::Win => (523.25, 800, 0.8),
             SoundType::Lose => (220.0, 600, 0.5),
             SoundType::Draw => (349.23, 500, 0.5),
@@ -171,11 +270,26 @@ impl AudioManager {
         
         if let Some(data) = self.sounds.get(&sound_type) {
             let cursor = Cursor::new(data.clone());
-            if let Ok(source) = Decoder::new(cursor) {
-                let _ = self.stream_handle.play_raw(source.convert_samples());
+            if let Ok(source) = Decoder::new(cursor)
+                && let Ok(sink) = Sink::try_new(&self.stream_handle)
+            {
+                sink.set_volume(self.volume);
+                sink.append(source);
+
+                // 每个音效使用独立的 Sink，短音效之间可以相互重叠
+                let mut playing = self.playing.borrow_mut();
+                playing.retain(|s| !s.empty());
+                playing.push(sink);
             }
         }
     }
+
+    /// 立即停止所有正在播放的音效（不影响背景音乐）
+    pub fn stop_all(&self) {
+        for sink in self.playing.borrow_mut().drain(..) {
+            sink.stop();
+        }
+    }
     
     /// 启用/禁用音效
     pub fn set_enabled(&mut self, enabled: bool) {
@@ -186,6 +300,64 @@ impl AudioManager {
     pub fn is_enabled(&self) -> bool {
         self.enabled
     }
+
+    /// 设置音效音量（0.0 - 1.0）
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = volume.clamp(0.0, 1.0);
+    }
+
+    /// 从头开始循环播放背景音乐，找不到或无法解码音乐文件时不播放
+    pub fn start_music(&mut self) {
+        self.stop_music();
+
+        let Some(path) = user_sounds_dir()
+            .and_then(|dir| MUSIC_FILES.iter().map(|name| dir.join(name)).find(|p| p.is_file()))
+        else {
+            return;
+        };
+        let source = match File::open(&path).map(BufReader::new) {
+            Ok(reader) => match Decoder::new(reader) {
+                Ok(source) => source,
+                Err(e) => {
+                    crate::log_warn!("背景音乐无法解码 {}: {}", path.display(), e);
+                    return;
+                }
+            },
+            Err(e) => {
+                crate::log_warn!("无法打开背景音乐 {}: {}", path.display(), e);
+                return;
+            }
+        };
+
+        match Sink::try_new(&self.stream_handle) {
+            Ok(sink) => {
+                sink.set_volume(self.music_volume);
+                sink.append(source.repeat_infinite());
+                self.music = Some(sink);
+            }
+            Err(e) => crate::log_warn!("无法播放背景音乐: {}", e),
+        }
+    }
+
+    /// 停止背景音乐
+    pub fn stop_music(&mut self) {
+        if let Some(sink) = self.music.take() {
+            sink.stop();
+        }
+    }
+
+    /// 背景音乐是否正在播放
+    pub fn is_music_playing(&self) -> bool {
+        self.music.is_some()
+    }
+
+    /// 设置背景音乐音量（0.0 - 1.0），与音效音量相互独立
+    pub fn set_music_volume(&mut self, volume: f32) {
+        self.music_volume = volume.clamp(0.0, 1.0);
+        if let Some(ref sink) = self.music {
+            sink.set_volume(self.music_volume);
+        }
+    }
 }
 
 impl Default for AudioManager {
@@ -205,12 +377,71 @@ impl SoundPlayer {
             audio: AudioManager::new(),
         }
     }
+
+    /// 不打开音频设备的播放器，所有播放调用均无效果
+    pub fn silent() -> Self {
+        Self { audio: None }
+    }
     
     pub fn play(&self, sound_type: SoundType) {
         if let Some(ref audio) = self.audio {
             audio.play(sound_type);
         }
     }
+
+    /// 停止所有正在播放的音效
+    pub fn stop_all(&self) {
+        if let Some(ref audio) = self.audio {
+            audio.stop_all();
+        }
+    }
+
+    /// 启用/禁用音效
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if let Some(ref mut audio) = self.audio {
+            audio.set_enabled(enabled);
+        }
+    }
+
+    /// 设置音效音量（0.0 - 1.0）
+    pub fn set_volume(&mut self, volume: f32) {
+        if let Some(ref mut audio) = self.audio {
+            audio.set_volume(volume);
+        }
+    }
+
+    /// 重新加载音效
+    pub fn reload_sounds(&mut self) {
+        if let Some(ref mut audio) = self.audio {
+            audio.reload_sounds();
+        }
+    }
+
+    /// 开始播放背景音乐
+    pub fn start_music(&mut self) {
+        if let Some(ref mut audio) = self.audio {
+            audio.start_music();
+        }
+    }
+
+    /// 停止背景音乐
+    pub fn stop_music(&mut self) {
+        if let Some(ref mut audio) = self.audio {
+            audio.stop_music();
+        }
+    }
+
+    /// 背景音乐是否正在播放
+    pub fn is_music_playing(&self) -> bool {
+        self.audio.as_ref().is_some_and(|audio| audio.is_music_playing())
+    }
+
+    /// 设置背景音乐音量（0.0 - 1.0）
+    pub fn set_music_volume(&mut self, volume: f32) {
+        if let Some(ref mut audio) = self.audio {
+            audio.set_music_volume(volume);
+        }
+    }
     
     pub fn click(&self) {
         self.play(SoundType::Click);
@@ -228,6 +459,10 @@ impl SoundPlayer {
         self.play(SoundType::Capture);
     }
     
+    pub fn double_capture(&self) {
+        self.play(SoundType::DoubleCapture);
+    }
+    
     pub fn win(&self) {
         self.play(SoundType::Win);
     }
@@ -1,20 +1,117 @@
 //! 棋盘定义与操作
 
-use crate::game::piece::{initial_pieces, Piece, Side};
+use crate::game::piece::{initial_pieces, Piece, PieceState, Side};
+use crate::game::rules::Move;
 use crate::game::{CapturedRecord, MoveRecord};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
 
-/// 棋盘大小（4x4）
+/// 默认棋盘大小（4x4）
 pub const BOARD_SIZE: u8 = 4;
 
+/// 最小棋盘大小（初始布局需要至少4行）
+pub const MIN_BOARD_SIZE: u8 = 4;
+
+/// 最大棋盘大小（坐标按 i8 计算，且棋子ID须在 u8 范围内）
+pub const MAX_BOARD_SIZE: u8 = 8;
+
+/// 正方形棋盘的对称变换数（4种旋转各自可再左右镜像）
+pub const SYMMETRY_COUNT: usize = 8;
+
+/// 四个正交方向：上、下、右、左
+pub const DIRECTIONS: [(i8, i8); 4] = [(0, 1), (0, -1), (1, 0), (-1, 0)];
+
 /// 棋盘
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// 派生的 `PartialEq` 为逐字段比较（包括棋子ID与顺序）；
+/// 只关心局面是否相同时请使用 [`Board::positions_equal`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Board {
+    /// 棋盘大小（每边交叉点数）
+    #[serde(default = "default_board_size")]
+    pub size: u8,
     /// 所有棋子
     pub pieces: Vec<Piece>,
+    /// 双方合法走法的缓存
+    #[serde(skip)]
+    move_cache: MoveCache,
+}
+
+/// 双方子力与机动性概况（见 [`Board::summary`]）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BoardSummary {
+    /// 黑方在盘棋子数
+    pub black_count: usize,
+    /// 白方在盘棋子数
+    pub white_count: usize,
+    /// 黑方合法走法数
+    pub black_moves: usize,
+    /// 白方合法走法数
+    pub white_moves: usize,
+}
+
+impl BoardSummary {
+    /// 某方在盘棋子数
+    pub fn count(&self, side: Side) -> usize {
+        match side {
+            Side::Black => self.black_count,
+            Side::White => self.white_count,
+        }
+    }
+
+    /// 某方合法走法数
+    pub fn moves(&self, side: Side) -> usize {
+        match side {
+            Side::Black => self.black_moves,
+            Side::White => self.white_moves,
+        }
+    }
+}
+
+fn default_board_size() -> u8 {
+    BOARD_SIZE
+}
+
+/// Zobrist 键：以 splitmix64 将（所属方，交叉点）编号散列为64位随机数
+fn zobrist_key(index: u64) -> u64 {
+    let mut z = index.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
 }
 
+/// 合法走法缓存
+///
+/// 以双方棋子的占位掩码为键：无论通过方法还是直接修改 `pieces` 改变了局面，
+/// 键都会随之改变，查询时发现不一致即重新生成；交叉点超过128个的棋盘不缓存
+#[derive(Debug, Default)]
+struct MoveCache(RefCell<Option<CachedMoves>>);
+
+#[derive(Debug)]
+struct CachedMoves {
+    /// 生成走法时的（黑方, 白方）占位掩码
+    key: (u128, u128),
+    /// 黑、白双方的合法走法，按需生成
+    moves: [Option<Vec<Move>>; 2],
+}
+
+impl Clone for MoveCache {
+    /// 克隆出的棋盘通常随即被修改，不复制缓存
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl PartialEq for MoveCache {
+    /// 缓存不影响局面，比较棋盘时忽略
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for MoveCache {}
+
 impl Default for Board {
     fn default() -> Self {
         Self::empty()
@@ -24,14 +121,191 @@ impl Default for Board {
 impl Board {
     /// 创建空棋盘
     pub fn empty() -> Self {
-        Self { pieces: Vec::new() }
+        Self::empty_sized(BOARD_SIZE)
+    }
+
+    /// 创建指定大小的空棋盘
+    pub fn empty_sized(size: u8) -> Self {
+        Self::with_pieces(size.clamp(MIN_BOARD_SIZE, MAX_BOARD_SIZE), Vec::new())
+    }
+
+    /// 由棋盘大小与棋子列表创建棋盘
+    pub fn with_pieces(size: u8, pieces: Vec<Piece>) -> Self {
+        Self {
+            size,
+            pieces,
+            move_cache: MoveCache::default(),
+        }
     }
 
     /// 创建初始棋盘
     pub fn initial() -> Self {
-        Self {
-            pieces: initial_pieces(),
+        Self::initial_sized(BOARD_SIZE)
+    }
+
+    /// 创建指定大小的初始棋盘
+    pub fn initial_sized(size: u8) -> Self {
+        let size = size.clamp(MIN_BOARD_SIZE, MAX_BOARD_SIZE);
+        Self::with_pieces(size, initial_pieces(size))
+    }
+
+    /// 检查两个棋盘的局面是否相同
+    ///
+    /// 只比较棋盘大小和在盘棋子的（所属方，位置），忽略棋子ID与 `pieces` 中的顺序
+    pub fn positions_equal(&self, other: &Board) -> bool {
+        self.size == other.size && self.to_notation() == other.to_notation()
+    }
+
+    /// 导出为局面记谱字符串
+    ///
+    /// 格式：`.` 表示空点，`B` 表示黑棋，`W` 表示白棋，
+    /// 每行之间以 `/` 分隔，从最上一行开始，
+    /// 例如 4x4 初始局面为 `WWWW/W..W/B..B/BBBB`
+    pub fn to_notation(&self) -> String {
+        (0..self.size)
+            .rev()
+            .map(|y| {
+                (0..self.size)
+                    .map(|x| match self.piece_at(x, y).map(|p| p.side) {
+                        Some(Side::Black) => 'B',
+                        Some(Side::White) => 'W',
+                        None => '.',
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// 从局面记谱字符串创建棋盘
+    ///
+    /// 格式见 [`Board::to_notation`]，棋盘大小由行数决定（4至8行），每行格子数须与行数相同；
+    /// 棋子ID按先黑后白、自下而上的顺序分配
+    pub fn from_notation(notation: &str) -> Result<Self> {
+        let rows: Vec<&str> = notation.trim().split('/').collect();
+        let size = rows.len();
+        if size < MIN_BOARD_SIZE as usize || size > MAX_BOARD_SIZE as usize {
+            anyhow::bail!("记谱行数错误: 需要 {} 至 {} 行，实际 {} 行", MIN_BOARD_SIZE, MAX_BOARD_SIZE, size);
         }
+        let size = size as u8;
+
+        let mut cells = Vec::new();
+        for (row_idx, row) in rows.iter().enumerate() {
+            let y = size - 1 - row_idx as u8;
+            let chars: Vec<char> = row.chars().collect();
+            if chars.len() != size as usize {
+                anyhow::bail!("记谱第 {} 行格子数错误: \"{}\"", row_idx + 1, row);
+            }
+            for (x, c) in chars.into_iter().enumerate() {
+                let side = match c {
+                    '.' => continue,
+                    'B' => Side::Black,
+                    'W' => Side::White,
+                    _ => anyhow::bail!("记谱包含非法字符: '{}'", c),
+                };
+                cells.push((side, (x as u8, y)));
+            }
+        }
+
+        // 先黑后白、自下而上分配棋子ID，与初始布局保持一致
+        cells.sort_by_key(|&(side, (x, y))| (side == Side::White, y, x));
+        let pieces = cells
+            .into_iter()
+            .zip(1u8..)
+            .map(|((side, position), id)| Piece {
+                id,
+                side,
+                position,
+                state: PieceState::Idle,
+                active: true,
+            })
+            .collect();
+
+        Ok(Self::with_pieces(size, pieces))
+    }
+
+    /// 顺时针旋转90度后的棋盘（棋子ID不变）
+    pub fn rotate90(&self) -> Self {
+        let last = self.size - 1;
+        self.transformed(|(x, y)| (y, last - x))
+    }
+
+    /// 左右镜像后的棋盘（棋子ID不变）
+    pub fn mirror_horizontal(&self) -> Self {
+        let last = self.size - 1;
+        self.transformed(|(x, y)| (last - x, y))
+    }
+
+    /// 上下镜像后的棋盘（棋子ID不变）
+    pub fn mirror_vertical(&self) -> Self {
+        let last = self.size - 1;
+        self.transformed(|(x, y)| (x, last - y))
+    }
+
+    /// 交叉点关于棋盘中心的对称点（旋转180度）
+    pub fn center_mirror(&self, (x, y): (u8, u8)) -> (u8, u8) {
+        let last = self.size - 1;
+        (last - x, last - y)
+    }
+
+    /// 棋盘的全部8种对称变换（4种旋转及各自的左右镜像），第一个为原局面
+    ///
+    /// 第 i 个棋盘即按 [`Board::symmetric_square`]`(i, ..)` 移动所有棋子
+    pub fn symmetries(&self) -> Vec<Self> {
+        (0..SYMMETRY_COUNT)
+            .map(|index| self.transformed(|pos| self.symmetric_square(index, pos)))
+            .collect()
+    }
+
+    /// 第 index 种对称变换下交叉点的位置：先顺时针旋转 index / 2 次90度，
+    /// index 为奇数时再左右镜像
+    pub fn symmetric_square(&self, index: usize, (mut x, mut y): (u8, u8)) -> (u8, u8) {
+        let last = self.size - 1;
+        for _ in 0..index / 2 {
+            (x, y) = (y, last - x);
+        }
+        if index % 2 == 1 {
+            x = last - x;
+        }
+        (x, y)
+    }
+
+    /// 按坐标变换移动所有棋子
+    fn transformed(&self, f: impl Fn((u8, u8)) -> (u8, u8)) -> Self {
+        let pieces = self
+            .pieces
+            .iter()
+            .map(|piece| Piece { position: f(piece.position), ..piece.clone() })
+            .collect();
+        Self::with_pieces(self.size, pieces)
+    }
+
+    /// 局面的 Zobrist 哈希：只取决于在盘棋子的（所属方，位置），忽略棋子ID与顺序
+    ///
+    /// 每个（所属方，交叉点）的键由固定种子推导，跨进程保持一致，可用于开局库
+    pub fn zobrist_hash(&self) -> u64 {
+        self.pieces
+            .iter()
+            .filter(|p| p.active)
+            .map(|piece| {
+                let (x, y) = piece.position;
+                let square = y as u64 * self.size as u64 + x as u64;
+                let side = match piece.side {
+                    Side::Black => 0,
+                    Side::White => 1,
+                };
+                zobrist_key(self.size as u64 * self.size as u64 * side + square)
+            })
+            .fold(self.size as u64, |hash, key| hash ^ key)
+    }
+
+    /// 对称等价类的哈希：全部8种对称变换中最小的 Zobrist 哈希（开局库按此匹配局面）
+    pub fn canonical_hash(&self) -> u64 {
+        self.symmetries()
+            .iter()
+            .map(Board::zobrist_hash)
+            .min()
+            .unwrap_or_default()
     }
 
     /// 获取指定位置的棋子（如果有）
@@ -55,8 +329,9 @@ impl Board {
     }
 
     /// 检查位置是否在棋盘内
-    pub fn is_valid_pos(x: i8, y: i8) -> bool {
-        x >= 0 && x < BOARD_SIZE as i8 && y >= 0 && y < BOARD_SIZE as i8
+    pub fn is_valid_pos(&self, x: i8, y: i8) -> bool {
+        let size = self.size as i8;
+        (0..size).contains(&x) && (0..size).contains(&y)
     }
 
     /// 检查位置是否为空
@@ -64,6 +339,114 @@ impl Board {
         self.piece_at(x, y).is_none()
     }
 
+    /// 获取指定棋子的所有合法目标位置（上下左右相邻的空点）
+    ///
+    /// 棋子不存在或已被吃掉时返回空列表
+    pub fn legal_moves_for(&self, piece_id: u8) -> Vec<(u8, u8)> {
+        let Some(piece) = self.piece_by_id(piece_id).filter(|p| p.active) else {
+            return Vec::new();
+        };
+        let (x, y) = piece.position;
+
+        DIRECTIONS
+            .iter()
+            .map(|&(dx, dy)| (x as i8 + dx, y as i8 + dy))
+            .filter(|&(nx, ny)| self.is_valid_pos(nx, ny) && self.is_empty(nx as u8, ny as u8))
+            .map(|(nx, ny)| (nx as u8, ny as u8))
+            .collect()
+    }
+
+    /// 获取某方所有合法走法（带缓存）
+    pub fn valid_moves(&self, side: Side) -> Vec<Move> {
+        self.with_valid_moves(side, <[Move]>::to_vec)
+    }
+
+    /// 某方是否有合法走法（带缓存）
+    pub fn has_valid_moves(&self, side: Side) -> bool {
+        self.with_valid_moves(side, |moves| !moves.is_empty())
+    }
+
+    /// 以某方的合法走法调用 `f`，局面未变时复用缓存
+    fn with_valid_moves<R>(&self, side: Side, f: impl FnOnce(&[Move]) -> R) -> R {
+        let Some(key) = self.occupancy() else {
+            return f(&self.generate_moves(side));
+        };
+
+        let mut cache = self.move_cache.0.borrow_mut();
+        let entry = match cache.as_mut() {
+            Some(entry) if entry.key == key => entry,
+            _ => cache.insert(CachedMoves { key, moves: [None, None] }),
+        };
+        let index = match side {
+            Side::Black => 0,
+            Side::White => 1,
+        };
+        f(entry.moves[index].get_or_insert_with(|| self.generate_moves(side)))
+    }
+
+    /// 生成某方所有合法走法（不使用缓存）
+    fn generate_moves(&self, side: Side) -> Vec<Move> {
+        self.active_pieces_of(side)
+            .iter()
+            .flat_map(|piece| {
+                let from = piece.position;
+                self.legal_moves_for(piece.id).into_iter().map(move |to| (from, to))
+            })
+            .collect()
+    }
+
+    /// 双方在盘棋子的占位掩码（黑方, 白方），交叉点超过128个时返回 None
+    fn occupancy(&self) -> Option<(u128, u128)> {
+        let size = self.size as u32;
+        if size * size > u128::BITS {
+            return None;
+        }
+        let mut masks = (0u128, 0u128);
+        for piece in self.pieces.iter().filter(|p| p.active) {
+            let (x, y) = piece.position;
+            let bit = 1u128 << (y as u32 * size + x as u32);
+            match piece.side {
+                Side::Black => masks.0 |= bit,
+                Side::White => masks.1 |= bit,
+            }
+        }
+        Some(masks)
+    }
+
+    /// 一次遍历统计双方棋子数与合法走法数
+    ///
+    /// 结果与分别调用 [`Board::count_active`] 和 [`Board::valid_moves`] 一致，
+    /// 但不生成走法列表，适合在搜索中频繁调用
+    pub fn summary(&self) -> BoardSummary {
+        let occupied = self.occupancy().map(|(black, white)| black | white);
+        let mut summary = BoardSummary::default();
+        for piece in self.pieces.iter().filter(|p| p.active) {
+            let (x, y) = piece.position;
+            let moves = DIRECTIONS
+                .iter()
+                .map(|&(dx, dy)| (x as i8 + dx, y as i8 + dy))
+                .filter(|&(nx, ny)| {
+                    self.is_valid_pos(nx, ny)
+                        && match occupied {
+                            Some(mask) => mask & (1u128 << (ny as u32 * self.size as u32 + nx as u32)) == 0,
+                            None => self.is_empty(nx as u8, ny as u8),
+                        }
+                })
+                .count();
+            match piece.side {
+                Side::Black => {
+                    summary.black_count += 1;
+                    summary.black_moves += moves;
+                }
+                Side::White => {
+                    summary.white_count += 1;
+                    summary.white_moves += moves;
+                }
+            }
+        }
+        summary
+    }
+
     /// 获取某方的所有活跃棋子
     pub fn active_pieces_of(&self, side: Side) -> Vec<&Piece> {
         self.pieces
@@ -125,12 +508,37 @@ impl Board {
         })
     }
 
+    /// 预演一步移动会吃掉的棋子ID（不修改棋盘）
+    ///
+    /// 起始位置没有棋子或棋子已被吃掉时返回空列表
+    pub fn captures_if_moved(&self, from: (u8, u8), to: (u8, u8)) -> Vec<u8> {
+        crate::game::rules::captures_after_move(&mut self.clone(), from, to)
+    }
+
+    /// 模拟 side 方的一步移动会吃掉的棋子ID（不修改棋盘）
+    ///
+    /// 走法不合法时返回 None，合法但不吃子时返回空列表
+    pub fn simulate_capture(&self, from: (u8, u8), to: (u8, u8), side: Side) -> Option<Vec<u8>> {
+        crate::game::rules::is_valid_move(self, from, to, side).then(|| self.captures_if_moved(from, to))
+    }
+
     /// 悔棋（撤销移动）
+    ///
+    /// 记录中的棋子不存在或不在目标位置时返回错误，棋盘保持不变
     pub fn undo_move(&mut self, record: &MoveRecord) -> Result<()> {
+        if !self
+            .piece_by_id(record.piece_id)
+            .is_some_and(|piece| piece.active && piece.position == record.to)
+        {
+            anyhow::bail!("行棋记录与棋盘不一致: 棋子 {} 不在 {:?}", record.piece_id, record.to);
+        }
+        if let Some(captured) = record.captured.iter().find(|c| self.piece_by_id(c.piece_id).is_none()) {
+            anyhow::bail!("行棋记录与棋盘不一致: 被吃棋子 {} 不存在", captured.piece_id);
+        }
+
         // 恢复移动的棋子位置
         if let Some(piece) = self.piece_by_id_mut(record.piece_id) {
             piece.position = record.from;
-            piece.active = true;
         }
 
         // 恢复被吃的棋子（包括位置）
@@ -144,20 +552,32 @@ impl Board {
         Ok(())
     }
 
+    /// 按行棋记录重新执行一步（不重新计算吃子，用于回放）
+    pub fn apply_record(&mut self, record: &MoveRecord) {
+        if let Some(piece) = self.piece_by_id_mut(record.piece_id) {
+            piece.position = record.to;
+        }
+        for captured_record in &record.captured {
+            if let Some(piece) = self.piece_by_id_mut(captured_record.piece_id) {
+                piece.active = false;
+            }
+        }
+    }
+
     /// 获取某位置在屏幕上的坐标（用于渲染）
     /// 
     /// 棋子放在交叉点上（线的交点），而不是格子中间
     /// 
     /// 参数:
     /// - board_rect: 棋盘在屏幕上的矩形区域 (x, y, width, height)
-    /// - pos: 棋盘坐标 (x, y)，范围 0-3
+    /// - pos: 棋盘坐标 (x, y)，范围 0..size
     /// 
     /// 返回: 屏幕坐标 (x, y)
-    pub fn board_to_screen(board_rect: (f32, f32, f32, f32), pos: (u8, u8)) -> (f32, f32) {
+    pub fn board_to_screen(&self, board_rect: (f32, f32, f32, f32), pos: (u8, u8)) -> (f32, f32) {
         let (bx, by, bw, bh) = board_rect;
-        // 3x3格子，4x4交叉点，格子大小为 width / 3
-        let cell_w = bw / (BOARD_SIZE - 1) as f32;
-        let cell_h = bh / (BOARD_SIZE - 1) as f32;
+        // (N-1)x(N-1)格子，NxN交叉点，格子大小为 width / (N-1)
+        let cell_w = bw / (self.size - 1) as f32;
+        let cell_h = bh / (self.size - 1) as f32;
         
         // (0,0) 在左下角，棋子放在交叉点上
         let screen_x = bx + pos.0 as f32 * cell_w;
@@ -177,20 +597,21 @@ impl Board {
     /// 
     /// 返回: 可选的棋盘坐标
     pub fn screen_to_board(
+        &self,
         board_rect: (f32, f32, f32, f32),
         screen_pos: (f32, f32),
         tolerance: f32,
     ) -> Option<(u8, u8)> {
         let (bx, by, bw, bh) = board_rect;
-        // 3x3格子，4x4交叉点，格子大小为 width / 3
-        let cell_w = bw / (BOARD_SIZE - 1) as f32;
-        let cell_h = bh / (BOARD_SIZE - 1) as f32;
+        // (N-1)x(N-1)格子，NxN交叉点，格子大小为 width / (N-1)
+        let cell_w = bw / (self.size - 1) as f32;
+        let cell_h = bh / (self.size - 1) as f32;
 
         // 计算相对于棋盘左下角的坐标
         let rel_x = screen_pos.0 - bx;
         let rel_y = bh - (screen_pos.1 - by); // 翻转Y轴
 
-        // 计算最近的交叉点索引（0-3）
+        // 计算最近的交叉点索引
         let board_x = (rel_x / cell_w).round() as i32;
         let board_y = (rel_y / cell_h).round() as i32;
 
@@ -203,12 +624,276 @@ impl Board {
         
         let max_dist = cell_w.min(cell_h) * tolerance;
 
-        if dist_x <= max_dist && dist_y <= max_dist {
-            if Self::is_valid_pos(board_x as i8, board_y as i8) {
-                return Some((board_x as u8, board_y as u8));
-            }
+        if dist_x <= max_dist && dist_y <= max_dist && self.is_valid_pos(board_x as i8, board_y as i8) {
+            return Some((board_x as u8, board_y as u8));
         }
 
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notation_round_trips_for_all_sizes() {
+        for size in MIN_BOARD_SIZE..=MAX_BOARD_SIZE {
+            let board = Board::initial_sized(size);
+            let notation = board.to_notation();
+            let parsed = Board::from_notation(&notation).unwrap();
+            assert_eq!(parsed.size, size);
+            assert!(parsed.positions_equal(&board));
+            assert_eq!(parsed.to_notation(), notation);
+        }
+        let notation = "..W./B.W./.B../W..B";
+        assert_eq!(Board::from_notation(notation).unwrap().to_notation(), notation);
+    }
+
+    #[test]
+    fn notation_rejects_bad_input() {
+        let square = |size: usize| vec![".".repeat(size); size].join("/");
+        for notation in [
+            square(3),
+            square(MAX_BOARD_SIZE as usize + 1),
+            square(200),
+            "..../..../.../....".to_string(),
+            "..../..../..X./....".to_string(),
+            String::new(),
+        ] {
+            assert!(Board::from_notation(&notation).is_err(), "{notation:?}");
+        }
+    }
+
+    #[test]
+    fn single_piece_mode_counts_pieces_anywhere_on_the_board() {
+        // 剩一子的一方无论在棋盘边上还是内部都进入单子状态
+        for (notation, single) in [
+            ("WWWW/W..W/B..B/BBBB", false),
+            ("WW../..../.B../B...", false),
+            ("WW../..../..../B...", true),
+            ("..../.W../..B./BB..", true),
+        ] {
+            let board = Board::from_notation(notation).unwrap();
+            assert_eq!(board.is_single_piece_mode(), single, "{notation}");
+        }
+    }
+
+    #[test]
+    fn all_symmetries_share_one_canonical_hash() {
+        // 无对称性的局面：8种变换各不相同
+        let board = Board::from_notation("WW../B.W./.B../B..B").unwrap();
+        let symmetries = board.symmetries();
+        assert_eq!(symmetries.len(), SYMMETRY_COUNT);
+        let mut hashes: Vec<u64> = symmetries.iter().map(Board::zobrist_hash).collect();
+        hashes.sort_unstable();
+        hashes.dedup();
+        assert_eq!(hashes.len(), SYMMETRY_COUNT);
+
+        let canonical = board.canonical_hash();
+        assert_eq!(canonical, hashes[0]);
+        for symmetric in &symmetries {
+            assert_eq!(symmetric.canonical_hash(), canonical, "{}", symmetric.to_notation());
+        }
+        assert_ne!(Board::initial().canonical_hash(), canonical);
+    }
+
+    #[test]
+    fn symmetries_match_the_single_transforms() {
+        let board = Board::from_notation("WW.../B.W../.B.../...../B...B").unwrap();
+        let symmetries = board.symmetries();
+        assert!(symmetries[0].positions_equal(&board));
+        assert!(symmetries[1].positions_equal(&board.mirror_horizontal()));
+        assert!(symmetries[2].positions_equal(&board.rotate90()));
+        assert!(symmetries[4].positions_equal(&board.rotate90().rotate90()));
+        assert!(symmetries[5].positions_equal(&board.mirror_vertical()));
+        for (index, symmetric) in symmetries.iter().enumerate() {
+            for piece in &board.pieces {
+                let moved = symmetric.piece_by_id(piece.id).unwrap();
+                assert_eq!(moved.position, board.symmetric_square(index, piece.position));
+            }
+        }
+        // 旋转四次回到原局面
+        assert_eq!(board.rotate90().rotate90().rotate90().rotate90().to_notation(), board.to_notation());
+    }
+
+    #[test]
+    fn positions_equal_ignores_piece_order_and_ids() {
+        let board = Board::from_notation("W..W/.B../..W./B..B").unwrap();
+        let mut reordered = board.clone();
+        reordered.pieces.reverse();
+        for (piece, id) in reordered.pieces.iter_mut().zip(10u8..) {
+            piece.id = id;
+        }
+        assert_ne!(reordered, board);
+        assert!(reordered.positions_equal(&board));
+        assert!(board.positions_equal(&reordered));
+
+        // 被吃掉的棋子不参与比较
+        let mut with_captured = board.clone();
+        with_captured.pieces.push(Piece { active: false, ..Piece::new(99, Side::White, 1, 1) });
+        assert!(with_captured.positions_equal(&board));
+
+        // 棋子换了位置、换了所属方或棋盘大小不同时局面不同
+        let mut moved = board.clone();
+        moved.piece_at_mut(1, 2).unwrap().position = (1, 1);
+        assert!(!moved.positions_equal(&board));
+        let mut recoloured = board.clone();
+        recoloured.piece_at_mut(1, 2).unwrap().side = Side::White;
+        assert!(!recoloured.positions_equal(&board));
+        assert!(!Board::empty_sized(5).positions_equal(&Board::empty()));
+    }
+
+    #[test]
+    fn legal_moves_for_lists_empty_neighbours_on_the_board() {
+        // 黑子 1 在 (0,1)、2 在 (1,2)，白子 3 在 (1,1)、4 在角上 (0,3)
+        let mut board = Board::from_notation("W.../.B../BW../....").unwrap();
+        let moves = |board: &Board, id: u8| {
+            let mut moves = board.legal_moves_for(id);
+            moves.sort_unstable();
+            moves
+        };
+        // 边上的棋子：右边被白子挡住，左边出界
+        assert_eq!(moves(&board, 1), vec![(0, 0), (0, 2)]);
+        // 下方被白子挡住
+        assert_eq!(moves(&board, 2), vec![(0, 2), (1, 3), (2, 2)]);
+        // 上、左均被黑子挡住
+        assert_eq!(moves(&board, 3), vec![(1, 0), (2, 1)]);
+        // 角上的棋子只有两个方向
+        assert_eq!(moves(&board, 4), vec![(0, 2), (1, 3)]);
+
+        // 不存在或已被吃掉的棋子没有走法
+        assert!(board.legal_moves_for(99).is_empty());
+        board.piece_by_id_mut(2).unwrap().active = false;
+        assert!(board.legal_moves_for(2).is_empty());
+        // 被吃掉的棋子不再挡路
+        assert_eq!(moves(&board, 3), vec![(1, 0), (1, 2), (2, 1)]);
+    }
+
+    #[test]
+    fn move_cache_follows_moves_and_undo() {
+        // 缓存的走法与不带缓存重新生成的走法一致
+        fn assert_fresh(board: &Board) {
+            for side in [Side::Black, Side::White] {
+                let mut cached = board.valid_moves(side);
+                let mut fresh = board.generate_moves(side);
+                cached.sort_unstable();
+                fresh.sort_unstable();
+                assert_eq!(cached, fresh, "{side:?} @ {}", board.to_notation());
+                assert_eq!(board.has_valid_moves(side), !fresh.is_empty());
+            }
+        }
+
+        let mut board = Board::initial();
+        assert_fresh(&board);
+        let initial_black = board.valid_moves(Side::Black);
+
+        // 最后一步吃掉白子，被吃棋子让出的交叉点也要出现在新走法中
+        let moves = [((0, 1), (1, 1)), ((0, 2), (0, 1)), ((1, 1), (1, 2)), ((3, 2), (2, 2)), ((1, 0), (1, 1))];
+        let mut records = Vec::new();
+        for (i, (from, to)) in moves.into_iter().enumerate() {
+            let side = if i % 2 == 0 { Side::Black } else { Side::White };
+            records.push(board.execute_move(from, to, side).unwrap());
+            assert_fresh(&board);
+        }
+        assert!(!records.last().unwrap().captured.is_empty());
+
+        while let Some(record) = records.pop() {
+            board.undo_move(&record).unwrap();
+            assert_fresh(&board);
+        }
+        assert_eq!(board.valid_moves(Side::Black), initial_black);
+
+        // 直接修改棋子同样使缓存失效
+        board.piece_at_mut(0, 1).unwrap().active = false;
+        assert_fresh(&board);
+        assert!(board.valid_moves(Side::Black).iter().any(|&(_, to)| to == (0, 1)));
+    }
+
+    #[test]
+    fn captures_if_moved_matches_the_captures_of_the_played_move() {
+        // 沿一局确定的对局（优先吃子）检查每个局面的每一步合法走法
+        let mut board = Board::initial();
+        let mut side = Side::Black;
+        let mut captures_seen = 0;
+        for _ in 0..30 {
+            let moves = board.valid_moves(side);
+            if moves.is_empty() || board.count_active(Side::Black) < 2 || board.count_active(Side::White) < 2 {
+                break;
+            }
+            let before = board.clone();
+            for &(from, to) in &moves {
+                let predicted = board.captures_if_moved(from, to);
+                assert_eq!(board, before);
+
+                let mut played = board.clone();
+                let record = played.execute_move(from, to, side).unwrap();
+                let captured: Vec<u8> = record.captured.iter().map(|c| c.piece_id).collect();
+                assert_eq!(predicted, captured, "{:?} -> {:?} @ {}", from, to, board.to_notation());
+                captures_seen += captured.len();
+            }
+            let &(from, to) = moves
+                .iter()
+                .find(|&&(from, to)| !board.captures_if_moved(from, to).is_empty())
+                .unwrap_or(&moves[moves.len() / 2]);
+            board.execute_move(from, to, side).unwrap();
+            side = side.opposite();
+        }
+        assert!(captures_seen > 0);
+
+        // 起始位置没有棋子时不吃子
+        assert!(Board::initial().captures_if_moved((1, 1), (1, 2)).is_empty());
+    }
+
+    #[test]
+    fn simulate_capture_reports_ids_without_changing_the_board() {
+        // 黑子 1 在 (0,0)、2 在 (1,1)，白子 3 在 (2,0)、4 在 (3,3)
+        let board = Board::from_notation("...W/..../.B../B.W.").unwrap();
+        let before = board.clone();
+
+        // 黑子 2 走到 (1,0)，与黑子 1 二比一吃掉白子 3
+        assert_eq!(board.simulate_capture((1, 1), (1, 0), Side::Black), Some(vec![3]));
+        // 不吃子的合法走法
+        assert_eq!(board.simulate_capture((1, 1), (1, 2), Side::Black), Some(Vec::new()));
+        // 不合法的走法：不是本方棋子、不相邻、起点无子
+        assert_eq!(board.simulate_capture((1, 1), (1, 0), Side::White), None);
+        assert_eq!(board.simulate_capture((1, 1), (3, 1), Side::Black), None);
+        assert_eq!(board.simulate_capture((2, 2), (2, 1), Side::Black), None);
+
+        assert_eq!(board, before);
+        assert_eq!(board.to_notation(), before.to_notation());
+    }
+
+    #[test]
+    fn summary_matches_individual_counts() {
+        let mut captured = Board::initial();
+        captured.pieces[0].active = false;
+        let positions = [
+            Board::initial(),
+            Board::initial_sized(6),
+            Board::empty(),
+            captured,
+            Board::from_notation("...W/..../.B../B.W.").unwrap(),
+            // 白方单子被困在角上
+            Board::from_notation("WB../B.../..../....").unwrap(),
+        ];
+        for board in positions {
+            let summary = board.summary();
+            for side in [Side::Black, Side::White] {
+                assert_eq!(summary.count(side), board.count_active(side), "{}", board.to_notation());
+                assert_eq!(summary.moves(side), board.valid_moves(side).len(), "{}", board.to_notation());
+            }
+        }
+
+        let summary = Board::from_notation("WB../B.../..../....").unwrap().summary();
+        assert_eq!((summary.black_count, summary.white_count), (2, 1));
+        assert_eq!((summary.black_moves, summary.white_moves), (4, 0));
+    }
+
+    #[test]
+    fn sized_constructors_clamp_to_supported_range() {
+        assert_eq!(Board::empty_sized(2).size, MIN_BOARD_SIZE);
+        assert_eq!(Board::empty_sized(200).size, MAX_BOARD_SIZE);
+        assert_eq!(Board::initial_sized(u8::MAX).size, MAX_BOARD_SIZE);
+    }
+}
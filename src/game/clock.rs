@@ -0,0 +1,155 @@
+//! 对局计时：双方各自累计的思考用时
+//!
+//! 默认只作显示用途，不影响规则；启用限时模式后用完时间的一方判负。
+//! 悔棋不回退已累计的用时
+
+use crate::game::piece::Side;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// 限时模式：每方的基本用时与每步加秒
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeControl {
+    /// 每方基本用时（秒）
+    pub base_secs: u32,
+    /// 每走完一步为该方增加的用时（秒）
+    pub increment_secs: u32,
+}
+
+impl Default for TimeControl {
+    fn default() -> Self {
+        Self { base_secs: 5 * 60, increment_secs: 0 }
+    }
+}
+
+/// 双方计时器
+#[derive(Debug, Clone, Default)]
+pub struct GameClock {
+    /// 限时模式（None 表示不限时）
+    time_control: Option<TimeControl>,
+    /// 黑方累计用时
+    black: Duration,
+    /// 白方累计用时
+    white: Duration,
+    /// 黑方累计获得的加秒
+    black_bonus: Duration,
+    /// 白方累计获得的加秒
+    white_bonus: Duration,
+    /// 正在计时的一方及上次结算的时刻（None 表示暂停）
+    running: Option<(Side, Instant)>,
+}
+
+impl GameClock {
+    /// 创建清零且暂停的计时器
+    pub fn new(time_control: Option<TimeControl>) -> Self {
+        Self { time_control, ..Self::default() }
+    }
+
+    /// 将正在计时一方的用时结算到 now，然后改由 side 计时（None 表示暂停）
+    pub fn update(&mut self, side: Option<Side>, now: Instant) {
+        if let Some((running, since)) = self.running {
+            *self.elapsed_mut(running) += now.saturating_duration_since(since);
+        }
+        self.running = side.map(|side| (side, now));
+    }
+
+    /// 某方截至上次结算的累计用时
+    pub fn elapsed(&self, side: Side) -> Duration {
+        match side {
+            Side::Black => self.black,
+            Side::White => self.white,
+        }
+    }
+
+    /// 正在计时的一方
+    pub fn running(&self) -> Option<Side> {
+        self.running.map(|(side, _)| side)
+    }
+
+    /// 当前的限时模式
+    pub fn time_control(&self) -> Option<TimeControl> {
+        self.time_control
+    }
+
+    /// 某方走完一步后按限时模式加秒（不限时时忽略）
+    pub fn add_increment(&mut self, side: Side) {
+        let Some(control) = self.time_control else {
+            return;
+        };
+        let increment = Duration::from_secs(control.increment_secs as u64);
+        match side {
+            Side::Black => self.black_bonus += increment,
+            Side::White => self.white_bonus += increment,
+        }
+    }
+
+    /// 某方剩余用时，不限时时返回 None
+    pub fn remaining(&self, side: Side) -> Option<Duration> {
+        let control = self.time_control?;
+        let bonus = match side {
+            Side::Black => self.black_bonus,
+            Side::White => self.white_bonus,
+        };
+        let total = Duration::from_secs(control.base_secs as u64) + bonus;
+        Some(total.saturating_sub(self.elapsed(side)))
+    }
+
+    /// 已用完时间的一方（不限时时始终为 None）
+    pub fn flagged(&self) -> Option<Side> {
+        [Side::Black, Side::White]
+            .into_iter()
+            .find(|&side| self.remaining(side) == Some(Duration::ZERO))
+    }
+
+    fn elapsed_mut(&mut self, side: Side) -> &mut Duration {
+        match side {
+            Side::Black => &mut self.black,
+            Side::White => &mut self.white,
+        }
+    }
+}
+
+/// 格式化用时为 `分:秒`
+pub fn format_clock(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn side_that_runs_out_of_time_is_flagged() {
+        let control = TimeControl { base_secs: 10, increment_secs: 2 };
+        let mut clock = GameClock::new(Some(control));
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        clock.update(Some(Side::Black), start);
+        clock.update(Some(Side::White), at(9));
+        clock.add_increment(Side::Black);
+        assert_eq!(clock.remaining(Side::Black), Some(Duration::from_secs(3)));
+        assert_eq!(clock.flagged(), None);
+
+        // 白方用掉全部 10 秒
+        clock.update(Some(Side::Black), at(19));
+        assert_eq!(clock.remaining(Side::White), Some(Duration::ZERO));
+        assert_eq!(clock.flagged(), Some(Side::White));
+
+        // 超时后剩余用时不为负
+        clock.update(None, at(30));
+        assert_eq!(clock.remaining(Side::Black), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn unlimited_clock_never_flags() {
+        let mut clock = GameClock::new(None);
+        let start = Instant::now();
+        clock.update(Some(Side::Black), start);
+        clock.update(None, start + Duration::from_secs(3600));
+        assert_eq!(clock.elapsed(Side::Black), Duration::from_secs(3600));
+        assert_eq!(clock.remaining(Side::Black), None);
+        assert_eq!(clock.flagged(), None);
+    }
+}
@@ -3,20 +3,29 @@
 pub mod ai;
 pub mod audio;
 pub mod board;
+pub mod clock;
+pub mod opening;
 pub mod piece;
 pub mod rules;
+pub mod replay;
 pub mod save;
+pub mod settings;
 pub mod state;
 
 use crate::game::board::Board;
+use crate::game::clock::GameClock;
 use crate::game::piece::Side;
-use crate::game::rules::{check_game_end, calculate_captures};
+use crate::game::ai::AiPlayer;
+use crate::game::rules::{RuleSet, SetupError, StandardRules};
+use crate::game::save::{is_initial_position, SaveMetadata};
+use crate::game::settings::{GameSettings, DEMO_NO_CAPTURE_DRAW_LIMIT, REPETITION_DRAW_COUNT};
 use crate::game::state::GameEvent;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
 
 /// 被吃棋子的记录
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CapturedRecord {
     /// 棋子ID
     pub piece_id: u8,
@@ -25,7 +34,7 @@ pub struct CapturedRecord {
 }
 
 /// 移动记录（用于悔棋）
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct MoveRecord {
     /// 移动的棋子ID
     pub piece_id: u8,
@@ -56,7 +65,7 @@ pub struct Game {
     pub move_history: Vec<MoveRecord>,
     /// AI难度等级 (1-5)
     pub ai_level: u8,
-    /// 当前选中的棋子（仅在PieceSelected状态下有效）
+    /// 当前选中的棋子（仅在已选中/拖拽中/等待点击目标点状态下有效）
     #[serde(skip)]
     pub selected_piece: Option<SelectedPiece>,
     /// 当前正在移动的棋子动画信息
@@ -67,6 +76,60 @@ pub struct Game {
     pub last_captured: Vec<u8>,
     /// 游戏结果（如果已结束）
     pub last_result: Option<GameResult>,
+    /// 对局结束的原因（从结果无法推知原因的旧存档为 None）
+    #[serde(default)]
+    pub end_reason: Option<GameEndReason>,
+    /// 当前结果是否由认输或议和产生，而非行棋所致（悔棋时只撤销该结果，不回退棋步）
+    #[serde(default, alias = "resigned")]
+    pub ended_without_move: bool,
+    /// 游戏设置
+    #[serde(default)]
+    pub settings: GameSettings,
+    /// 演示模式：双方均由电脑行棋（黑方占用玩家一方的位置）
+    #[serde(skip)]
+    pub demo: bool,
+    /// 对局使用的规则集（默认为标准规则）
+    #[serde(skip, default = "standard_rules")]
+    pub rules: Box<dyn RuleSet>,
+    /// 本局开始（或加载）的时刻，用于统计对局用时
+    #[serde(skip)]
+    pub started_at: Option<Instant>,
+    /// 双方思考用时
+    #[serde(skip)]
+    pub clock: GameClock,
+    /// 事件监听器（每次处理完事件后调用）
+    #[serde(skip)]
+    event_listener: Option<EventListener>,
+}
+
+/// 默认规则集
+fn standard_rules() -> Box<dyn RuleSet> {
+    Box::new(StandardRules)
+}
+
+/// 事件监听回调：参数为刚处理的事件和处理后的状态
+type EventCallback = Box<dyn FnMut(&GameEvent, &GameState)>;
+
+/// 事件监听器（包装回调以便 Game 保持 Debug）
+struct EventListener(EventCallback);
+
+impl std::fmt::Debug for EventListener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("EventListener")
+    }
+}
+
+/// 对局统计（用于结束对话框）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GameStats {
+    /// 已走步数（半回合）
+    pub moves: usize,
+    /// 黑方吃掉的棋子数
+    pub black_captures: usize,
+    /// 白方吃掉的棋子数
+    pub white_captures: usize,
+    /// 对局用时
+    pub elapsed: Duration,
 }
 
 /// 待执行的移动（用于动画）
@@ -90,6 +153,14 @@ impl Default for Game {
             pending_move: None,
             last_captured: Vec::new(),
             last_result: None,
+            end_reason: None,
+            ended_without_move: false,
+            settings: GameSettings::default(),
+            demo: false,
+            rules: standard_rules(),
+            started_at: None,
+            clock: GameClock::default(),
+            event_listener: None,
         }
     }
 }
@@ -100,18 +171,63 @@ impl Game {
         Self::default()
     }
 
+    /// 设置事件监听器
+    ///
+    /// 每次 `handle_event` 成功处理事件后，以该事件和处理后的状态调用回调，
+    /// 便于外部观察状态流转（日志、测试等）；再次设置会替换之前的监听器
+    pub fn set_event_listener(&mut self, listener: impl FnMut(&GameEvent, &GameState) + 'static) {
+        self.event_listener = Some(EventListener(Box::new(listener)));
+    }
+
+    /// 移除事件监听器
+    pub fn clear_event_listener(&mut self) {
+        self.event_listener = None;
+    }
+
     /// 处理游戏事件，驱动状态机流转
     /// 
-    /// 这是状态机的核心方法，根据当前状态和事件决定下一个状态
-    pub fn handle_event(&mut self, event: GameEvent) -> Result<()> {
+    /// 这是状态机的核心方法，根据当前状态和事件决定下一个状态，
+    /// 并返回描述本次流转的 [`TransitionOutcome`]
+    pub fn handle_event(&mut self, event: GameEvent) -> Result<TransitionOutcome> {
+        let notified_event = self.event_listener.is_some().then(|| event.clone());
+        let previous = self.state;
+        let history_len = self.move_history.len();
+
+        let accepted = self.transition(event)?;
+
+        let new_record = self.move_history.get(history_len);
+        let outcome = TransitionOutcome {
+            accepted,
+            previous,
+            state: self.state,
+            moved: new_record.is_some(),
+            captured: new_record
+                .map(|r| r.captured.iter().map(|c| c.piece_id).collect())
+                .unwrap_or_default(),
+        };
+
+        if let (Some(event), Some(listener)) = (notified_event, self.event_listener.as_mut()) {
+            (listener.0)(&event, &self.state);
+        }
+        Ok(outcome)
+    }
+
+    /// 状态流转表：根据当前状态和事件更新状态，返回事件是否被接受
+    fn transition(&mut self, event: GameEvent) -> Result<bool> {
+        // 终局不变式：棋局结束后不再处理行棋事件，棋盘只能经由悔棋或新局改变
+        if self.last_result.is_some() && event.is_gameplay() {
+            return Ok(false);
+        }
+
         match (&self.state, event) {
             // ===== 新局开始 =====
-            (GameState::NewGame, GameEvent::StartNewGame { player_first, ai_level }) => {
+            // 任意状态下均可开始新局（对局中途放弃由界面确认）
+            (_, GameEvent::StartNewGame { player_first, ai_level }) => {
                 self.start_new_game(player_first, ai_level);
             }
             
             // 电脑先行 -> 进入电脑思考中
-            (GameState::NewGame, _) if self.current_turn != self.player_side => {
+            (GameState::NewGame, _) if self.is_ai_turn() => {
                 self.state = GameState::AiThinking;
             }
             
@@ -121,51 +237,117 @@ impl Game {
             }
             
             // ===== 等待玩家行棋（初始状态）=====
-            (GameState::WaitingForPlayer, GameEvent::PlayerSelectPiece { piece_id, start_pos }) => {
-                // 检查是否是己方棋子且有可移动位置
-                if self.can_piece_move(piece_id) {
-                    self.selected_piece = Some(SelectedPiece {
-                        piece_id,
-                        start_pos,
-                    });
-                    // 进入棋子已选中状态
-                    self.state = GameState::PieceSelected;
-                }
+            // 检查是否是己方棋子且有可移动位置
+            (GameState::WaitingForPlayer, GameEvent::PlayerSelectPiece { piece_id, start_pos })
+                if self.can_piece_move(piece_id) =>
+            {
+                self.selected_piece = Some(SelectedPiece {
+                    piece_id,
+                    start_pos,
+                });
+                // 进入棋子已选中状态
+                self.state = GameState::PieceSelected;
             }
             
-            (GameState::WaitingForPlayer, GameEvent::StartUndo) => {
-                if self.can_undo() {
-                    self.state = GameState::UndoAnimating;
-                }
+            (GameState::WaitingForPlayer, GameEvent::StartUndo) if self.can_undo() => {
+                self.state = GameState::UndoAnimating;
+            }
+            
+            // 玩家认输，电脑获胜
+            (GameState::WaitingForPlayer, GameEvent::Resign) => {
+                self.ended_without_move = true;
+                self.finish(GameResult::AiWin, GameEndReason::Resignation);
+            }
+
+            // 玩家提和：电脑接受则以和棋结束，拒绝则继续等待玩家行棋
+            (GameState::WaitingForPlayer, GameEvent::OfferDraw)
+                if AiPlayer::new(self.ai_level).accepts_draw(&self.board, self.player_side.opposite()) =>
+            {
+                self.ended_without_move = true;
+                self.finish(GameResult::Draw, GameEndReason::DrawAgreed);
             }
             
-            // ===== 棋子已选中状态 =====
-            (GameState::PieceSelected, GameEvent::PlayerClickTarget { target_pos }) => {
+            // 限时模式下行棋方用完时间判负（思考或选子过程中均可发生）
+            (
+                GameState::WaitingForPlayer
+                | GameState::PieceSelected
+                | GameState::PieceDragging
+                | GameState::WaitingForTargetClick
+                | GameState::ConfirmingMove
+                | GameState::AiThinking,
+                GameEvent::FlagFall { side },
+            ) if side == self.current_turn => {
+                let result = if side == self.player_side { GameResult::AiWin } else { GameResult::PlayerWin };
+                self.selected_piece = None;
+                self.pending_move = None;
+                self.ended_without_move = true;
+                self.finish(result, GameEndReason::Timeout);
+            }
+
+            // ===== 棋子已选中 / 拖拽中 / 等待点击目标点 =====
+            // 目标点必须是选中棋子的合法相邻空点
+            (
+                GameState::PieceSelected | GameState::PieceDragging | GameState::WaitingForTargetClick,
+                GameEvent::PlayerClickTarget { target_pos },
+            ) if self.is_legal_target(target_pos) => {
                 if let Some(selected) = self.selected_piece {
-                    // 执行移动
+                    // 执行移动；需要确认落子时先保留选中，等待确认
                     self.pending_move = Some(PendingMove {
                         from: selected.start_pos,
                         to: target_pos,
                         is_ai: false,
                     });
-                    self.state = GameState::PieceMoving;
-                    self.selected_piece = None;
+                    if self.settings.confirm_moves {
+                        self.state = GameState::ConfirmingMove;
+                    } else {
+                        self.state = GameState::PieceMoving;
+                        self.selected_piece = None;
+                    }
                 }
             }
+
+            // ===== 等待确认落子 =====
+            (GameState::ConfirmingMove, GameEvent::ConfirmMove) if self.pending_move.is_some() => {
+                self.state = GameState::PieceMoving;
+                self.selected_piece = None;
+            }
+
+            // 取消落子：棋子留在原位，返回初始状态
+            (GameState::ConfirmingMove, GameEvent::PlayerCancel) => {
+                self.pending_move = None;
+                self.selected_piece = None;
+                self.state = GameState::WaitingForPlayer;
+            }
             
-            // 点击了非目标点或右键，返回初始状态
+            // 点击了非目标点（含非法目标点）或右键，返回初始状态
             (GameState::PieceSelected, GameEvent::PlayerClickInvalid) |
-            (GameState::PieceSelected, GameEvent::PlayerCancel) => {
+            (GameState::PieceSelected, GameEvent::PlayerCancel) |
+            (GameState::PieceDragging, GameEvent::PlayerCancel) |
+            (GameState::WaitingForTargetClick, GameEvent::PlayerClickInvalid) |
+            (GameState::WaitingForTargetClick, GameEvent::PlayerCancel) |
+            (GameState::WaitingForTargetClick, GameEvent::PlayerClickTarget { .. }) => {
                 self.selected_piece = None;
                 self.state = GameState::WaitingForPlayer;
             }
             
+            // 按住选中的棋子开始拖动
+            (GameState::PieceSelected | GameState::WaitingForTargetClick, GameEvent::PlayerStartMoving)
+                if self.selected_piece.is_some() =>
+            {
+                self.state = GameState::PieceDragging;
+            }
+            
+            // 松开但未移动（或未落到合法目标点）：棋子放回原位，保持选中，等待点击目标点
+            (GameState::PieceSelected | GameState::PieceDragging, GameEvent::PlayerReleaseWithoutMove) => {
+                self.state = GameState::WaitingForTargetClick;
+            }
+            
             // ===== 棋子移动动画 =====
             (GameState::PieceMoving, GameEvent::PieceMoveAnimationComplete { moved }) => {
                 if let Some(pending) = self.pending_move {
                     if moved {
-                        // 执行实际的移动
-                        let record = self.execute_move(pending.from, pending.to, self.player_side)?;
+                        // 执行实际的移动（行棋方为当前回合方）
+                        let record = self.execute_move(pending.from, pending.to, self.current_turn)?;
                         self.last_captured = record.captured.iter().map(|c| c.piece_id).collect();
                         self.move_history.push(record);
                         
@@ -194,27 +376,18 @@ impl Game {
             }
             
             // ===== 胜负判断 =====
+            // 结果由 `check_game_end_after_move` 得出（困毙针对即将行棋的一方）
             (GameState::CheckingGameEnd, GameEvent::GameEndCheckComplete { result }) => {
-                if let Some(result) = result {
-                    self.last_result = Some(result);
-                    self.state = GameState::GameOverDialog(result);
-                } else {
-                    // 切换回合
-                    self.current_turn = self.current_turn.opposite();
-                    
-                    // 切换回合后，检查新回合方是否被困毙
-                    // 注意：这里需要检查新回合方（current_turn）是否有合法移动
-                    if let Some(stalemate_result) = self.check_stalemate_for_current_turn() {
-                        self.last_result = Some(stalemate_result);
-                        self.state = GameState::GameOverDialog(stalemate_result);
-                    } else {
-                        // 根据当前轮到谁决定下一状态
-                        if self.current_turn == self.player_side {
-                            self.state = GameState::WaitingForPlayer;
-                        } else {
-                            self.state = GameState::AiThinking;
-                        }
-                    }
+                if result.is_none() {
+                    // 行棋方走完一步，限时模式下加秒
+                    self.clock.add_increment(self.current_turn);
+                }
+                // 终局时同样切换回合，使 current_turn 始终为下一个行棋方（困毙时即被困一方）
+                self.current_turn = self.current_turn.opposite();
+                match result {
+                    Some((result, reason)) => self.finish(result, reason),
+                    None if self.is_ai_turn() => self.state = GameState::AiThinking,
+                    None => self.state = GameState::WaitingForPlayer,
                 }
             }
             
@@ -222,7 +395,14 @@ impl Game {
             (GameState::GameOverDialog(_), GameEvent::DialogAction(action)) => {
                 match action {
                     DialogAction::Undo => {
-                        if self.can_undo() {
+                        if self.ended_without_move {
+                            // 撤销认输或议和，回到之前的局面
+                            self.ended_without_move = false;
+                            self.last_result = None;
+                            self.end_reason = None;
+                            self.state = GameState::WaitingForPlayer;
+                        } else if self.undo_steps() > 0 {
+                            // 回到玩家最后一步之前：玩家走出终局一步时只回退一步
                             self.state = GameState::UndoAnimating;
                         }
                     }
@@ -234,6 +414,11 @@ impl Game {
                         let player_first = self.player_side == Side::Black;
                         self.start_new_game(player_first, self.ai_level);
                     }
+                    DialogAction::SwapSides => {
+                        // 交换先后手开启新局：上一局先行则本局后行
+                        let player_first = self.player_side == Side::Black;
+                        self.start_new_game(!player_first, self.ai_level);
+                    }
                 }
             }
             
@@ -254,15 +439,15 @@ impl Game {
             }
             
             // 其他未处理的事件组合
-            _ => {}
+            _ => return Ok(false),
         }
         
-        Ok(())
+        Ok(true)
     }
     
     /// 开始新局
     fn start_new_game(&mut self, player_first: bool, ai_level: u8) {
-        self.board = Board::initial();
+        self.board = Board::initial_sized(self.board.size);
         self.player_side = if player_first { Side::Black } else { Side::White };
         self.current_turn = Side::Black; // 黑方先行
         self.move_history.clear();
@@ -270,6 +455,11 @@ impl Game {
         self.pending_move = None;
         self.last_captured.clear();
         self.last_result = None;
+        self.end_reason = None;
+        self.ended_without_move = false;
+        self.demo = false;
+        self.started_at = Some(Instant::now());
+        self.reset_clock();
         self.ai_level = ai_level.clamp(1, 5);
         
         // 根据先行方设置初始状态
@@ -280,6 +470,60 @@ impl Game {
         }
     }
     
+    /// 开始演示：从初始局面起双方均由电脑行棋，黑方先行
+    ///
+    /// 结果沿用玩家视角：`PlayerWin` 表示黑方胜，`AiWin` 表示白方胜
+    pub fn start_demo(&mut self) {
+        self.start_new_game(true, self.ai_level);
+        self.demo = true;
+        self.reset_clock();
+        self.state = GameState::AiThinking;
+    }
+
+    /// 从自定义局面开始对局
+    ///
+    /// 局面须通过规则集的 [`RuleSet::validate_setup`] 校验；行棋历史清空，之后的悔棋最多回到该局面
+    pub fn start_from_position(&mut self, board: Board, player_side: Side, side_to_move: Side) -> Result<(), SetupError> {
+        self.rules.validate_setup(&board, side_to_move, &self.settings)?;
+        self.start_new_game(player_side == Side::Black, self.ai_level);
+        self.board = board;
+        self.current_turn = side_to_move;
+        self.state = if self.is_ai_turn() {
+            GameState::AiThinking
+        } else {
+            GameState::WaitingForPlayer
+        };
+        Ok(())
+    }
+
+    /// 对局是否仍处于开局状态：尚未行棋且棋盘为初始布局
+    ///
+    /// 与 [`is_initial_position`] 不同，行棋后棋子恰好回到初始布局不算开局状态
+    pub fn is_at_start(&self) -> bool {
+        self.move_history.is_empty() && is_initial_position(&self.board)
+    }
+
+    /// 当前是否轮到电脑行棋（演示模式下双方均由电脑行棋）
+    pub fn is_ai_turn(&self) -> bool {
+        self.demo || self.current_turn != self.player_side
+    }
+
+    /// 状态栏显示的行棋方文本（演示模式下显示黑方或白方）
+    pub fn turn_text(&self) -> String {
+        use crate::t;
+        if self.demo {
+            let side = match self.current_turn {
+                Side::Black => t!("game.black"),
+                Side::White => t!("game.white"),
+            };
+            t!("status.demo_turn", side = side)
+        } else if self.current_turn == self.player_side {
+            t!("status.turn_player")
+        } else {
+            t!("status.turn_ai")
+        }
+    }
+
     /// 执行移动
     fn execute_move(&mut self, from: (u8, u8), to: (u8, u8), side: Side) -> Result<MoveRecord> {
         let was_single = self.board.is_single_piece_mode();
@@ -292,7 +536,7 @@ impl Game {
         piece.position = to;
         
         // 检查吃子
-        let captured_ids = calculate_captures(&self.board, piece_id);
+        let captured_ids = self.rules.captures(&self.board, piece_id);
         
         // 收集被吃棋子的记录
         let mut captured_records = Vec::new();
@@ -320,86 +564,988 @@ impl Game {
     
     /// 检查指定棋子是否可以移动
     fn can_piece_move(&self, piece_id: u8) -> bool {
-        if let Some(piece) = self.board.piece_by_id(piece_id) {
-            if piece.side != self.player_side || !piece.active {
-                return false;
-            }
-            
-            // 检查四个方向是否有可移动位置
-            let (x, y) = piece.position;
-            let directions = [(0, 1), (0, -1), (1, 0), (-1, 0)];
-            
-            for (dx, dy) in directions {
-                let nx = x as i8 + dx;
-                let ny = y as i8 + dy;
-                
-                if Board::is_valid_pos(nx, ny) && self.board.is_empty(nx as u8, ny as u8) {
-                    return true;
-                }
-            }
-        }
-        false
+        !self.legal_targets(piece_id).is_empty()
+    }
+
+    /// 玩家指定棋子按规则集可走到的所有位置；不是玩家的在盘棋子时为空
+    pub fn legal_targets(&self, piece_id: u8) -> Vec<(u8, u8)> {
+        let Some(piece) = self
+            .board
+            .piece_by_id(piece_id)
+            .filter(|piece| piece.active && piece.side == self.player_side)
+        else {
+            return Vec::new();
+        };
+        self.rules
+            .valid_moves(&self.board, self.player_side)
+            .into_iter()
+            .filter(|&(from, _)| from == piece.position)
+            .map(|(_, to)| to)
+            .collect()
+    }
+    
+    /// 检查目标点是否是当前选中棋子的合法落点
+    fn is_legal_target(&self, target_pos: (u8, u8)) -> bool {
+        self.selected_piece.is_some_and(|selected| {
+            self.rules.is_valid_move(&self.board, selected.start_pos, target_pos, self.player_side)
+        })
     }
     
     /// 检查是否可以悔棋
     /// 
     /// 根据 spec:
     /// - 在"等待玩家行棋"状态可以悔棋
-    /// - 需要至少有一次历史记录
+    /// - 需要历史记录中有玩家的行棋
     pub fn can_undo(&self) -> bool {
-        self.state.can_undo() && !self.move_history.is_empty()
+        self.undo_status().is_available()
+    }
+
+    /// 悔棋是否可用；不可用时给出原因
+    pub fn undo_status(&self) -> UndoStatus {
+        if matches!(
+            self.state,
+            GameState::PieceMoving | GameState::CaptureAnimating | GameState::UndoAnimating
+        ) {
+            UndoStatus::Animating
+        } else if self.demo || !self.state.can_undo() {
+            UndoStatus::WrongState
+        } else if self.undo_steps() == 0 {
+            UndoStatus::NoHistory
+        } else {
+            UndoStatus::Available
+        }
+    }
+
+    /// 悔棋需要回退的步数：回退到玩家最近一步行棋之前（包括其后电脑的应着）
+    ///
+    /// 通常为2步；玩家走出胜着后为1步；历史中没有玩家行棋（如仅有电脑先行的一步）时为0
+    pub fn undo_steps(&self) -> usize {
+        self.move_history
+            .iter()
+            .rev()
+            .position(|record| record.side == self.player_side)
+            .map_or(0, |index| index + 1)
     }
     
     /// 执行悔棋（实际修改棋盘状态）
+    ///
+    /// 每次回退到玩家最近一步之前，可反复悔棋直到历史中不再有玩家行棋；
+    /// 行棋方取自被回退的记录，不依赖悔棋前的回合状态。
+    /// 先在棋盘副本上逐步撤销，全部成功后才提交，任一步失败时棋盘、历史与回合均保持不变
     fn perform_undo(&mut self) -> Result<()> {
-        // 需要回退两步（AI一步 + 玩家一步）
-        for _ in 0..2 {
-            if let Some(record) = self.move_history.pop() {
-                self.board.undo_move(&record)?;
-                self.current_turn = self.current_turn.opposite();
-            } else {
-                break;
-            }
+        let steps = self.undo_steps();
+        let kept = self.move_history.len() - steps;
+        let mut board = self.board.clone();
+        for record in self.move_history[kept..].iter().rev() {
+            board.undo_move(record)?;
         }
-        
-        // 确保回到玩家回合
-        self.current_turn = self.player_side;
+        if let Some(record) = self.move_history.get(kept) {
+            // 回退后轮到最早被撤销那一步的行棋方
+            self.current_turn = record.side;
+        }
+        self.board = board;
+        self.move_history.truncate(kept);
         self.last_result = None;
+        self.end_reason = None;
+        self.ended_without_move = false;
+        self.selected_piece = None;
+        self.pending_move = None;
+        self.last_captured.clear();
         
         Ok(())
     }
     
-    /// 检查游戏是否结束
-    pub fn check_game_end(&self) -> Option<GameResult> {
-        check_game_end(&self.board, self.current_turn, self.player_side)
+    /// 检查游戏是否结束（轮到 current_turn 行棋）
+    pub fn check_game_end(&self) -> Option<(GameResult, GameEndReason)> {
+        self.check_game_end_for(self.current_turn)
     }
-    
-    /// 检查当前回合方是否被困毙
-    /// 返回 Some(GameResult) 如果当前方被困毙，否则返回 None
-    pub fn check_stalemate_for_current_turn(&self) -> Option<GameResult> {
-        use crate::game::rules::is_stalemated;
-        
-        if is_stalemated(&self.board, self.current_turn) {
-            // 当前方被困毙，判负，对方获胜
-            let winner = self.current_turn.opposite();
-            Some(if winner == self.player_side {
-                GameResult::PlayerWin
-            } else {
-                GameResult::AiWin
-            })
-        } else {
-            None
+
+    /// 刚走完一步、尚未切换回合时检查游戏是否结束：困毙与少子判和针对即将行棋的对方
+    pub fn check_game_end_after_move(&self) -> Option<(GameResult, GameEndReason)> {
+        self.check_game_end_for(self.current_turn.opposite())
+    }
+
+    fn check_game_end_for(&self, side_to_move: Side) -> Option<(GameResult, GameEndReason)> {
+        self.rules.is_game_over(&self.board, side_to_move, self.player_side, &self.settings)
+            .or_else(|| self.check_no_capture_draw())
+            .or_else(|| self.check_repetition_draw())
+    }
+
+    /// 当前应计时的一方：玩家等待或选子时计玩家用时，电脑思考时计电脑用时，其余状态暂停
+    pub fn clock_side(&self) -> Option<Side> {
+        let thinking = matches!(
+            self.state,
+            GameState::WaitingForPlayer
+                | GameState::PieceSelected
+                | GameState::PieceDragging
+                | GameState::WaitingForTargetClick
+                | GameState::ConfirmingMove
+                | GameState::AiThinking
+        );
+        thinking.then_some(self.current_turn)
+    }
+
+    /// 每帧更新计时；paused 为 true 时（如有对话框打开）暂停双方计时
+    ///
+    /// 限时模式下行棋方用完时间时结束对局，返回对局结果
+    pub fn tick_clock(&mut self, paused: bool) -> Option<GameResult> {
+        let side = if paused { None } else { self.clock_side() };
+        self.clock.update(side, Instant::now());
+        if paused {
+            return None;
         }
+        let side = self.clock.flagged().filter(|&side| self.clock_side() == Some(side))?;
+        self.handle_event(GameEvent::FlagFall { side }).ok()?;
+        self.last_result
     }
-    
-    /// 执行AI移动（由外部AI模块调用）
-    pub fn execute_ai_move(&mut self, from: (u8, u8), to: (u8, u8)) -> Result<Vec<u8>> {
-        let record = self.execute_move(from, to, self.player_side.opposite())?;
+
+    /// 按设置重置计时器（演示对局不限时）
+    pub fn reset_clock(&mut self) {
+        let time_control = self.settings.time_control.filter(|_| !self.demo);
+        self.clock = GameClock::new(time_control);
+    }
+
+    /// 生成当前棋局的存档元数据
+    pub fn save_metadata(&self, player_name: &str) -> SaveMetadata {
+        SaveMetadata::new(player_name, self.ai_level, self.last_result)
+    }
+
+    /// 当前对局统计：步数与吃子数由行棋历史推算，悔棋后自动回退
+    pub fn stats(&self) -> GameStats {
+        let captures = |side: Side| {
+            self.move_history
+                .iter()
+                .filter(|record| record.side == side)
+                .map(|record| record.captured.len())
+                .sum()
+        };
+        GameStats {
+            moves: self.move_history.len(),
+            black_captures: captures(Side::Black),
+            white_captures: captures(Side::White),
+            elapsed: self.started_at.map(|start| start.elapsed()).unwrap_or_default(),
+        }
+    }
+
+    /// 距离最近一次吃子已经过的步数（半回合）
+    ///
+    /// 直接由行棋历史推算，悔棋后自动恢复正确的计数
+    pub fn moves_since_capture(&self) -> u32 {
+        self.move_history
+            .iter()
+            .rev()
+            .take_while(|record| record.captured.is_empty())
+            .count() as u32
+    }
+
+    /// 检查无吃子判和规则
+    ///
+    /// 演示模式下即使关闭了该规则也以 [`DEMO_NO_CAPTURE_DRAW_LIMIT`] 为上限，保证电脑对弈能结束
+    fn check_no_capture_draw(&self) -> Option<(GameResult, GameEndReason)> {
+        let limit = self
+            .settings
+            .no_capture_draw_limit
+            .or(self.demo.then_some(DEMO_NO_CAPTURE_DRAW_LIMIT))?;
+        (self.moves_since_capture() >= limit).then_some((GameResult::Draw, GameEndReason::NoCaptureLimit))
+    }
+
+    /// 当前局面（同一方刚走完）在本局中出现的次数，含当前这次
+    ///
+    /// 局面以 Zobrist 哈希比较，与棋子ID无关
+    pub fn position_repetitions(&self) -> usize {
+        let Some(last) = self.move_history.last() else {
+            return 1;
+        };
+        let hash = self.board.zobrist_hash();
+        self.positions_since_capture()
+            .into_iter()
+            .filter(|&(side, position)| side == last.side && position == hash)
+            .count()
+    }
+
+    /// 最近一次吃子以来每步走完时的局面：（刚走完的一方，Zobrist 哈希），从当前局面起倒序
+    ///
+    /// 吃子不可逆，更早的局面不会再出现，只需从当前局面沿行棋历史逐步撤销到最近一次吃子
+    fn positions_since_capture(&self) -> Vec<(Side, u64)> {
+        let Some(last) = self.move_history.last() else {
+            return Vec::new();
+        };
+        let mut board = self.board.clone();
+        let mut positions = vec![(last.side, board.zobrist_hash())];
+        for (index, record) in self.move_history.iter().enumerate().skip(1).rev() {
+            if !record.captured.is_empty() || board.undo_move(record).is_err() {
+                break;
+            }
+            // 撤销后为上一步走完时的局面
+            positions.push((self.move_history[index - 1].side, board.zobrist_hash()));
+        }
+        positions
+    }
+
+    /// 检查重复局面判和规则（可在设置中关闭）
+    fn check_repetition_draw(&self) -> Option<(GameResult, GameEndReason)> {
+        (self.settings.repetition_draw && self.position_repetitions() >= REPETITION_DRAW_COUNT).then_some((GameResult::Draw, GameEndReason::Repetition))
+    }
+
+    /// 以给定结果结束对局，并记录结束原因
+    fn finish(&mut self, result: GameResult, reason: GameEndReason) {
+        self.last_result = Some(result);
+        self.end_reason = Some(reason);
+        self.state = GameState::GameOverDialog(result);
+    }
+
+    /// 无界面行棋：由当前回合方走一步
+    ///
+    /// 一次性完成合法性校验、移动、吃子、胜负判断与回合切换，
+    /// 跳过所有动画相关的状态流转，供脚本与自对弈使用
+    pub fn play_move(&mut self, from: (u8, u8), to: (u8, u8)) -> Result<MoveResult> {
+        if self.last_result.is_some() {
+            anyhow::bail!("棋局已结束");
+        }
+        if !self.rules.is_valid_move(&self.board, from, to, self.current_turn) {
+            anyhow::bail!("非法移动: {:?} -> {:?}", from, to);
+        }
+
+        let record = self.execute_move(from, to, self.current_turn)?;
         let captured: Vec<u8> = record.captured.iter().map(|c| c.piece_id).collect();
+        self.last_captured = captured.clone();
         self.move_history.push(record);
-        Ok(captured)
+        self.selected_piece = None;
+        self.pending_move = None;
+
+        // 胜负判断（困毙针对即将行棋的对方），随后切换回合
+        let result = self.check_game_end_after_move();
+        self.current_turn = self.current_turn.opposite();
+
+        match result {
+            Some((result, reason)) => self.finish(result, reason),
+            None if self.is_ai_turn() => self.state = GameState::AiThinking,
+            None => self.state = GameState::WaitingForPlayer,
+        }
+
+        Ok(MoveResult {
+            moved: true,
+            captured,
+            returned: false,
+        })
+    }
+
+    /// 无界面行棋：由AI为当前回合方选择走法并执行
+    pub fn ai_turn(&mut self) -> Result<MoveResult> {
+        self.ai_turn_with(&self.ai_for_turn(self.ai_level))
+    }
+
+    /// 按设置构造为当前回合方行棋的指定等级AI（开局变化、行棋风格、对方上一步）；
+    /// 演示模式下另传入己方走过的局面，使双方电脑对弈时占优一方不因重复局面判和
+    pub fn ai_for_turn(&self, level: u8) -> AiPlayer {
+        let last_opponent_move = self
+            .move_history
+            .last()
+            .filter(|record| record.side != self.current_turn)
+            .map(|record| (record.from, record.to));
+        let ai = AiPlayer::new(level)
+            .with_opening_variety(self.settings.opening_variety)
+            .with_style(self.settings.ai_style, last_opponent_move);
+        if self.demo {
+            ai.with_seen_positions(self.seen_positions_for_turn())
+        } else {
+            ai
+        }
+    }
+
+    /// 当前回合方在最近一次吃子以来走完后出现过的局面（Zobrist 哈希），供AI避免重复局面
+    pub fn seen_positions_for_turn(&self) -> Vec<u64> {
+        self.positions_since_capture()
+            .into_iter()
+            .filter(|&(side, _)| side == self.current_turn)
+            .map(|(_, position)| position)
+            .collect()
+    }
+
+    /// 无界面行棋：由指定的AI为当前回合方选择走法并执行
+    pub fn ai_turn_with(&mut self, ai: &AiPlayer) -> Result<MoveResult> {
+        let (from, to) = ai.select_move(&self.board, self.current_turn)?;
+        self.play_move(from, to)
     }
 }
 
 // 重新导出状态相关的类型
-pub use state::{AnimationType, DialogAction, GameResult, GameState, MoveResult, SelectedPiece};
+pub use state::{AnimationType, DialogAction, GameEndReason, GameResult, GameState, MoveResult, SelectedPiece, TransitionOutcome, UndoStatus};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 开始新局（玩家执黑时先行）
+    fn new_game(player_first: bool) -> Game {
+        let mut game = Game::new();
+        game.handle_event(GameEvent::StartNewGame { player_first, ai_level: 1 }).unwrap();
+        game
+    }
+
+    /// 从给定局面开始对局（玩家执黑），关闭少子判和以便在少子局面中分出胜负
+    fn game_from(notation: &str, side_to_move: Side) -> Game {
+        let mut game = new_game(true);
+        game.board = Board::from_notation(notation).unwrap();
+        game.settings.small_material_draw = false;
+        game.current_turn = side_to_move;
+        game.state = if game.is_ai_turn() { GameState::AiThinking } else { GameState::WaitingForPlayer };
+        game
+    }
+
+    /// 在终局对话框中选择悔棋并完成悔棋动画
+    fn undo_from_dialog(game: &mut Game) {
+        game.handle_event(GameEvent::DialogAction(DialogAction::Undo)).unwrap();
+        assert_eq!(game.state, GameState::UndoAnimating);
+        game.handle_event(GameEvent::UndoAnimationComplete).unwrap();
+    }
+
+    #[test]
+    fn dialog_undo_takes_back_one_ply_after_a_winning_player_move() {
+        // 白方先走一步，黑方单子随即担吃白方最后两子
+        let mut game = game_from("..../..../.BW./W...", Side::White);
+        game.play_move((2, 1), (2, 0)).unwrap();
+        let before_win = game.board.to_notation();
+        game.play_move((1, 1), (1, 0)).unwrap();
+        assert_eq!(game.state, GameState::GameOverDialog(GameResult::PlayerWin));
+        assert_eq!(game.undo_steps(), 1);
+
+        undo_from_dialog(&mut game);
+        assert_eq!(game.state, GameState::WaitingForPlayer);
+        assert_eq!(game.move_history.len(), 1);
+        assert_eq!(game.current_turn, Side::Black);
+        assert_eq!(game.board.to_notation(), before_win);
+        assert!(game.last_result.is_none());
+    }
+
+    #[test]
+    fn dialog_undo_takes_back_two_plies_after_a_winning_ai_reply() {
+        // 黑方走一步后，白方单子担吃黑方仅剩的两子
+        let start = "..../..../.WB./B...";
+        let mut game = game_from(start, Side::Black);
+        game.play_move((2, 1), (2, 0)).unwrap();
+        game.play_move((1, 1), (1, 0)).unwrap();
+        assert_eq!(game.state, GameState::GameOverDialog(GameResult::AiWin));
+        assert_eq!(game.undo_steps(), 2);
+
+        undo_from_dialog(&mut game);
+        assert_eq!(game.state, GameState::WaitingForPlayer);
+        assert!(game.move_history.is_empty());
+        assert_eq!(game.current_turn, Side::Black);
+        assert_eq!(game.board.to_notation(), start);
+        assert_eq!(game.board.count_active(Side::Black), 2);
+    }
+
+    /// 测试用规则集：只有最左列的棋子可以移动，从不吃子，不接受任何自定义局面
+    #[derive(Debug)]
+    struct LeftColumnRules;
+
+    impl RuleSet for LeftColumnRules {
+        fn captures(&self, _board: &Board, _moved_piece_id: u8) -> Vec<u8> {
+            Vec::new()
+        }
+
+        fn is_game_over(
+            &self,
+            board: &Board,
+            side_to_move: Side,
+            player_side: Side,
+            settings: &GameSettings,
+        ) -> Option<(GameResult, GameEndReason)> {
+            // 困毙按本规则集的走法判定
+            if board.count_active(side_to_move) > 0 && self.valid_moves(board, side_to_move).is_empty() {
+                let result = if side_to_move == player_side { GameResult::AiWin } else { GameResult::PlayerWin };
+                return Some((result, GameEndReason::Stalemate));
+            }
+            StandardRules.is_game_over(board, side_to_move, player_side, settings)
+        }
+
+        fn is_valid_move(&self, board: &Board, from: (u8, u8), to: (u8, u8), side: Side) -> bool {
+            from.0 == 0 && StandardRules.is_valid_move(board, from, to, side)
+        }
+
+        fn valid_moves(&self, board: &Board, side: Side) -> Vec<rules::Move> {
+            StandardRules.valid_moves(board, side).into_iter().filter(|&(from, _)| from.0 == 0).collect()
+        }
+
+        fn validate_setup(&self, _board: &Board, _side_to_move: Side, _settings: &GameSettings) -> Result<(), SetupError> {
+            Err(SetupError::NoLegalMoves)
+        }
+    }
+
+    #[test]
+    fn game_dispatches_through_its_rule_set() {
+        let mut game = new_game(true);
+        game.rules = Box::new(LeftColumnRules);
+
+        let right = game.board.piece_at(3, 1).unwrap().id;
+        let left = game.board.piece_at(0, 1).unwrap().id;
+        assert!(game.legal_targets(right).is_empty());
+        assert_eq!(game.legal_targets(left), vec![(1, 1)]);
+        game.handle_event(GameEvent::PlayerSelectPiece { piece_id: right, start_pos: (3, 1) }).unwrap();
+        assert_eq!(game.state, GameState::WaitingForPlayer);
+        assert!(game.play_move((3, 1), (2, 1)).is_err());
+        assert_eq!(
+            game.start_from_position(Board::initial(), Side::Black, Side::Black),
+            Err(SetupError::NoLegalMoves)
+        );
+
+        // 标准规则下 (0,1)→(0,0) 形成二比一吃掉 (2,0) 的白子；
+        // 测试规则集不吃子，且白方最左列无子可走，随即被困毙
+        let position = "...W/..../B.../.BW.";
+        let mut standard = game_from(position, Side::Black);
+        assert_eq!(standard.play_move((0, 1), (0, 0)).unwrap().captured.len(), 1);
+        let mut custom = game_from(position, Side::Black);
+        custom.rules = Box::new(LeftColumnRules);
+        assert!(custom.play_move((0, 1), (0, 0)).unwrap().captured.is_empty());
+        assert_eq!(custom.last_result, Some(GameResult::PlayerWin));
+        assert_eq!(custom.end_reason, Some(GameEndReason::Stalemate));
+    }
+
+    #[test]
+    fn transition_outcome_reports_accepted_events_and_captures() {
+        let mut game = game_from("..BB/.W../W.B./B...", Side::White);
+
+        // 等待电脑行棋时不处理玩家选子
+        let piece_id = game.board.piece_at(0, 0).unwrap().id;
+        let outcome = game.handle_event(GameEvent::PlayerSelectPiece { piece_id, start_pos: (0, 0) }).unwrap();
+        assert!(!outcome.accepted);
+        assert!(!outcome.state_changed());
+        assert_eq!(outcome.state, GameState::AiThinking);
+
+        let outcome = game.handle_event(GameEvent::AiMoveSelected { from: (1, 2), to: (1, 1) }).unwrap();
+        assert!(outcome.accepted);
+        assert_eq!((outcome.previous, outcome.state), (GameState::AiThinking, GameState::PieceMoving));
+        assert!(!outcome.moved);
+
+        // 移动动画结束时执行走棋，结果中带有被吃的棋子
+        let captured = game.board.piece_at(2, 1).unwrap().id;
+        let outcome = game.handle_event(GameEvent::PieceMoveAnimationComplete { moved: true }).unwrap();
+        assert!(outcome.accepted && outcome.moved && outcome.has_capture());
+        assert_eq!(outcome.captured, vec![captured]);
+        assert_eq!(outcome.state, GameState::CheckingCapture);
+    }
+
+    /// 选中 `pos` 上的棋子后原地松开，进入等待点击目标点状态
+    fn wait_for_target_click(game: &mut Game, pos: (u8, u8)) {
+        let piece_id = game.board.piece_at(pos.0, pos.1).unwrap().id;
+        game.handle_event(GameEvent::PlayerSelectPiece { piece_id, start_pos: pos }).unwrap();
+        game.handle_event(GameEvent::PlayerReleaseWithoutMove).unwrap();
+        assert_eq!(game.state, GameState::WaitingForTargetClick);
+    }
+
+    #[test]
+    fn waiting_for_target_click_moves_to_a_legal_target() {
+        let mut game = new_game(true);
+        wait_for_target_click(&mut game, (0, 1));
+        let outcome = game.handle_event(GameEvent::PlayerClickTarget { target_pos: (1, 1) }).unwrap();
+        assert!(outcome.accepted);
+        assert_eq!(game.state, GameState::PieceMoving);
+        assert!(game.selected_piece.is_none());
+        let pending = game.pending_move.unwrap();
+        assert_eq!((pending.from, pending.to, pending.is_ai), ((0, 1), (1, 1), false));
+
+        while advance(&mut game) {}
+        assert_eq!(game.board.piece_at(1, 1).map(|p| p.side), Some(Side::Black));
+        assert_eq!(game.move_history.len(), 1);
+    }
+
+    #[test]
+    fn waiting_for_target_click_returns_to_waiting_on_invalid_click_or_cancel() {
+        // 非法目标点（不相邻）、点击空白处、右键取消均放弃选中，棋盘不变
+        for event in [
+            GameEvent::PlayerClickTarget { target_pos: (2, 2) },
+            GameEvent::PlayerClickInvalid,
+            GameEvent::PlayerCancel,
+        ] {
+            let mut game = new_game(true);
+            let board = game.board.clone();
+            wait_for_target_click(&mut game, (0, 1));
+            let outcome = game.handle_event(event.clone()).unwrap();
+            assert!(outcome.accepted, "{event:?}");
+            assert_eq!(game.state, GameState::WaitingForPlayer, "{event:?}");
+            assert!(game.selected_piece.is_none(), "{event:?}");
+            assert!(game.pending_move.is_none(), "{event:?}");
+            assert_eq!(game.board, board, "{event:?}");
+        }
+    }
+
+    #[test]
+    fn releasing_a_dragged_piece_without_moving_waits_for_a_target_click() {
+        let mut game = new_game(true);
+        let board = game.board.clone();
+        let piece_id = game.board.piece_at(0, 1).unwrap().id;
+        game.handle_event(GameEvent::PlayerSelectPiece { piece_id, start_pos: (0, 1) }).unwrap();
+        game.handle_event(GameEvent::PlayerStartMoving).unwrap();
+        assert_eq!(game.state, GameState::PieceDragging);
+
+        let outcome = game.handle_event(GameEvent::PlayerReleaseWithoutMove).unwrap();
+        assert!(outcome.accepted && !outcome.moved);
+        assert_eq!(game.state, GameState::WaitingForTargetClick);
+        // 棋子放回原位并保持选中
+        assert_eq!(game.board, board);
+        assert!(game.move_history.is_empty());
+        assert!(game.pending_move.is_none());
+        assert_eq!(game.selected_piece.map(|s| (s.piece_id, s.start_pos)), Some((piece_id, (0, 1))));
+    }
+
+    #[test]
+    fn event_listener_sees_each_event_of_a_move_in_order() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let mut game = new_game(true);
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let recorder = Rc::clone(&seen);
+        game.set_event_listener(move |event, state| recorder.borrow_mut().push((format!("{event:?}"), *state)));
+
+        let piece_id = game.board.piece_at(0, 1).unwrap().id;
+        game.handle_event(GameEvent::PlayerSelectPiece { piece_id, start_pos: (0, 1) }).unwrap();
+        game.handle_event(GameEvent::PlayerClickTarget { target_pos: (1, 1) }).unwrap();
+        while advance(&mut game) {}
+
+        let expected = [
+            (format!("PlayerSelectPiece {{ piece_id: {piece_id}, start_pos: (0, 1) }}"), GameState::PieceSelected),
+            ("PlayerClickTarget { target_pos: (1, 1) }".to_string(), GameState::PieceMoving),
+            ("PieceMoveAnimationComplete { moved: true }".to_string(), GameState::CheckingCapture),
+            (
+                "CaptureCheckComplete { has_capture: false, captured_piece_ids: [] }".to_string(),
+                GameState::CheckingGameEnd,
+            ),
+            ("GameEndCheckComplete { result: None }".to_string(), GameState::AiThinking),
+        ];
+        assert_eq!(*seen.borrow(), expected);
+
+        // 移除后不再收到通知
+        game.clear_event_listener();
+        game.handle_event(GameEvent::AiMoveSelected { from: (0, 2), to: (1, 2) }).unwrap();
+        assert_eq!(seen.borrow().len(), expected.len());
+    }
+
+    /// 开启落子确认，选中 from 处的棋子并点击 to，停在等待确认状态
+    fn confirming_move(from: (u8, u8), to: (u8, u8)) -> Game {
+        let mut game = new_game(true);
+        game.settings.confirm_moves = true;
+        let piece_id = game.board.piece_at(from.0, from.1).unwrap().id;
+        game.handle_event(GameEvent::PlayerSelectPiece { piece_id, start_pos: from }).unwrap();
+        game.handle_event(GameEvent::PlayerClickTarget { target_pos: to }).unwrap();
+        assert_eq!(game.state, GameState::ConfirmingMove);
+        assert!(game.board.positions_equal(&Board::initial()));
+        game
+    }
+
+    #[test]
+    fn confirming_a_move_plays_it() {
+        let mut game = confirming_move((0, 1), (1, 1));
+        game.handle_event(GameEvent::ConfirmMove).unwrap();
+        assert_eq!(game.state, GameState::PieceMoving);
+        assert!(game.selected_piece.is_none());
+        while advance(&mut game) {}
+
+        assert_eq!(game.state, GameState::AiThinking);
+        assert!(game.board.piece_at(0, 1).is_none());
+        assert_eq!(game.board.piece_at(1, 1).map(|piece| piece.side), Some(Side::Black));
+        assert_eq!(game.move_history.len(), 1);
+        assert_eq!(game.current_turn, Side::White);
+    }
+
+    #[test]
+    fn cancelling_a_confirmation_leaves_the_piece_in_place() {
+        let mut game = confirming_move((0, 1), (1, 1));
+        game.handle_event(GameEvent::PlayerCancel).unwrap();
+
+        assert_eq!(game.state, GameState::WaitingForPlayer);
+        assert!(game.selected_piece.is_none());
+        assert!(game.pending_move.is_none());
+        assert!(game.board.positions_equal(&Board::initial()));
+        assert!(game.move_history.is_empty());
+        assert_eq!(game.current_turn, Side::Black);
+
+        // 取消后确认不再有效
+        game.handle_event(GameEvent::ConfirmMove).unwrap();
+        assert_eq!(game.state, GameState::WaitingForPlayer);
+    }
+
+    #[test]
+    fn gameplay_events_are_ignored_after_the_game_ends() {
+        let mut game = new_game(true);
+        game.play_move((0, 1), (1, 1)).unwrap();
+        game.play_move((0, 2), (0, 1)).unwrap();
+        game.handle_event(GameEvent::Resign).unwrap();
+        assert_eq!(game.last_result, Some(GameResult::AiWin));
+
+        let piece_id = game.board.piece_at(1, 1).unwrap().id;
+        let white_id = game.board.piece_at(0, 1).unwrap().id;
+        let events = [
+            GameEvent::AiMoveSelected { from: (3, 2), to: (2, 2) },
+            GameEvent::PlayerSelectPiece { piece_id, start_pos: (1, 1) },
+            GameEvent::PlayerClickTarget { target_pos: (1, 2) },
+            GameEvent::ConfirmMove,
+            GameEvent::PlayerStartMoving,
+            GameEvent::PlayerReleaseWithoutMove,
+            GameEvent::PieceMoveAnimationComplete { moved: true },
+            GameEvent::CaptureCheckComplete { has_capture: true, captured_piece_ids: vec![white_id] },
+            GameEvent::CaptureAnimationComplete,
+            GameEvent::GameEndCheckComplete { result: Some((GameResult::PlayerWin, GameEndReason::NoPieces)) },
+            GameEvent::Resign,
+            GameEvent::OfferDraw,
+            GameEvent::FlagFall { side: Side::Black },
+            GameEvent::FlagFall { side: Side::White },
+        ];
+        let states = [
+            GameState::GameOverDialog(GameResult::AiWin),
+            GameState::WaitingForPlayer,
+            GameState::PieceSelected,
+            GameState::PieceDragging,
+            GameState::WaitingForTargetClick,
+            GameState::ConfirmingMove,
+            GameState::AiThinking,
+            GameState::PieceMoving,
+            GameState::CheckingCapture,
+            GameState::CaptureAnimating,
+            GameState::CheckingGameEnd,
+        ];
+
+        let pieces = game.board.pieces.clone();
+        let history = game.move_history.clone();
+        for state in states {
+            for event in events.clone() {
+                assert!(event.is_gameplay(), "{event:?}");
+                game.state = state;
+                game.selected_piece = Some(SelectedPiece { piece_id, start_pos: (1, 1) });
+                game.pending_move = Some(PendingMove { from: (1, 1), to: (1, 2), is_ai: false });
+                game.last_captured = vec![white_id];
+
+                game.handle_event(event.clone()).unwrap();
+                assert_eq!(game.board.pieces, pieces, "{state:?} {event:?}");
+                assert_eq!(game.move_history, history, "{state:?} {event:?}");
+                assert_eq!(game.state, state, "{state:?} {event:?}");
+                assert_eq!(game.last_result, Some(GameResult::AiWin));
+                assert_eq!(game.end_reason, Some(GameEndReason::Resignation));
+            }
+        }
+    }
+
+    /// 按界面的处理方式推进一步：动画状态发送对应的完成事件，判断状态发送判断结果；
+    /// 等待行棋或棋局结束时返回 false
+    fn advance(game: &mut Game) -> bool {
+        let event = match game.state {
+            GameState::PieceMoving => GameEvent::PieceMoveAnimationComplete { moved: true },
+            GameState::CheckingCapture => GameEvent::CaptureCheckComplete {
+                has_capture: !game.last_captured.is_empty(),
+                captured_piece_ids: game.last_captured.clone(),
+            },
+            GameState::CaptureAnimating => GameEvent::CaptureAnimationComplete,
+            GameState::CheckingGameEnd => GameEvent::GameEndCheckComplete { result: game.check_game_end_after_move() },
+            _ => return false,
+        };
+        game.handle_event(event).unwrap();
+        true
+    }
+
+    #[test]
+    fn small_material_move_that_stalemates_wins_instead_of_drawing() {
+        // 双方均不超过2子：黑方 (1,2)→(0,2) 把白方单子困在角上
+        let notation = "WB../.B../..../....";
+        let expected = (Some(GameResult::PlayerWin), Some(GameEndReason::Stalemate));
+
+        let mut headless = game_from(notation, Side::Black);
+        headless.settings.small_material_draw = true;
+        headless.play_move((1, 2), (0, 2)).unwrap();
+        assert_eq!((headless.last_result, headless.end_reason), expected);
+
+        // 经状态机逐步推进的结果相同，终局时轮到被困的白方
+        let mut animated = game_from(notation, Side::Black);
+        animated.settings.small_material_draw = true;
+        animated.pending_move = Some(PendingMove { from: (1, 2), to: (0, 2), is_ai: false });
+        animated.state = GameState::PieceMoving;
+        while advance(&mut animated) {}
+        assert_eq!((animated.last_result, animated.end_reason), expected);
+        assert_eq!(animated.current_turn, Side::White);
+        assert_eq!(animated.check_game_end(), Some((GameResult::PlayerWin, GameEndReason::Stalemate)));
+    }
+
+    /// 双方各把一枚棋子走出再走回，不吃子地原地徘徊，直到分出结果或走满 `max_plies` 步
+    fn shuffle(game: &mut Game, max_plies: usize) {
+        let cycle = [((0, 1), (1, 1)), ((0, 2), (1, 2)), ((1, 1), (0, 1)), ((1, 2), (0, 2))];
+        for &(from, to) in cycle.iter().cycle().take(max_plies) {
+            if game.last_result.is_some() {
+                break;
+            }
+            game.play_move(from, to).unwrap();
+        }
+    }
+
+    #[test]
+    fn ai_avoids_seen_positions_only_in_demo_games() {
+        let mut game = new_game(true);
+        shuffle(&mut game, 4);
+        assert!(!game.seen_positions_for_turn().is_empty());
+        assert!(game.ai_for_turn(5).seen_positions.is_empty());
+
+        game.demo = true;
+        assert_eq!(game.ai_for_turn(5).seen_positions, game.seen_positions_for_turn());
+    }
+
+    #[test]
+    fn moves_since_capture_resets_on_capture() {
+        let mut game = new_game(true);
+        for (from, to) in [((0, 1), (1, 1)), ((0, 2), (0, 1)), ((1, 1), (1, 2)), ((3, 2), (2, 2))] {
+            game.play_move(from, to).unwrap();
+        }
+        assert_eq!(game.moves_since_capture(), 4);
+
+        // 黑方 (1,0)→(1,1) 吃子，计数归零，此后重新累计
+        assert!(!game.play_move((1, 0), (1, 1)).unwrap().captured.is_empty());
+        assert_eq!(game.moves_since_capture(), 0);
+        let (from, to) = game
+            .rules
+            .valid_moves(&game.board, game.current_turn)
+            .into_iter()
+            .find(|&(from, to)| game.board.captures_if_moved(from, to).is_empty())
+            .unwrap();
+        game.play_move(from, to).unwrap();
+        assert_eq!(game.moves_since_capture(), 1);
+    }
+
+    #[test]
+    fn no_capture_draw_fires_exactly_at_the_limit() {
+        assert_eq!(GameSettings::default().no_capture_draw_limit, None);
+
+        let mut game = new_game(true);
+        game.settings.repetition_draw = false;
+        game.settings.no_capture_draw_limit = Some(6);
+        shuffle(&mut game, 5);
+        assert_eq!(game.moves_since_capture(), 5);
+        assert!(game.last_result.is_none());
+        game.play_move((0, 2), (1, 2)).unwrap();
+        assert_eq!(game.moves_since_capture(), 6);
+        assert_eq!(game.last_result, Some(GameResult::Draw));
+        assert_eq!(game.end_reason, Some(GameEndReason::NoCaptureLimit));
+
+        // 默认关闭时同样的走法不判和
+        let mut game = new_game(true);
+        game.settings.repetition_draw = false;
+        shuffle(&mut game, 20);
+        assert!(game.last_result.is_none());
+    }
+
+    #[test]
+    fn stalling_position_is_drawn_by_repetition() {
+        let mut game = new_game(true);
+        shuffle(&mut game, 20);
+        // 黑方第1、5、9步走完后为同一局面，第三次出现时判和
+        assert_eq!(game.move_history.len(), 9);
+        assert_eq!(game.last_result, Some(GameResult::Draw));
+        assert_eq!(game.end_reason, Some(GameEndReason::Repetition));
+        assert_eq!(game.position_repetitions(), REPETITION_DRAW_COUNT);
+    }
+
+    #[test]
+    fn repetition_draw_can_be_disabled() {
+        let mut game = new_game(true);
+        game.settings.repetition_draw = false;
+        shuffle(&mut game, 20);
+        assert_eq!(game.move_history.len(), 20);
+        assert!(game.last_result.is_none());
+        assert_eq!(game.position_repetitions(), 5);
+    }
+
+    #[test]
+    fn failed_undo_leaves_game_unchanged() {
+        let mut game = new_game(true);
+        game.play_move((0, 1), (1, 1)).unwrap();
+        game.play_move((0, 2), (1, 2)).unwrap();
+        // 玩家那一步的记录与棋盘不一致：AI一步可以撤销，玩家一步撤销失败
+        game.move_history[0].piece_id = 99;
+        let history = game.move_history.clone();
+        let notation = game.board.to_notation();
+
+        assert!(game.perform_undo().is_err());
+        assert_eq!(game.move_history, history);
+        assert_eq!(game.current_turn, Side::Black);
+        assert_eq!(game.board.to_notation(), notation);
+    }
+
+    #[test]
+    fn repeated_undo_returns_to_the_start_then_reports_no_history() {
+        let mut game = new_game(true);
+        // 玩家执黑，第五步吃掉白子，电脑随后应一步
+        let moves = [((0, 1), (1, 1)), ((0, 2), (0, 1)), ((1, 1), (1, 2)), ((3, 2), (2, 2)), ((1, 0), (1, 1))];
+        let mut before_player_moves = Vec::new();
+        for (i, (from, to)) in moves.into_iter().enumerate() {
+            if i % 2 == 0 {
+                before_player_moves.push(game.board.clone());
+            }
+            game.play_move(from, to).unwrap();
+        }
+        assert!(!game.move_history.last().unwrap().captured.is_empty());
+        let (from, to) = game.board.valid_moves(Side::White)[0];
+        game.play_move(from, to).unwrap();
+
+        // 每次悔棋回到玩家上一步之前的局面
+        while let Some(expected) = before_player_moves.pop() {
+            assert_eq!(game.undo_status(), UndoStatus::Available);
+            game.handle_event(GameEvent::StartUndo).unwrap();
+            game.handle_event(GameEvent::UndoAnimationComplete).unwrap();
+            assert_eq!(game.state, GameState::WaitingForPlayer);
+            assert_eq!(game.current_turn, Side::Black);
+            assert_eq!(game.move_history.len(), before_player_moves.len() * 2);
+            assert!(game.board.positions_equal(&expected));
+        }
+        assert!(game.is_at_start());
+
+        // 回到开局后不能再悔棋，再次请求不改变对局
+        assert_eq!(game.undo_status(), UndoStatus::NoHistory);
+        game.handle_event(GameEvent::StartUndo).unwrap();
+        assert_eq!(game.state, GameState::WaitingForPlayer);
+        assert!(game.move_history.is_empty());
+        assert!(game.board.positions_equal(&Board::initial()));
+    }
+
+    #[test]
+    fn is_at_start_until_a_move_is_played_and_again_after_undo() {
+        let mut game = new_game(true);
+        assert!(game.is_at_start());
+
+        game.play_move((0, 1), (1, 1)).unwrap();
+        game.play_move((0, 2), (1, 2)).unwrap();
+        assert!(!game.is_at_start());
+
+        game.perform_undo().unwrap();
+        assert!(game.is_at_start());
+
+        // 棋子走回初始布局但有行棋历史，不算开局状态
+        shuffle(&mut game, 4);
+        assert!(game.board.positions_equal(&Board::initial()));
+        assert!(!game.is_at_start());
+    }
+
+    #[test]
+    fn flag_fall_ends_the_game_for_the_side_to_move() {
+        let mut game = new_game(true);
+        game.settings.time_control = Some(clock::TimeControl { base_secs: 0, increment_secs: 0 });
+        game.reset_clock();
+        assert_eq!(game.clock.flagged(), Some(Side::Black));
+
+        // 不是行棋方的超时事件被忽略
+        game.handle_event(GameEvent::FlagFall { side: Side::White }).unwrap();
+        assert_eq!(game.state, GameState::WaitingForPlayer);
+
+        // 暂停时不判负
+        assert_eq!(game.tick_clock(true), None);
+        assert_eq!(game.state, GameState::WaitingForPlayer);
+
+        assert_eq!(game.tick_clock(false), Some(GameResult::AiWin));
+        assert_eq!(game.state, GameState::GameOverDialog(GameResult::AiWin));
+        assert_eq!(game.end_reason, Some(GameEndReason::Timeout));
+    }
+
+    #[test]
+    fn turn_text_names_the_side_to_move() {
+        use crate::t;
+        let mut game = new_game(true);
+        assert_eq!(game.turn_text(), t!("status.turn_player"));
+        game.play_move((0, 1), (1, 1)).unwrap();
+        assert_eq!(game.turn_text(), t!("status.turn_ai"));
+
+        // 演示对局按颜色显示行棋方
+        game.demo = true;
+        assert_eq!(game.turn_text(), t!("status.demo_turn", side = t!("game.white")));
+        game.play_move((0, 2), (0, 1)).unwrap();
+        assert_eq!(game.turn_text(), t!("status.demo_turn", side = t!("game.black")));
+    }
+
+    #[test]
+    fn undo_status_explains_why_undo_is_unavailable() {
+        let mut game = new_game(true);
+        assert_eq!(game.undo_status(), UndoStatus::NoHistory);
+
+        // 玩家行棋后轮到电脑思考
+        game.play_move((0, 1), (1, 1)).unwrap();
+        assert_eq!(game.state, GameState::AiThinking);
+        assert_eq!(game.undo_status(), UndoStatus::WrongState);
+
+        game.play_move((0, 2), (1, 2)).unwrap();
+        assert_eq!(game.undo_status(), UndoStatus::Available);
+        assert!(game.can_undo());
+
+        // 棋子移动动画进行中
+        let piece_id = game.board.piece_at(1, 1).unwrap().id;
+        game.handle_event(GameEvent::PlayerSelectPiece { piece_id, start_pos: (1, 1) }).unwrap();
+        game.handle_event(GameEvent::PlayerClickTarget { target_pos: (2, 1) }).unwrap();
+        assert_eq!(game.state, GameState::PieceMoving);
+        assert_eq!(game.undo_status(), UndoStatus::Animating);
+        while advance(&mut game) {}
+        game.play_move((1, 2), (0, 2)).unwrap();
+        assert_eq!(game.undo_status(), UndoStatus::Available);
+
+        // 演示对局不能悔棋
+        game.demo = true;
+        assert_eq!(game.undo_status(), UndoStatus::WrongState);
+
+        for status in [UndoStatus::NoHistory, UndoStatus::WrongState, UndoStatus::Animating] {
+            assert!(!status.is_available());
+            assert!(status.reason_text().is_some());
+        }
+        assert_eq!(UndoStatus::Available.reason_text(), None);
+    }
+
+    #[test]
+    fn undo_steps_is_zero_when_only_the_ai_has_moved() {
+        let mut game = new_game(false);
+        game.play_move((0, 1), (1, 1)).unwrap();
+        assert_eq!(game.state, GameState::WaitingForPlayer);
+        assert_eq!(game.undo_steps(), 0);
+        assert!(!game.can_undo());
+
+        game.handle_event(GameEvent::StartUndo).unwrap();
+        assert_eq!(game.state, GameState::WaitingForPlayer);
+        assert_eq!(game.move_history.len(), 1);
+        assert_eq!(game.current_turn, Side::White);
+    }
+
+    #[test]
+    fn undo_steps_is_one_when_the_player_moved_last() {
+        let mut game = new_game(true);
+        game.play_move((0, 1), (1, 1)).unwrap();
+        assert_eq!(game.undo_steps(), 1);
+
+        game.perform_undo().unwrap();
+        assert!(game.move_history.is_empty());
+        assert_eq!(game.current_turn, Side::Black);
+        assert!(game.board.positions_equal(&Board::initial()));
+    }
+
+    #[test]
+    fn undo_steps_is_two_when_the_ai_moved_last() {
+        let mut game = new_game(true);
+        game.play_move((0, 1), (1, 1)).unwrap();
+        game.play_move((0, 2), (1, 2)).unwrap();
+        assert_eq!(game.undo_steps(), 2);
+
+        game.handle_event(GameEvent::StartUndo).unwrap();
+        game.handle_event(GameEvent::UndoAnimationComplete).unwrap();
+        assert_eq!(game.state, GameState::WaitingForPlayer);
+        assert!(game.move_history.is_empty());
+        assert_eq!(game.current_turn, Side::Black);
+        assert!(game.board.positions_equal(&Board::initial()));
+    }
+
+    #[test]
+    fn undo_after_ai_opening_keeps_the_ai_move() {
+        let mut game = new_game(false);
+        game.play_move((0, 1), (1, 1)).unwrap();
+        let after_ai = game.board.to_notation();
+        game.play_move((0, 2), (0, 1)).unwrap();
+        game.play_move((1, 1), (1, 2)).unwrap();
+        assert_eq!(game.undo_steps(), 2);
+
+        game.handle_event(GameEvent::StartUndo).unwrap();
+        game.handle_event(GameEvent::UndoAnimationComplete).unwrap();
+        assert_eq!(game.move_history.len(), 1);
+        assert_eq!(game.current_turn, Side::White);
+        assert_eq!(game.board.to_notation(), after_ai);
+        assert_eq!(game.undo_steps(), 0);
+    }
+}
@@ -0,0 +1,76 @@
+//! 开局库
+//!
+//! 收录开局阶段各局面下同样强的应着（评估分相同），
+//! 供3级以上的电脑直接选用，省去开局搜索。
+//! 每个对称等价类只收录一个局面，查询时按 [`Board::canonical_hash`] 匹配并换算应着
+
+use crate::game::board::Board;
+use crate::game::piece::Side;
+use crate::game::rules::Move;
+
+/// 开局库条目：局面记谱、行棋方与同样强的应着
+const BOOK: [(&str, Side, &[Move]); 2] = [
+    // 初始局面：黑方边棋向中间靠拢（左右对称）
+    ("WWWW/W..W/B..B/BBBB", Side::Black, &[((0, 1), (1, 1)), ((3, 1), (2, 1))]),
+    // 黑方边棋靠中后（另一侧为其镜像）：白方边棋向中间靠拢
+    ("WWWW/W..W/.B.B/BBBB", Side::White, &[((0, 2), (1, 2)), ((3, 2), (2, 2))]),
+];
+
+/// 查询开局库中当前局面的应着，未收录时返回空
+///
+/// 收录局面经对称变换与当前局面相同时，应着按同一变换换算到当前棋盘
+pub fn book_replies(board: &Board, side: Side) -> Vec<Move> {
+    let canonical = board.canonical_hash();
+    let hash = board.zobrist_hash();
+    BOOK.iter()
+        .filter(|(_, book_side, _)| *book_side == side)
+        .filter_map(|(position, _, replies)| {
+            let book = Board::from_notation(position).ok()?;
+            if book.size != board.size || book.canonical_hash() != canonical {
+                return None;
+            }
+            let index = book.symmetries().iter().position(|symmetric| symmetric.zobrist_hash() == hash)?;
+            let map = |pos| book.symmetric_square(index, pos);
+            Some(replies.iter().map(|&(from, to)| (map(from), map(to))).collect())
+        })
+        .next()
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(mut moves: Vec<Move>) -> Vec<Move> {
+        moves.sort_unstable();
+        moves
+    }
+
+    #[test]
+    fn mirrored_position_uses_the_mapped_entry() {
+        let board = Board::from_notation("WWWW/W..W/.B.B/BBBB").unwrap();
+        assert_eq!(book_replies(&board, Side::White), vec![((0, 2), (1, 2)), ((3, 2), (2, 2))]);
+
+        // 左右镜像的局面未单独收录，应着随之镜像
+        let mirrored = board.mirror_horizontal();
+        assert_eq!(mirrored.to_notation(), "WWWW/W..W/B.B./BBBB");
+        assert_eq!(sorted(book_replies(&mirrored, Side::White)), vec![((0, 2), (1, 2)), ((3, 2), (2, 2))]);
+
+        // 换算后的应着在每种对称局面中都是合法走法
+        for symmetric in board.symmetries() {
+            let replies = book_replies(&symmetric, Side::White);
+            assert_eq!(replies.len(), 2);
+            for (from, to) in replies {
+                assert!(crate::game::rules::is_valid_move(&symmetric, from, to, Side::White), "{}", symmetric.to_notation());
+            }
+        }
+    }
+
+    #[test]
+    fn unlisted_positions_and_wrong_side_have_no_replies() {
+        let board = Board::initial();
+        assert!(book_replies(&board, Side::White).is_empty());
+        assert!(book_replies(&Board::from_notation("WWWW/W..W/.BB./BBBB").unwrap(), Side::White).is_empty());
+        assert!(book_replies(&Board::initial_sized(5), Side::Black).is_empty());
+    }
+}
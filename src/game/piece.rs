@@ -50,7 +50,7 @@ pub enum PieceState {
 }
 
 /// 棋子
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Piece {
     /// 棋子唯一ID
     pub id: u8,
@@ -83,21 +83,44 @@ impl Piece {
     }
 }
 
-/// 获取初始棋子布局
-pub fn initial_pieces() -> Vec<Piece> {
-    let mut pieces = Vec::with_capacity(12);
+/// 指定棋盘大小下每方初始棋子数量（底线整行 + 次线两端）
+pub const fn pieces_per_side(size: u8) -> usize {
+    size as usize + 2
+}
+
+/// 黑方初始位置：底线（y=0）整行，次线（y=1）两端
+///
+/// 4x4 时为 (0,0), (1,0), (2,0), (3,0), (0,1), (3,1)
+pub fn initial_black_positions(size: u8) -> Vec<(u8, u8)> {
+    (0..size)
+        .map(|x| (x, 0))
+        .chain([(0, 1), (size - 1, 1)])
+        .collect()
+}
+
+/// 白方初始位置：与黑方关于水平中线对称
+///
+/// 4x4 时为 (0,3), (1,3), (2,3), (3,3), (0,2), (3,2)
+pub fn initial_white_positions(size: u8) -> Vec<(u8, u8)> {
+    initial_black_positions(size)
+        .into_iter()
+        .map(|(x, y)| (x, size - 1 - y))
+        .collect()
+}
+
+/// 获取指定棋盘大小的初始棋子布局
+pub fn initial_pieces(size: u8) -> Vec<Piece> {
+    let mut pieces = Vec::with_capacity(pieces_per_side(size) * 2);
     let mut id = 1u8;
 
     // 黑方初始位置
-    let black_positions = [(0, 0), (1, 0), (2, 0), (3, 0), (0, 1), (3, 1)];
-    for (x, y) in black_positions {
+    for (x, y) in initial_black_positions(size) {
         pieces.push(Piece::new(id, Side::Black, x, y));
         id += 1;
     }
 
     // 白方初始位置
-    let white_positions = [(0, 3), (1, 3), (2, 3), (3, 3), (0, 2), (3, 2)];
-    for (x, y) in white_positions {
+    for (x, y) in initial_white_positions(size) {
         pieces.push(Piece::new(id, Side::White, x, y));
         id += 1;
     }
@@ -0,0 +1,175 @@
+//! 棋局回放
+//!
+//! 根据行棋历史重建任意步数时的棋盘，供回放界面只读浏览
+
+use crate::game::ai::AiPlayer;
+use crate::game::board::Board;
+use crate::game::piece::Side;
+use crate::game::MoveRecord;
+
+/// 失误复盘时检查的玩家最近行棋数
+pub const BLUNDER_REVIEW_MOVES: usize = 6;
+
+/// 棋局回放
+#[derive(Debug, Clone)]
+pub struct Replay {
+    /// 回放起始局面（第0步之前）
+    start_board: Board,
+    /// 行棋历史
+    history: Vec<MoveRecord>,
+    /// 当前所处步数（已执行的记录数，0..=history.len()）
+    index: usize,
+    /// 当前步数对应的棋盘
+    board: Board,
+}
+
+impl Replay {
+    /// 由终局棋盘和行棋历史创建回放，初始定位到终局
+    pub fn new(final_board: &Board, history: &[MoveRecord]) -> Self {
+        // 从终局逆序撤销所有记录，得到起始局面
+        let mut start_board = final_board.clone();
+        for record in history.iter().rev() {
+            let _ = start_board.undo_move(record);
+        }
+
+        Self {
+            start_board,
+            history: history.to_vec(),
+            index: history.len(),
+            board: final_board.clone(),
+        }
+    }
+
+    /// 当前步数
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// 总步数
+    pub fn len(&self) -> usize {
+        self.history.len()
+    }
+
+    /// 是否没有任何行棋记录
+    pub fn is_empty(&self) -> bool {
+        self.history.is_empty()
+    }
+
+    /// 当前步数对应的棋盘
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// 下一步将要执行的记录（用于播放动画）
+    pub fn next_record(&self) -> Option<&MoveRecord> {
+        self.history.get(self.index)
+    }
+
+    /// 跳转到指定步数
+    pub fn seek(&mut self, index: usize) {
+        let index = index.min(self.history.len());
+        let mut board = self.start_board.clone();
+        for record in &self.history[..index] {
+            board.apply_record(record);
+        }
+        self.board = board;
+        self.index = index;
+    }
+
+    /// 前进一步，已到终局时返回 false
+    pub fn step_forward(&mut self) -> bool {
+        match self.history.get(self.index) {
+            Some(record) => {
+                self.board.apply_record(record);
+                self.index += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 后退一步，已在起始局面时返回 false
+    pub fn step_backward(&mut self) -> bool {
+        if self.index == 0 {
+            return false;
+        }
+        self.index -= 1;
+        let _ = self.board.undo_move(&self.history[self.index]);
+        true
+    }
+
+    /// 找出玩家最近 [`BLUNDER_REVIEW_MOVES`] 步中使局面评估下降最多的一步（失误）
+    ///
+    /// 以玩家视角比较行棋前与对方应着后（没有应着时为行棋后）的评估分，
+    /// 返回该步在行棋历史中的下标；没有任何一步使评估下降时返回 None
+    pub fn find_blunder(&self, player_side: Side, ai: &AiPlayer) -> Option<usize> {
+        let mut boards = Vec::with_capacity(self.history.len() + 1);
+        let mut board = self.start_board.clone();
+        boards.push(board.clone());
+        for record in &self.history {
+            board.apply_record(record);
+            boards.push(board.clone());
+        }
+
+        let player_eval = |board: &Board| ai.evaluate(board, player_side);
+        self.history
+            .iter()
+            .enumerate()
+            .filter(|(_, record)| record.side == player_side)
+            .map(|(index, _)| index)
+            .rev()
+            .take(BLUNDER_REVIEW_MOVES)
+            .map(|index| {
+                let reply = self.history.get(index + 1).is_some_and(|r| r.side != player_side);
+                let after = if reply { index + 2 } else { index + 1 };
+                (index, player_eval(&boards[after]) - player_eval(&boards[index]))
+            })
+            .filter(|&(_, swing)| swing < 0)
+            .min_by_key(|&(index, swing)| (swing, std::cmp::Reverse(index)))
+            .map(|(index, _)| index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::rules::Move;
+
+    /// 从开局依次执行走法（黑方先行），返回终局棋盘与行棋历史
+    fn play(moves: &[Move]) -> (Board, Vec<MoveRecord>) {
+        let mut board = Board::initial();
+        let mut side = Side::Black;
+        let mut history = Vec::new();
+        for &(from, to) in moves {
+            history.push(board.execute_move(from, to, side).unwrap());
+            side = side.opposite();
+        }
+        (board, history)
+    }
+
+    #[test]
+    fn find_blunder_flags_the_move_that_loses_a_piece() {
+        // 玩家执白：第二步 (3,2)→(2,2) 让黑方随即吃掉 (0,1) 的白子
+        let moves = [((0, 1), (1, 1)), ((0, 2), (0, 1)), ((1, 1), (1, 2)), ((3, 2), (2, 2)), ((1, 0), (1, 1))];
+        let (board, history) = play(&moves);
+        assert!(!history[4].captured.is_empty());
+
+        let replay = Replay::new(&board, &history);
+        assert_eq!(replay.find_blunder(Side::White, &AiPlayer::new(3)), Some(3));
+        // 复盘不移动回放位置
+        assert_eq!(replay.index(), history.len());
+    }
+
+    #[test]
+    fn find_blunder_ignores_safe_moves() {
+        // 同一局的前四步：黑方尚未吃子，白方的两步都没有丢子
+        let moves = [((0, 1), (1, 1)), ((0, 2), (0, 1)), ((1, 1), (1, 2)), ((3, 2), (2, 2))];
+        let (board, history) = play(&moves);
+        let replay = Replay::new(&board, &history);
+        assert_eq!(replay.find_blunder(Side::White, &AiPlayer::new(3)), None);
+
+        // 没有行棋记录时没有失误
+        let replay = Replay::new(&Board::initial(), &[]);
+        assert_eq!(replay.find_blunder(Side::Black, &AiPlayer::new(3)), None);
+    }
+}
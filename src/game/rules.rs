@@ -1,8 +1,97 @@
 //! 游戏规则验证
 
 use crate::game::board::Board;
-use crate::game::piece::Side;
-use crate::game::GameResult;
+use crate::game::piece::{pieces_per_side, Side};
+use crate::game::settings::GameSettings;
+use crate::game::{GameEndReason, GameResult};
+
+/// 走法：(起始位置, 目标位置)
+pub type Move = ((u8, u8), (u8, u8));
+
+/// 规则集：对局使用的走法、吃子与胜负判定
+///
+/// `Game` 的走法校验、可走位置、吃子、胜负与自定义局面校验均通过此接口调用，
+/// 新的变体只需提供另一实现。电脑搜索、`Board::execute_move` 等棋盘层接口
+/// 以及编辑器的预校验固定按标准规则计算，不随规则集变化
+pub trait RuleSet: std::fmt::Debug {
+    /// 棋子移动后被吃掉的棋子ID列表（棋盘已反映该次移动）
+    fn captures(&self, board: &Board, moved_piece_id: u8) -> Vec<u8>;
+
+    /// 判断对局是否结束，返回结果及原因，未结束返回 None
+    fn is_game_over(
+        &self,
+        board: &Board,
+        side_to_move: Side,
+        player_side: Side,
+        settings: &GameSettings,
+    ) -> Option<(GameResult, GameEndReason)>;
+
+    /// 检查移动是否合法
+    fn is_valid_move(&self, board: &Board, from: (u8, u8), to: (u8, u8), side: Side) -> bool;
+
+    /// 某方所有合法移动
+    fn valid_moves(&self, board: &Board, side: Side) -> Vec<Move>;
+
+    /// 校验自定义局面能否作为对局起点（终局判定与对局使用相同的规则设置）
+    fn validate_setup(&self, board: &Board, side_to_move: Side, settings: &GameSettings) -> Result<(), SetupError>;
+}
+
+/// 标准规则：二比一吃子、担吃与单子规则
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StandardRules;
+
+impl RuleSet for StandardRules {
+    fn captures(&self, board: &Board, moved_piece_id: u8) -> Vec<u8> {
+        calculate_captures(board, moved_piece_id)
+    }
+
+    fn is_game_over(
+        &self,
+        board: &Board,
+        side_to_move: Side,
+        player_side: Side,
+        settings: &GameSettings,
+    ) -> Option<(GameResult, GameEndReason)> {
+        check_game_end_with(board, side_to_move, player_side, settings.small_material_draw)
+    }
+
+    fn is_valid_move(&self, board: &Board, from: (u8, u8), to: (u8, u8), side: Side) -> bool {
+        is_valid_move(board, from, to, side)
+    }
+
+    fn valid_moves(&self, board: &Board, side: Side) -> Vec<Move> {
+        get_valid_moves(board, side)
+    }
+
+    fn validate_setup(&self, board: &Board, side_to_move: Side, settings: &GameSettings) -> Result<(), SetupError> {
+        validate_setup(board, side_to_move, settings)
+    }
+}
+
+/// 走法不合法的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IllegalMoveReason {
+    /// 起始位置没有执子方的在盘棋子
+    NoOwnPiece,
+    /// 目标位置在棋盘外
+    OutOfBoard,
+    /// 目标位置已有棋子
+    Occupied,
+    /// 不是上下左右相邻的一格（斜走或多走）
+    NotAdjacent,
+}
+
+impl IllegalMoveReason {
+    /// 获取本地化的说明文本（用于新手提示）
+    pub fn display_text(&self) -> String {
+        match self {
+            IllegalMoveReason::NoOwnPiece => crate::t!("illegal.no_own_piece"),
+            IllegalMoveReason::OutOfBoard => crate::t!("illegal.out_of_board"),
+            IllegalMoveReason::Occupied => crate::t!("illegal.occupied"),
+            IllegalMoveReason::NotAdjacent => crate::t!("illegal.not_adjacent"),
+        }
+    }
+}
 
 /// 检查移动是否合法
 /// 
@@ -14,18 +103,30 @@ use crate::game::GameResult;
 /// 
 /// 返回: 是否合法
 pub fn is_valid_move(board: &Board, from: (u8, u8), to: (u8, u8), side: Side) -> bool {
+    is_valid_move_detailed(board, from, to, side).is_ok()
+}
+
+/// 检查移动是否合法，不合法时返回原因
+///
+/// 按起始棋子、目标在棋盘内、目标为空、相邻一格的顺序检查，返回第一个不满足的条件
+pub fn is_valid_move_detailed(
+    board: &Board,
+    from: (u8, u8),
+    to: (u8, u8),
+    side: Side,
+) -> Result<(), IllegalMoveReason> {
     // 检查起始位置有己方棋子
     let piece = match board.piece_at(from.0, from.1) {
         Some(p) if p.side == side => p,
-        _ => return false,
+        _ => return Err(IllegalMoveReason::NoOwnPiece),
     };
 
     // 检查目标位置为空且在棋盘内
-    if !Board::is_valid_pos(to.0 as i8, to.1 as i8) {
-        return false;
+    if !board.is_valid_pos(to.0 as i8, to.1 as i8) {
+        return Err(IllegalMoveReason::OutOfBoard);
     }
     if !board.is_empty(to.0, to.1) {
-        return false;
+        return Err(IllegalMoveReason::Occupied);
     }
 
     // 检查移动距离（只能上下左右移动一格）
@@ -37,19 +138,23 @@ pub fn is_valid_move(board: &Board, from: (u8, u8), to: (u8, u8), side: Side) ->
     let is_vertical = dx == 0 && dy.abs() == 1;
 
     if !is_horizontal && !is_vertical {
-        return false;
+        return Err(IllegalMoveReason::NotAdjacent);
     }
 
     // 棋子状态检查（不能移动被吃掉的棋子）
     if !piece.active {
-        return false;
+        return Err(IllegalMoveReason::NoOwnPiece);
     }
 
-    true
+    Ok(())
 }
 
 /// 计算移动后的吃子
 /// 
+/// 横、纵两个方向分别检查，同时成立时两个方向的棋子一并吃掉（不重复）。
+/// 单子担吃理论上可在十字形中吃四枚，但单子只能从空着的相邻点走入，
+/// 实战中至多在一个方向上担吃两枚
+///
 /// 返回: 被吃掉的棋子ID列表
 pub fn calculate_captures(board: &Board, moved_piece_id: u8) -> Vec<u8> {
     let moved_piece = match board.piece_by_id(moved_piece_id) {
@@ -62,7 +167,11 @@ pub fn calculate_captures(board: &Board, moved_piece_id: u8) -> Vec<u8> {
     let mut captured = Vec::new();
 
     if board.is_single_piece_mode() {
-        // 单子状态：检查"担"吃
+        // 单子状态："二比一"对双方均失效，只有单子方才能"担"吃
+        // 优势方行棋（即使对方只剩一子）不触发任何吃子
+        if board.count_active(side) != 1 {
+            return captured;
+        }
         // 检查水平方向
         check_single_piece_capture(board, x, y, side, true, &mut captured);
         // 检查垂直方向
@@ -85,6 +194,8 @@ pub fn calculate_captures(board: &Board, moved_piece_id: u8) -> Vec<u8> {
 /// 2. 这3枚棋子紧紧相连（无间隔）
 /// 3. 其中两枚是本方棋子（且一枚是刚移动的），一枚是对方棋子
 /// 4. 3枚棋子占据的格子两侧必须是边界或空点
+///
+/// 每次调用只扫描经过 (x, y) 的一整行（或一整列）
 fn check_two_vs_one(
     board: &Board,
     x: u8,
@@ -94,89 +205,37 @@ fn check_two_vs_one(
     moved_piece_id: u8,
     captured: &mut Vec<u8>,
 ) {
-    let dx = if horizontal { 1 } else { 0 };
-    let dy = if horizontal { 0 } else { 1 };
-
-    // 检查两种可能的"二比一"模式：
-    // 模式1: [本方][本方(刚移动)][对方] - 本方在左/下，对方在右/上
-    // 模式2: [对方][本方(刚移动)][本方] - 对方在左/下，本方在右/上
-    // 模式3: [本方(刚移动)][本方][对方] - 刚移动的本方在最左/下
-    // 模式4: [对方][本方][本方(刚移动)] - 刚移动的本方在最右/上
-
-    // 先检查从刚移动棋子向左/下的情况
-    check_two_vs_one_in_direction(board, x, y, side, dx, dy, moved_piece_id, captured);
-    // 再检查从刚移动棋子向右/上的情况
-    check_two_vs_one_in_direction(board, x, y, side, -dx, -dy, moved_piece_id, captured);
-}
-
-/// 在指定方向检查"二比一"吃棋
-fn check_two_vs_one_in_direction(
-    board: &Board,
-    x: u8,
-    y: u8,
-    side: Side,
-    _dx: i8,
-    dy: i8,
-    moved_piece_id: u8,
-    captured: &mut Vec<u8>,
-) {
-    // 确认刚移动的棋子仍然存在且活跃
-    if board.piece_by_id(moved_piece_id).map_or(true, |p| !p.active) {
-        return;
-    }
+    // 沿扫描方向的坐标：水平方向取x，垂直方向取y
+    let coord = |pos: (u8, u8)| if horizontal { pos.0 } else { pos.1 };
 
-    // 收集这一行/列上所有棋子的位置
-    // 水平方向：固定y，变化x；垂直方向：固定x，变化y
-    let is_horizontal = dy == 0;
-
-    // 获取这一行/列上的所有棋子（按位置排序）
-    let pieces_on_line: Vec<_> = board.pieces.iter()
-        .filter(|p| p.active && if is_horizontal { p.position.1 == y } else { p.position.0 == x })
-        .map(|p| p)
+    // 获取这一行/列上的所有棋子，按位置排序
+    let mut pieces_on_line: Vec<_> = board.pieces.iter()
+        .filter(|p| p.active && if horizontal { p.position.1 == y } else { p.position.0 == x })
         .collect();
 
     // 必须有且只有3枚棋子
     if pieces_on_line.len() != 3 {
         return;
     }
+    pieces_on_line.sort_by_key(|p| coord(p.position));
 
     // 检查这3枚棋子是否紧紧相连（相邻位置差为1）
-    let mut positions: Vec<(u8, u8)> = pieces_on_line.iter().map(|p| p.position).collect();
-    positions.sort_by(|a, b| {
-        let a_coord = if is_horizontal { a.0 } else { a.1 };
-        let b_coord = if is_horizontal { b.0 } else { b.1 };
-        a_coord.cmp(&b_coord)
-    });
-
-    // 检查是否紧紧相连
-    for i in 0..positions.len() - 1 {
-        let coord1 = if is_horizontal { positions[i].0 } else { positions[i].1 };
-        let coord2 = if is_horizontal { positions[i + 1].0 } else { positions[i + 1].1 };
-        if coord2 - coord1 != 1 {
-            return; // 不相连
-        }
+    let first_coord = coord(pieces_on_line[0].position);
+    let last_coord = coord(pieces_on_line[2].position);
+    if last_coord - first_coord != 2 {
+        return; // 不相连
     }
 
     // 检查两侧是否为空或边界
-    let first_coord = if is_horizontal { positions[0].0 } else { positions[0].1 };
-    let last_coord = if is_horizontal { positions[2].0 } else { positions[2].1 };
-
-    // 检查左侧/下方
-    let left_coord = first_coord as i8 - 1;
-    if left_coord >= 0 {
-        let check_pos = if is_horizontal { (left_coord as u8, y) } else { (x, left_coord as u8) };
-        if !board.is_empty(check_pos.0, check_pos.1) {
-            return; // 左侧/下方有棋子
-        }
-    }
-
-    // 检查右侧/上方
-    let right_coord = last_coord as i8 + 1;
-    if right_coord < 4 {
-        let check_pos = if is_horizontal { (right_coord as u8, y) } else { (x, right_coord as u8) };
-        if !board.is_empty(check_pos.0, check_pos.1) {
-            return; // 右侧/上方有棋子
+    let is_open = |c: i8| {
+        if c < 0 || c >= board.size as i8 {
+            return true;
         }
+        let pos = if horizontal { (c as u8, y) } else { (x, c as u8) };
+        board.is_empty(pos.0, pos.1)
+    };
+    if !is_open(first_coord as i8 - 1) || !is_open(last_coord as i8 + 1) {
+        return;
     }
 
     // 现在确定是3枚棋子紧紧相连，检查是否满足"二比一"条件
@@ -185,63 +244,24 @@ fn check_two_vs_one_in_direction(
     // 1. [本方][本方][对方] - 本方在位置0-1（相邻），对方在位置2
     // 2. [对方][本方][本方] - 对方在位置0，本方在位置1-2（相邻）
     //
+    // 刚移动的棋子可以是两枚本方棋子中的任一枚（紧挨对方的一枚或外端的一枚）
+    //
     // 无效排列（不能吃子）：
     // [本方][对方][本方] - 本方不相邻（中间隔着对方）
-
-    // 获取3枚棋子按位置排序后的信息
-    let sorted_pieces: Vec<_> = pieces_on_line.iter()
-        .map(|p| (p.position, p.side, p.id))
-        .collect();
-
-    // 确定每枚棋子的位置索引（0, 1, 2）
-    let mut pieces_with_index: Vec<(usize, Side, u8)> = Vec::new();
-    for (pos, side_val, id) in sorted_pieces {
-        let idx = if is_horizontal {
-            (pos.0 - first_coord) as usize
-        } else {
-            (pos.1 - first_coord) as usize
-        };
-        pieces_with_index.push((idx, side_val, id));
-    }
-
-    // 检查本方两枚棋子是否相邻
-    let own_pieces_indices: Vec<usize> = pieces_with_index.iter()
-        .filter(|(_, s, _)| *s == side)
-        .map(|(idx, _, _)| *idx)
-        .collect();
-    let enemy_pieces_indices: Vec<usize> = pieces_with_index.iter()
-        .filter(|(_, s, _)| *s != side)
-        .map(|(idx, _, _)| *idx)
-        .collect();
-
-    // 必须是2枚本方，1枚对方
-    if own_pieces_indices.len() != 2 || enemy_pieces_indices.len() != 1 {
-        return;
-    }
-
-    // 本方两枚棋子必须相邻（索引差为1）
-    let own_idx_diff = (own_pieces_indices[0] as i8 - own_pieces_indices[1] as i8).abs();
-    if own_idx_diff != 1 {
-        return; // 本方棋子不相邻，不能吃子
-    }
+    let enemy = match pieces_on_line.as_slice() {
+        [a, b, c] if a.side == side && b.side == side && c.side != side => *c,
+        [a, b, c] if a.side != side && b.side == side && c.side == side => *a,
+        _ => return,
+    };
 
     // 本方棋子中必须有刚移动的那枚
-    let own_piece_ids: Vec<u8> = pieces_with_index.iter()
-        .filter(|(_, s, _)| *s == side)
-        .map(|(_, _, id)| *id)
-        .collect();
-    if !own_piece_ids.contains(&moved_piece_id) {
+    if !pieces_on_line.iter().any(|p| p.id == moved_piece_id && p.side == side) {
         return;
     }
 
     // 所有条件满足，吃掉对方棋子
-    let enemy_id = pieces_with_index.iter()
-        .find(|(_, s, _)| *s != side)
-        .map(|(_, _, id)| *id)
-        .unwrap();
-    
-    if !captured.contains(&enemy_id) {
-        captured.push(enemy_id);
+    if !captured.contains(&enemy.id) {
+        captured.push(enemy.id);
     }
 }
 
@@ -265,27 +285,26 @@ fn check_single_piece_capture(
     let rx = x as i8 - dx;
     let ry = y as i8 - dy;
 
-    if !Board::is_valid_pos(nx, ny) || !Board::is_valid_pos(rx, ry) {
+    if !board.is_valid_pos(nx, ny) || !board.is_valid_pos(rx, ry) {
         return;
     }
 
     // 检查是否两侧都是对方棋子
-    if let Some(p1) = board.piece_at(nx as u8, ny as u8) {
-        if let Some(p2) = board.piece_at(rx as u8, ry as u8) {
-            if p1.side != side && p2.side != side && p1.active && p2.active {
-                // 形成 "对方-单子-对方"，担吃两枚对方棋子
-                if !captured.contains(&p1.id) {
-                    captured.push(p1.id);
-                }
-                if !captured.contains(&p2.id) {
-                    captured.push(p2.id);
-                }
-            }
+    if let Some(p1) = board.piece_at(nx as u8, ny as u8)
+        && let Some(p2) = board.piece_at(rx as u8, ry as u8)
+        && p1.side != side && p2.side != side && p1.active && p2.active
+    {
+        // 形成 "对方-单子-对方"，担吃两枚对方棋子
+        if !captured.contains(&p1.id) {
+            captured.push(p1.id);
+        }
+        if !captured.contains(&p2.id) {
+            captured.push(p2.id);
         }
     }
 }
 
-/// 检查游戏是否结束
+/// 检查游戏是否结束（启用少子判和规则）
 /// 
 /// 参数:
 /// - board: 当前棋盘
@@ -293,88 +312,503 @@ fn check_single_piece_capture(
 /// - player_side: 玩家执哪一方
 /// 
 /// 返回: 如果有结果则返回 GameResult
-pub fn check_game_end(board: &Board, side_to_move: Side, player_side: Side) -> Option<GameResult> {
+pub fn check_game_end(board: &Board, side_to_move: Side, player_side: Side) -> Option<(GameResult, GameEndReason)> {
+    check_game_end_with(board, side_to_move, player_side, true)
+}
+
+/// 检查游戏是否结束，`small_material_draw` 控制是否启用少子判和规则
+///
+/// 终局条件按以下优先级判定，先满足者生效：
+/// 1. 无子判负：某方棋子全部被吃
+/// 2. 困毙判负：轮到行棋的一方无子可动
+/// 3. 少子判和：双方均不超过2枚棋子，且任何一方都无法在一步之内困毙对方
+///
+/// 无吃子判和不在此处判定，见 `Game::check_game_end`
+pub fn check_game_end_with(
+    board: &Board,
+    side_to_move: Side,
+    player_side: Side,
+    small_material_draw: bool,
+) -> Option<(GameResult, GameEndReason)> {
     let black_count = board.count_active(Side::Black);
     let white_count = board.count_active(Side::White);
-
-    // 检查无子判负
-    if black_count == 0 {
-        // 黑方无子，白方胜
-        return Some(if player_side == Side::White {
+    let win_for = |winner: Side| {
+        if winner == player_side {
             GameResult::PlayerWin
         } else {
             GameResult::AiWin
-        });
+        }
+    };
+
+    // 检查无子判负：某方无子，对方获胜
+    if black_count == 0 {
+        return Some((win_for(Side::White), GameEndReason::NoPieces));
     }
     if white_count == 0 {
-        // 白方无子，黑方胜
-        return Some(if player_side == Side::Black {
-            GameResult::PlayerWin
-        } else {
-            GameResult::AiWin
-        });
-    }
-
-    // 检查平局：双方均不超过2枚棋子
-    if black_count <= 2 && white_count <= 2 {
-        return Some(GameResult::Draw);
+        return Some((win_for(Side::Black), GameEndReason::NoPieces));
     }
 
     // 检查困毙
     if is_stalemated(board, side_to_move) {
         // 轮到 side_to_move 行棋，但无法移动，side_to_move判负
-        // side_to_move的对手获胜
-        let winner = side_to_move.opposite();
-        return Some(if winner == player_side {
-            GameResult::PlayerWin
-        } else {
-            GameResult::AiWin
-        });
+        return Some((win_for(side_to_move.opposite()), GameEndReason::Stalemate));
+    }
+
+    // 检查平局：双方均不超过2枚棋子，但仍有困毙机会时继续对局
+    if small_material_draw && black_count <= 2 && white_count <= 2 && !stalemate_possible(board) {
+        return Some((GameResult::Draw, GameEndReason::InsufficientMaterial));
     }
 
     None
 }
 
+/// 局面中是否仍存在困毙：某方已无子可动，或某方一步之内可使对方无子可动
+///
+/// 只在双方均不超过2枚棋子时调用，每个走法各用一份棋盘副本，无需撤销
+fn stalemate_possible(board: &Board) -> bool {
+    [Side::Black, Side::White].into_iter().any(|side| {
+        if is_stalemated(board, side) {
+            return true;
+        }
+        get_valid_moves(board, side).into_iter().any(|(from, to)| {
+            let mut board = board.clone();
+            if board.execute_move(from, to, side).is_err() {
+                return false;
+            }
+            let opponent = side.opposite();
+            board.count_active(opponent) > 0 && is_stalemated(&board, opponent)
+        })
+    })
+}
+
+/// 自定义局面不合法的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetupError {
+    /// 某方没有棋子
+    NoPieces(Side),
+    /// 某方棋子数超过上限
+    TooManyPieces(Side),
+    /// 行棋方无子可动
+    NoLegalMoves,
+    /// 局面已满足终局条件（如双方均不超过2枚棋子）
+    AlreadyOver,
+}
+
+/// 校验自定义局面能否作为对局起点
+///
+/// 双方均须有1至每方初始棋子数枚棋子，行棋方须有合法移动，
+/// 且按 `settings` 中的规则（如是否启用少子判和）局面尚未分出胜负
+pub fn validate_setup(board: &Board, side_to_move: Side, settings: &GameSettings) -> Result<(), SetupError> {
+    for side in [Side::Black, Side::White] {
+        let count = board.count_active(side);
+        if count == 0 {
+            return Err(SetupError::NoPieces(side));
+        }
+        if count > pieces_per_side(board.size) {
+            return Err(SetupError::TooManyPieces(side));
+        }
+    }
+    if is_stalemated(board, side_to_move) {
+        return Err(SetupError::NoLegalMoves);
+    }
+    if check_game_end_with(board, side_to_move, side_to_move, settings.small_material_draw).is_some() {
+        return Err(SetupError::AlreadyOver);
+    }
+    Ok(())
+}
+
 /// 检查某方是否被困毙（无合法移动）
 pub fn is_stalemated(board: &Board, side: Side) -> bool {
-    let pieces: Vec<_> = board.active_pieces_of(side).iter().map(|&p| p.clone()).collect();
+    !board.has_valid_moves(side)
+}
 
-    for piece in pieces {
-        let (x, y) = piece.position;
+/// 获取某方所有合法移动
+pub fn get_valid_moves(board: &Board, side: Side) -> Vec<Move> {
+    board.valid_moves(side)
+}
 
-        // 检查四个方向
-        let directions = [(0, 1), (0, -1), (1, 0), (-1, 0)];
-        for (dx, dy) in directions {
-            let nx = x as i8 + dx;
-            let ny = y as i8 + dy;
+/// 获取某方所有能吃子的走法及各自吃掉的棋子ID
+///
+/// 在同一份棋盘副本上临时移动棋子计算吃子，再移回原位，避免逐个走法克隆
+pub fn get_capturing_moves(board: &Board, side: Side) -> Vec<(Move, Vec<u8>)> {
+    let mut board = board.clone();
+    get_valid_moves(&board, side)
+        .into_iter()
+        .filter_map(|(from, to)| {
+            let captured = captures_after_move(&mut board, from, to);
+            (!captured.is_empty()).then_some(((from, to), captured))
+        })
+        .collect()
+}
 
-            if Board::is_valid_pos(nx, ny) && board.is_empty(nx as u8, ny as u8) {
-                return false; // 至少有一个合法移动
+/// 将 from 处的棋子临时移到 to，返回会被吃掉的棋子ID，棋盘随后恢复原状
+///
+/// 不检查走法是否合法；from 处无子时返回空列表
+pub(crate) fn captures_after_move(board: &mut Board, from: (u8, u8), to: (u8, u8)) -> Vec<u8> {
+    let Some(piece) = board.piece_at_mut(from.0, from.1) else {
+        return Vec::new();
+    };
+    piece.position = to;
+    let piece_id = piece.id;
+    let captured = calculate_captures(board, piece_id);
+    if let Some(piece) = board.piece_by_id_mut(piece_id) {
+        piece.position = from;
+    }
+    captured
+}
+
+/// 走法生成计数（perft）：统计从当前局面出发、双方交替走 `depth` 步后到达的叶子节点数
+///
+/// 用于核对走法生成与吃子逻辑；无子可走的局面不产生叶子节点。
+/// 执行或撤销走法失败说明记录与棋盘不一致，此时返回错误
+pub fn perft(board: &Board, side: Side, depth: u32) -> anyhow::Result<u64> {
+    let mut board = board.clone();
+    perft_recursive(&mut board, side, depth)
+}
+
+/// perft 递归实现：在同一棋盘上执行并撤销走法，避免反复克隆
+fn perft_recursive(board: &mut Board, side: Side, depth: u32) -> anyhow::Result<u64> {
+    if depth == 0 {
+        return Ok(1);
+    }
+
+    let mut nodes = 0;
+    for (from, to) in get_valid_moves(board, side) {
+        let record = board.execute_move(from, to, side)?;
+        nodes += perft_recursive(board, side.opposite(), depth - 1)?;
+        board.undo_move(&record)?;
+    }
+    Ok(nodes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 按记谱建盘，预演 from→to 并返回排序后的被吃棋子ID
+    fn captures_of(notation: &str, from: (u8, u8), to: (u8, u8)) -> Vec<u8> {
+        let board = Board::from_notation(notation).unwrap();
+        let mut captured = board.captures_if_moved(from, to);
+        captured.sort_unstable();
+        captured
+    }
+
+    /// 按记谱建盘，预演 from→to 并返回排序后的被吃棋子位置
+    fn captured_positions(notation: &str, from: (u8, u8), to: (u8, u8)) -> Vec<(u8, u8)> {
+        let board = Board::from_notation(notation).unwrap();
+        let mut positions: Vec<_> = board
+            .captures_if_moved(from, to)
+            .into_iter()
+            .map(|id| board.piece_by_id(id).unwrap().position)
+            .collect();
+        positions.sort_unstable();
+        positions
+    }
+
+    #[test]
+    fn two_vs_one_captures_at_either_end_of_a_row() {
+        // [对方][本方][本方][空]
+        assert_eq!(captured_positions("W..W/..../..B./WB..", (2, 1), (2, 0)), vec![(0, 0)]);
+        // [空][本方][本方][对方]
+        assert_eq!(captured_positions("W..W/..../.B../..BW", (1, 1), (1, 0)), vec![(3, 0)]);
+    }
+
+    #[test]
+    fn two_vs_one_captures_at_either_end_of_a_column() {
+        assert_eq!(captured_positions("W..W/B.../.B../...W", (1, 1), (0, 1)), vec![(0, 3)]);
+        assert_eq!(captured_positions("...W/.B../B.../W..W", (1, 2), (0, 2)), vec![(0, 0)]);
+    }
+
+    #[test]
+    fn two_vs_one_needs_exactly_three_pieces_on_the_line() {
+        // 整行占满：[对方][本方][本方][本方]、[对方][本方][本方][对方]
+        assert!(captured_positions("W..W/..../..B./WB.B", (2, 1), (2, 0)).is_empty());
+        assert!(captured_positions("...W/..../..B./WB.W", (2, 1), (2, 0)).is_empty());
+        // 5路棋盘上相连的三子之外还有一子
+        assert!(captured_positions("W...W/...../...../..B../WB..B", (2, 1), (2, 0)).is_empty());
+        // 相连的三子之外只有空点时照常吃子
+        assert_eq!(captured_positions("W...W/...../...../..B../WB...", (2, 1), (2, 0)), vec![(0, 0)]);
+    }
+
+    /// 按棋子列表生成记谱（首行为最上一行）
+    fn notation_of(size: u8, pieces: &[(Side, (u8, u8))]) -> String {
+        (0..size)
+            .rev()
+            .map(|y| {
+                (0..size)
+                    .map(|x| match pieces.iter().find(|&&(_, pos)| pos == (x, y)) {
+                        Some((Side::Black, _)) => 'B',
+                        Some((Side::White, _)) => 'W',
+                        None => '.',
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    #[test]
+    fn two_vs_one_every_arrangement_on_a_line() {
+        // 线上连续排列（o 本方、x 对方，其余为空）及是否吃掉其中的对方棋子
+        let patterns = [
+            ("xoo", true),
+            ("oox", true),
+            // 隔开的两子不构成二比一
+            ("oxo", false),
+            // 线上四子
+            ("xooo", false),
+            ("ooox", false),
+            ("xoox", false),
+            ("oxoo", false),
+            ("ooxo", false),
+        ];
+        for size in 4..=6u8 {
+            for mover in [Side::Black, Side::White] {
+                for along_row in [true, false] {
+                    // 第1行（或第1列）上的第 i 个点；行棋方从第0行（或第0列）走入
+                    let on_line = |i: u8| if along_row { (i, 1) } else { (1, i) };
+                    let entry = |i: u8| if along_row { (i, 0) } else { (0, i) };
+                    for (pattern, captures) in patterns {
+                        let len = pattern.len() as u8;
+                        for offset in 0..=size - len {
+                            let cells = || pattern.chars().zip(offset..);
+                            let own: Vec<u8> = cells().filter(|&(c, _)| c == 'o').map(|(_, i)| i).collect();
+                            let enemies: Vec<(u8, u8)> =
+                                cells().filter(|&(c, _)| c == 'x').map(|(_, i)| on_line(i)).collect();
+                            // 本方每一子都可以是走入的一子
+                            for &arrive in &own {
+                                let mut pieces: Vec<(Side, (u8, u8))> = own
+                                    .iter()
+                                    .map(|&i| (mover, if i == arrive { entry(i) } else { on_line(i) }))
+                                    .chain(enemies.iter().map(|&pos| (mover.opposite(), pos)))
+                                    .collect();
+                                // 对方在远处另有两子，避免进入单子状态
+                                pieces.push((mover.opposite(), (size - 2, size - 1)));
+                                pieces.push((mover.opposite(), (size - 1, size - 1)));
+                                let notation = notation_of(size, &pieces);
+                                assert_eq!(notation.matches(['B', 'W']).count(), pieces.len(), "{notation}");
+                                let expected = if captures { enemies.clone() } else { Vec::new() };
+                                assert_eq!(
+                                    captured_positions(&notation, entry(arrive), on_line(arrive)),
+                                    expected,
+                                    "{pattern} {notation} {mover:?}"
+                                );
+                            }
+                        }
+                    }
+                }
             }
         }
     }
 
-    true // 无合法移动，困毙
-}
+    #[test]
+    fn illegal_move_without_own_piece() {
+        let board = Board::initial();
+        // 起点为空、为对方棋子
+        assert_eq!(is_valid_move_detailed(&board, (1, 1), (1, 2), Side::Black), Err(IllegalMoveReason::NoOwnPiece));
+        assert_eq!(is_valid_move_detailed(&board, (0, 2), (1, 2), Side::Black), Err(IllegalMoveReason::NoOwnPiece));
+        assert_eq!(is_valid_move_detailed(&board, (0, 1), (1, 1), Side::Black), Ok(()));
+    }
 
-/// 获取某方所有合法移动
-pub fn get_valid_moves(board: &Board, side: Side) -> Vec<((u8, u8), (u8, u8))> {
-    let mut moves = Vec::new();
-    let pieces: Vec<_> = board.active_pieces_of(side).iter().map(|&p| p.clone()).collect();
+    #[test]
+    fn illegal_move_out_of_board() {
+        let board = Board::initial();
+        assert_eq!(is_valid_move_detailed(&board, (3, 0), (4, 0), Side::Black), Err(IllegalMoveReason::OutOfBoard));
+        assert_eq!(is_valid_move_detailed(&board, (0, 3), (0, 4), Side::White), Err(IllegalMoveReason::OutOfBoard));
+        // 坐标下溢
+        assert_eq!(is_valid_move_detailed(&board, (0, 0), (255, 0), Side::Black), Err(IllegalMoveReason::OutOfBoard));
+    }
+
+    #[test]
+    fn illegal_move_onto_occupied_point() {
+        let board = Board::initial();
+        assert_eq!(is_valid_move_detailed(&board, (0, 0), (1, 0), Side::Black), Err(IllegalMoveReason::Occupied));
+        assert_eq!(is_valid_move_detailed(&board, (0, 1), (0, 2), Side::Black), Err(IllegalMoveReason::Occupied));
+    }
+
+    #[test]
+    fn illegal_move_not_adjacent() {
+        let board = Board::initial();
+        // 斜走、走两格
+        assert_eq!(is_valid_move_detailed(&board, (0, 1), (1, 2), Side::Black), Err(IllegalMoveReason::NotAdjacent));
+        assert_eq!(is_valid_move_detailed(&board, (0, 1), (2, 1), Side::Black), Err(IllegalMoveReason::NotAdjacent));
+        assert!(!is_valid_move(&board, (0, 1), (2, 1), Side::Black));
+    }
+
+    #[test]
+    fn get_capturing_moves_lists_the_only_capture() {
+        // 黑子 1 在 (0,0)、2 在 (1,1)，白子 3 在 (2,0)、4 在 (3,3)：只有 2 走到 (1,0) 能吃子
+        let board = Board::from_notation("...W/..../.B../B.W.").unwrap();
+        let before = board.clone();
+        assert_eq!(get_capturing_moves(&board, Side::Black), vec![(((1, 1), (1, 0)), vec![3])]);
+        assert_eq!(board, before);
+
+        // 开局双方都没有吃子的走法
+        let initial = Board::initial();
+        assert!(get_capturing_moves(&initial, Side::Black).is_empty());
+        assert!(get_capturing_moves(&initial, Side::White).is_empty());
+    }
+
+    #[test]
+    fn capture_along_two_lines_and_undo() {
+        // 黑方 (1,0)→(1,1) 同时在第2行与B列形成二比一
+        let notation = ".W.W/.B../W.B./.B..";
+        assert_eq!(captured_positions(notation, (1, 0), (1, 1)), vec![(0, 1), (1, 3)]);
+
+        let mut board = Board::from_notation(notation).unwrap();
+        let before = board.pieces.clone();
+        let record = board.execute_move((1, 0), (1, 1), Side::Black).unwrap();
+        let mut captured: Vec<_> = record.captured.iter().map(|c| (c.piece_id, c.position)).collect();
+        captured.sort_unstable();
+        assert_eq!(captured, vec![(4, (0, 1)), (5, (1, 3))]);
+        assert!(captured.iter().all(|&(id, _)| !board.piece_by_id(id).unwrap().active));
+        assert_eq!(board.piece_at(1, 1).map(|p| p.id), Some(1));
+
+        board.undo_move(&record).unwrap();
+        assert_eq!(board.pieces, before);
+    }
+
+    #[test]
+    fn single_piece_flanks_two_enemies() {
+        // 黑方只剩一子，从 (1,0) 走入两枚白子之间
+        let captured = captures_of("..../..../W.W./.B..", (1, 0), (1, 1));
+        assert_eq!(captured, vec![2, 3]);
+    }
 
-    for piece in pieces {
-        let (x, y) = piece.position;
-        let directions = [(0, 1), (0, -1), (1, 0), (-1, 0)];
+    #[test]
+    fn single_piece_needs_enemies_on_both_sides() {
+        assert!(captures_of("..../..../W..W/.B..", (1, 0), (1, 1)).is_empty());
+    }
+
+    #[test]
+    fn majority_side_does_not_capture_in_single_piece_mode() {
+        // 白方两子与黑方单子形成「本方本方对方」，单子状态下二比一失效
+        assert!(captures_of("..../..../.W../W.B.", (1, 1), (1, 0)).is_empty());
+        // 黑方多一子时不是单子状态，同样的排列按二比一吃子
+        assert_eq!(captures_of("...B/..../.W../W.B.", (1, 1), (1, 0)), vec![1]);
+    }
+
+    /// 按记谱判定终局（玩家执黑）
+    fn game_end(notation: &str, side_to_move: Side, small_material_draw: bool) -> Option<(GameResult, GameEndReason)> {
+        let board = Board::from_notation(notation).unwrap();
+        check_game_end_with(&board, side_to_move, Side::Black, small_material_draw)
+    }
+
+    #[test]
+    fn small_material_draw_applies_to_two_or_fewer_pieces_each() {
+        let draw = Some((GameResult::Draw, GameEndReason::InsufficientMaterial));
+        // 2对2、1对1：一步之内都无法困毙对方
+        assert_eq!(game_end("W..W/..../..../B..B", Side::Black, true), draw);
+        assert_eq!(game_end("W.../..../..../...B", Side::White, true), draw);
+        // 3对2 不适用少子判和
+        assert_eq!(game_end("W..W/..../.B../B..B", Side::Black, true), None);
+        // 关闭该规则时继续对局
+        assert_eq!(game_end("W..W/..../..../B..B", Side::Black, false), None);
+    }
+
+    #[test]
+    fn small_material_continues_while_stalemate_is_possible() {
+        // 黑方 (2,3)→(1,3) 即可困毙角上的白方单子
+        assert_eq!(game_end("W.B./B.../..../....", Side::Black, true), None);
+    }
+
+    #[test]
+    fn end_checks_run_in_order() {
+        // 无子判负先于困毙：白方无子时轮到白方也判无子
+        assert_eq!(
+            game_end("..../..../..../BB..", Side::White, true),
+            Some((GameResult::PlayerWin, GameEndReason::NoPieces))
+        );
+        // 困毙先于少子判和：2对1 且轮到被困的白方
+        assert_eq!(
+            game_end("WB../B.../..../....", Side::White, true),
+            Some((GameResult::PlayerWin, GameEndReason::Stalemate))
+        );
+        // 黑方被困时判白方胜
+        assert_eq!(
+            game_end("BW../W.../..../...W", Side::Black, true),
+            Some((GameResult::AiWin, GameEndReason::Stalemate))
+        );
+    }
 
-        for (dx, dy) in directions {
-            let nx = x as i8 + dx;
-            let ny = y as i8 + dy;
+    #[test]
+    fn setup_validation_follows_small_material_setting() {
+        let board = Board::from_notation("W..W/..../..../B..B").unwrap();
+        let mut settings = GameSettings { small_material_draw: true, ..GameSettings::default() };
+        assert_eq!(validate_setup(&board, Side::Black, &settings), Err(SetupError::AlreadyOver));
+        // 关闭少子判和后2对2是可以开始的局面，与对局中的判定一致
+        settings.small_material_draw = false;
+        assert_eq!(validate_setup(&board, Side::Black, &settings), Ok(()));
+        assert_eq!(StandardRules.validate_setup(&board, Side::Black, &settings), Ok(()));
+    }
 
-            if Board::is_valid_pos(nx, ny) && board.is_empty(nx as u8, ny as u8) {
-                moves.push(((x, y), (nx as u8, ny as u8)));
+    #[test]
+    fn standard_rules_dispatch_matches_free_functions() {
+        let rules = StandardRules;
+        let settings = GameSettings::default();
+        for notation in [
+            "WWWW/W..W/B..B/BBBB",
+            "W.WW/.W../.BB./B..B",
+            "..../..../W.W./.B..",
+            "W..W/..../..../B..B",
+            "WB../B.../..../....",
+        ] {
+            let board = Board::from_notation(notation).unwrap();
+            for side in [Side::Black, Side::White] {
+                assert_eq!(rules.valid_moves(&board, side), get_valid_moves(&board, side));
+                assert_eq!(
+                    rules.is_game_over(&board, side, Side::Black, &settings),
+                    check_game_end_with(&board, side, Side::Black, settings.small_material_draw)
+                );
+                assert_eq!(rules.validate_setup(&board, side, &settings), validate_setup(&board, side, &settings));
+                for piece in board.active_pieces_of(side) {
+                    for (dx, dy) in crate::game::board::DIRECTIONS {
+                        let (x, y) = (piece.position.0 as i8 + dx, piece.position.1 as i8 + dy);
+                        if !board.is_valid_pos(x, y) {
+                            continue;
+                        }
+                        let to = (x as u8, y as u8);
+                        assert_eq!(
+                            rules.is_valid_move(&board, piece.position, to, side),
+                            is_valid_move(&board, piece.position, to, side)
+                        );
+                        let mut moved = board.clone();
+                        moved.piece_by_id_mut(piece.id).unwrap().position = to;
+                        assert_eq!(rules.captures(&moved, piece.id), calculate_captures(&moved, piece.id));
+                    }
+                }
             }
         }
     }
 
-    moves
+    #[test]
+    fn perft_counts_from_initial_position() {
+        let board = Board::initial();
+        let counts: Vec<u64> = (0..=3).map(|depth| perft(&board, Side::Black, depth).unwrap()).collect();
+        assert_eq!(counts, vec![1, 4, 18, 108]);
+        assert_eq!(board.to_notation(), Board::initial().to_notation());
+    }
+
+    /// 逐个走法克隆棋盘的朴素计数，用于核对 perft 的执行与撤销
+    fn naive_perft(board: &Board, side: Side, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        get_valid_moves(board, side)
+            .into_iter()
+            .map(|(from, to)| {
+                let mut board = board.clone();
+                board.execute_move(from, to, side).unwrap();
+                naive_perft(&board, side.opposite(), depth - 1)
+            })
+            .sum()
+    }
+
+    #[test]
+    fn perft_matches_naive_count_with_captures() {
+        let board = Board::from_notation("W.WW/.W../.BB./B..B").unwrap();
+        for depth in 1..=3 {
+            assert_eq!(perft(&board, Side::Black, depth).unwrap(), naive_perft(&board, Side::Black, depth));
+        }
+    }
 }
@@ -1,81 +1,366 @@
 //! 游戏存档功能
 
-use crate::game::board::{Board, BOARD_SIZE};
-use crate::game::piece::{Piece, PieceState, Side};
+use crate::game::board::{Board, BOARD_SIZE, MAX_BOARD_SIZE, MIN_BOARD_SIZE};
+use crate::game::piece::{
+    initial_black_positions, initial_white_positions, pieces_per_side, Piece, PieceState, Side,
+};
+use crate::game::rules::check_game_end_with;
+use crate::game::settings::{config_dir, GameSettings};
+use crate::game::state::GameResult;
+use crate::game::MoveRecord;
 use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// 存档文件版本
-const SAVE_VERSION: u8 = 1;
+///
+/// - v1: 仅保存棋子位置
+/// - v2: 额外保存完整棋子信息（含ID）与行棋历史，支持回放
+const SAVE_VERSION: u8 = 2;
+
+/// 二进制存档的扩展名（默认的 `.6zc` 为JSON格式）
+pub const BINARY_SAVE_EXTENSION: &str = "6zb";
+
+/// 二进制存档文件头，用于在加载时识别格式
+const BINARY_MAGIC: &[u8; 4] = b"6ZB\0";
+
+/// 自动存档文件名（位于配置目录下）
+const AUTOSAVE_FILE: &str = "autosave.6zc";
+
+/// 存档槽数量
+pub const SAVE_SLOT_COUNT: usize = 5;
+
+/// 存档槽目录名与索引文件名
+const SLOTS_DIR: &str = "saves";
+const SLOT_INDEX_FILE: &str = "slots.json";
 
 /// 存档数据结构
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SaveData {
     /// 版本号
     version: u8,
-    /// 棋子位置数据 [16个位置，每个位置存储棋子信息]
-    /// 索引 = y * 4 + x
+    /// 棋盘大小（缺省为4）
+    #[serde(default = "default_board_size")]
+    size: u8,
+    /// 棋子位置数据 [size*size个位置，每个位置存储棋子信息]
+    /// 索引 = y * size + x
     /// 值：0=空, 1=黑棋, 2=白棋
-    board: [u8; 16],
+    board: Vec<u8>,
     /// 当前轮到哪方行棋（加载后默认为玩家回合）
     current_turn: Side,
     /// 玩家执子方
     player_side: Side,
+    /// 完整棋子信息（含已被吃的棋子，v2起）
+    #[serde(default)]
+    pieces: Vec<Piece>,
+    /// 行棋历史（v2起）
+    #[serde(default)]
+    move_history: Vec<MoveRecord>,
+    /// 存档元数据（旧存档缺省为 None）
+    #[serde(default)]
+    metadata: Option<SaveMetadata>,
 }
 
-/// 保存游戏到文件
-pub fn save_game(board: &Board, player_side: Side, path: &Path) -> Result<()> {
-    let mut board_data = [0u8; 16];
-    
-    for piece in &board.pieces {
-        if piece.active {
-            let (x, y) = piece.position;
-            let idx = (y * BOARD_SIZE + x) as usize;
-            board_data[idx] = match piece.side {
-                Side::Black => 1,
-                Side::White => 2,
-            };
+/// 存档元数据：为存档提供可读的上下文信息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SaveMetadata {
+    /// 玩家名称或存档标签
+    #[serde(default)]
+    pub player_name: String,
+    /// 保存时间（RFC3339）
+    pub saved_at: String,
+    /// 电脑难度等级
+    pub ai_level: u8,
+    /// 对局结果（尚未结束时为 None）
+    #[serde(default)]
+    pub result: Option<GameResult>,
+}
+
+impl SaveMetadata {
+    /// 以当前时间创建元数据
+    pub fn new(player_name: &str, ai_level: u8, result: Option<GameResult>) -> Self {
+        Self {
+            player_name: player_name.to_string(),
+            saved_at: Local::now().to_rfc3339(),
+            ai_level,
+            result,
         }
     }
+
+    /// 解析保存时间，格式错误时返回 None
+    pub fn saved_at(&self) -> Option<DateTime<Local>> {
+        DateTime::parse_from_rfc3339(&self.saved_at)
+            .ok()
+            .map(|time| time.with_timezone(&Local))
+    }
+}
+
+fn default_board_size() -> u8 {
+    BOARD_SIZE
+}
+
+/// 存档文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveFormat {
+    /// 带缩进的JSON，便于阅读与分享（`.6zc`）
+    Json,
+    /// 紧凑的二进制格式（`.6zb`）
+    Binary,
+}
+
+impl SaveFormat {
+    /// 按文件扩展名选择格式：`.6zb` 为二进制，其余为JSON
+    pub fn from_path(path: &Path) -> Self {
+        let is_binary = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case(BINARY_SAVE_EXTENSION));
+        if is_binary { SaveFormat::Binary } else { SaveFormat::Json }
+    }
+}
+
+/// 加载的存档内容
+#[derive(Debug)]
+pub struct LoadedGame {
+    /// 棋盘
+    pub board: Board,
+    /// 玩家执子方
+    pub player_side: Side,
+    /// 当前轮到哪方行棋
+    pub current_turn: Side,
+    /// 行棋历史（v1存档为空）
+    pub move_history: Vec<MoveRecord>,
+    /// 存档元数据（旧存档为 None）
+    pub metadata: Option<SaveMetadata>,
+}
+
+/// 保存游戏到文件，格式由扩展名决定（见 [`SaveFormat::from_path`]）
+pub fn save_game(
+    board: &Board,
+    player_side: Side,
+    current_turn: Side,
+    move_history: &[MoveRecord],
+    metadata: Option<&SaveMetadata>,
+    path: &Path,
+) -> Result<()> {
+    let save_data = SaveData::new(board, player_side, current_turn, move_history, metadata);
     
-    let save_data = SaveData {
-        version: SAVE_VERSION,
-        board: board_data,
-        current_turn: Side::Black, // 加载后黑方先行
-        player_side,
+    let bytes = match SaveFormat::from_path(path) {
+        SaveFormat::Json => serde_json::to_vec_pretty(&save_data)
+            .context("序列化存档数据失败")?,
+        SaveFormat::Binary => {
+            let mut bytes = BINARY_MAGIC.to_vec();
+            bincode::serialize_into(&mut bytes, &save_data)
+                .context("序列化存档数据失败")?;
+            bytes
+        }
     };
-    
-    let json = serde_json::to_string_pretty(&save_data)
-        .context("序列化存档数据失败")?;
-    fs::write(path, json).context("写入存档文件失败")?;
-    
+    fs::write(path, bytes).context("写入存档文件失败")?;
+    crate::log_info!("已保存游戏 {}（{}步）", path.display(), move_history.len());
+
     Ok(())
 }
 
 /// 从文件加载游戏
-pub fn load_game(path: &Path) -> Result<(Board, Side)> {
-    let json = fs::read_to_string(path).context("读取存档文件失败")?;
-    let save_data: SaveData = serde_json::from_str(&json)
-        .context("解析存档数据失败")?;
-    
-    if save_data.version != SAVE_VERSION {
-        anyhow::bail!("不支持的存档版本: {}", save_data.version);
+///
+/// 以文件头识别二进制存档，否则按JSON解析（与扩展名无关）
+pub fn load_game(path: &Path) -> Result<LoadedGame> {
+    let bytes = fs::read(path).context("读取存档文件失败")?;
+    let save_data: SaveData = match bytes.strip_prefix(BINARY_MAGIC) {
+        Some(data) => bincode::deserialize(data).context("解析存档数据失败")?,
+        None => serde_json::from_slice(&bytes).context("解析存档数据失败")?,
+    };
+    let loaded = save_data.into_loaded()?;
+    crate::log_info!("已加载游戏 {}（{}步）", path.display(), loaded.move_history.len());
+    Ok(loaded)
+}
+
+/// 将完整棋局（含行棋历史）保存为JSON字符串，便于复制到剪贴板
+///
+/// 内容与 `.6zc` 存档文件相同，可由 [`load_from_string`] 加载
+pub fn save_to_string(
+    board: &Board,
+    player_side: Side,
+    current_turn: Side,
+    move_history: &[MoveRecord],
+    metadata: Option<&SaveMetadata>,
+) -> Result<String> {
+    let save_data = SaveData::new(board, player_side, current_turn, move_history, metadata);
+    serde_json::to_string_pretty(&save_data).context("序列化存档数据失败")
+}
+
+/// 局面的简短文本：局面记谱加行棋方（`B` 或 `W`），如 `WWWW/W..W/B..B/BBBB B`
+pub fn position_to_string(board: &Board, current_turn: Side) -> String {
+    let side = match current_turn {
+        Side::Black => 'B',
+        Side::White => 'W',
+    };
+    format!("{} {}", board.to_notation(), side)
+}
+
+/// 从字符串加载游戏
+///
+/// 以 `{` 开头时按完整棋局的JSON解析，否则按 [`position_to_string`] 的简短格式解析
+/// （缺省行棋方为黑方）；简短格式没有行棋历史，由玩家执行棋方
+pub fn load_from_string(text: &str) -> Result<LoadedGame> {
+    let text = text.trim();
+    if text.starts_with('{') {
+        let save_data: SaveData = serde_json::from_str(text).context("解析存档数据失败")?;
+        return save_data.into_loaded();
     }
-    
+
+    let mut parts = text.split_whitespace();
+    let notation = parts.next().context("局面文本为空")?;
+    let current_turn = match parts.next() {
+        None | Some("B" | "b") => Side::Black,
+        Some("W" | "w") => Side::White,
+        Some(other) => anyhow::bail!("行棋方标记错误: \"{}\"", other),
+    };
+    if let Some(extra) = parts.next() {
+        anyhow::bail!("局面文本包含多余内容: \"{}\"", extra);
+    }
+
+    Ok(LoadedGame {
+        board: Board::from_notation(notation)?,
+        player_side: current_turn,
+        current_turn,
+        move_history: Vec::new(),
+        metadata: None,
+    })
+}
+
+impl SaveData {
+    /// 由当前棋局生成存档数据
+    fn new(
+        board: &Board,
+        player_side: Side,
+        current_turn: Side,
+        move_history: &[MoveRecord],
+        metadata: Option<&SaveMetadata>,
+    ) -> Self {
+        let size = board.size as usize;
+        let mut board_data = vec![0u8; size * size];
+
+        for piece in board.pieces.iter().filter(|p| p.active) {
+            let (x, y) = (piece.position.0 as usize, piece.position.1 as usize);
+            if x < size && y < size {
+                board_data[y * size + x] = match piece.side {
+                    Side::Black => 1,
+                    Side::White => 2,
+                };
+            }
+        }
+
+        Self {
+            version: SAVE_VERSION,
+            size: board.size,
+            board: board_data,
+            current_turn,
+            player_side,
+            pieces: board.pieces.clone(),
+            move_history: move_history.to_vec(),
+            metadata: metadata.cloned(),
+        }
+    }
+
+    /// 按版本号还原棋局
+    fn into_loaded(self) -> Result<LoadedGame> {
+        match self.version {
+            1 => Ok(LoadedGame {
+                board: board_from_cells(&self.board, BOARD_SIZE)?,
+                player_side: self.player_side,
+                current_turn: Side::Black, // v1存档加载后黑方先行
+                move_history: Vec::new(),
+                metadata: self.metadata,
+            }),
+            SAVE_VERSION => {
+                // 存档保存完整棋子信息（含ID），行棋历史引用的ID与加载后的棋盘一致
+                let board = Board::with_pieces(self.size, self.pieces);
+                check_board(&board)?;
+                check_history(&board, &self.move_history)?;
+                Ok(LoadedGame {
+                    board,
+                    player_side: self.player_side,
+                    current_turn: self.current_turn,
+                    move_history: self.move_history,
+                    metadata: self.metadata,
+                })
+            }
+            version => anyhow::bail!("不支持的存档版本: {}", version),
+        }
+    }
+}
+
+/// 校验存档中的棋盘：大小在支持范围内，棋子ID不重复，在盘棋子都在棋盘内且互不重叠
+fn check_board(board: &Board) -> Result<()> {
+    if !(MIN_BOARD_SIZE..=MAX_BOARD_SIZE).contains(&board.size) {
+        anyhow::bail!("棋盘大小错误: {}", board.size);
+    }
+    let mut ids = std::collections::HashSet::new();
+    let mut occupied = std::collections::HashSet::new();
+    for piece in &board.pieces {
+        if !ids.insert(piece.id) {
+            anyhow::bail!("棋子ID重复: {}", piece.id);
+        }
+        if !piece.active {
+            continue;
+        }
+        let (x, y) = piece.position;
+        if x >= board.size || y >= board.size {
+            anyhow::bail!("棋子 {} 位于棋盘外: {:?}", piece.id, piece.position);
+        }
+        if !occupied.insert(piece.position) {
+            anyhow::bail!("多枚棋子重叠于 {:?}", piece.position);
+        }
+    }
+    Ok(())
+}
+
+/// 校验行棋历史与棋盘一致：从当前局面逐步撤销，每一步的棋子ID、所属方与位置都须吻合
+///
+/// 保证加载后悔棋与回放移动的是正确的棋子
+fn check_history(board: &Board, move_history: &[MoveRecord]) -> Result<()> {
+    let mut board = board.clone();
+    for (index, record) in move_history.iter().enumerate().rev() {
+        let moved_matches = board
+            .piece_by_id(record.piece_id)
+            .is_some_and(|p| p.active && p.side == record.side && p.position == record.to);
+        let captured_matches = record.captured.iter().all(|captured| {
+            board
+                .piece_by_id(captured.piece_id)
+                .is_some_and(|p| !p.active && p.side == record.side.opposite())
+        });
+        if !moved_matches || !captured_matches {
+            anyhow::bail!("行棋历史与棋盘不一致（第{}步）", index + 1);
+        }
+        board.undo_move(record)?;
+    }
+    Ok(())
+}
+
+/// 由逐格数据重建棋盘
+fn board_from_cells(cells: &[u8], size: u8) -> Result<Board> {
+    if cells.len() != size as usize * size as usize {
+        anyhow::bail!("棋盘数据长度错误: {}", cells.len());
+    }
+
     // 重建棋盘
-    let mut board = Board::empty();
+    let mut board = Board::empty_sized(size);
     let mut piece_id = 1u8;
     
     // 先清空默认棋子
     board.pieces.clear();
     
-    for (idx, &cell) in save_data.board.iter().enumerate() {
+    for (idx, &cell) in cells.iter().enumerate() {
         if cell != 0 {
-            let x = (idx % BOARD_SIZE as usize) as u8;
-            let y = (idx / BOARD_SIZE as usize) as u8;
-            let side = if cell == 1 { Side::Black } else { Side::White };
+            let x = (idx % size as usize) as u8;
+            let y = (idx / size as usize) as u8;
+            let side = match cell {
+                1 => Side::Black,
+                2 => Side::White,
+                _ => anyhow::bail!("棋盘数据包含非法格子值: {}", cell),
+            };
             
             board.pieces.push(Piece {
                 id: piece_id,
@@ -88,21 +373,25 @@ pub fn load_game(path: &Path) -> Result<(Board, Side)> {
         }
     }
     
-    Ok((board, save_data.player_side))
+    Ok(board)
 }
 
 /// 检查是否是初始局面
 pub fn is_initial_position(board: &Board) -> bool {
-    // 初始局面：黑方在下方(y=0,1)，白方在上方(y=2,3)
-    // 黑方: (0,0), (1,0), (2,0), (3,0), (0,1), (3,1)
-    // 白方: (0,3), (1,3), (2,3), (3,3), (0,2), (3,2)
+    // 初始局面：黑方在下方两行，白方在上方两行
+    // 4x4 时黑方: (0,0), (1,0), (2,0), (3,0), (0,1), (3,1)
+    //       白方: (0,3), (1,3), (2,3), (3,3), (0,2), (3,2)
+    if board.size < MIN_BOARD_SIZE {
+        return false;
+    }
     
-    let expected_black = [(0u8, 0u8), (1, 0), (2, 0), (3, 0), (0, 1), (3, 1)];
-    let expected_white = [(0u8, 3u8), (1, 3), (2, 3), (3, 3), (0, 2), (3, 2)];
+    let expected_black = initial_black_positions(board.size);
+    let expected_white = initial_white_positions(board.size);
+    let per_side = pieces_per_side(board.size);
     
     let active_pieces: Vec<_> = board.pieces.iter().filter(|p| p.active).collect();
     
-    if active_pieces.len() != 12 {
+    if active_pieces.len() != per_side * 2 {
         return false;
     }
     
@@ -117,8 +406,476 @@ pub fn is_initial_position(board: &Board) -> bool {
         .map(|p| p.position)
         .collect();
     
-    black_positions.len() == 6 
-        && white_positions.len() == 6
+    black_positions.len() == per_side
+        && white_positions.len() == per_side
         && expected_black.iter().all(|pos| black_positions.contains(pos))
         && expected_white.iter().all(|pos| white_positions.contains(pos))
 }
+
+/// 自动存档路径（配置目录下的 autosave.6zc）
+pub fn autosave_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(AUTOSAVE_FILE))
+}
+
+/// 写入自动存档（格式与普通存档相同）
+pub fn write_autosave(
+    board: &Board,
+    player_side: Side,
+    current_turn: Side,
+    move_history: &[MoveRecord],
+    metadata: &SaveMetadata,
+) -> Result<()> {
+    let path = autosave_path().context("无法确定配置目录")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).context("创建配置目录失败")?;
+    }
+    save_game(board, player_side, current_turn, move_history, Some(metadata), &path)
+}
+
+/// 删除自动存档（不存在时忽略）
+pub fn remove_autosave() {
+    if let Some(path) = autosave_path()
+        && path.exists()
+        && let Err(e) = fs::remove_file(&path)
+    {
+        crate::log_error!("删除自动存档失败: {}", e);
+    }
+}
+
+/// 查找可恢复的自动存档：文件存在、可以解析、不是初始局面且按当前设置尚未分出胜负
+pub fn find_resumable_autosave(settings: &GameSettings) -> Option<PathBuf> {
+    let path = autosave_path()?;
+    let loaded = load_game(&path).ok()?;
+    is_resumable(&loaded, settings).then_some(path)
+}
+
+/// 存档局面是否值得恢复：不是初始局面，且按给定设置（如是否启用少子判和）尚未分出胜负
+fn is_resumable(loaded: &LoadedGame, settings: &GameSettings) -> bool {
+    !is_initial_position(&loaded.board)
+        && check_game_end_with(&loaded.board, loaded.current_turn, loaded.player_side, settings.small_material_draw)
+            .is_none()
+}
+
+/// 存档槽目录（配置目录下的 saves/）
+pub fn slots_dir() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(SLOTS_DIR))
+}
+
+/// 指定存档槽（从0开始）的存档文件路径
+pub fn slot_path(slot: usize) -> Option<PathBuf> {
+    slots_dir().map(|dir| dir.join(format!("slot{}.6zc", slot + 1)))
+}
+
+/// 存档槽信息（记录在索引文件中，用于在存档管理对话框中显示）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlotInfo {
+    /// 存档名称
+    pub name: String,
+    /// 保存时间
+    pub saved_at: DateTime<Local>,
+    /// 黑方在盘棋子数
+    pub black_count: usize,
+    /// 白方在盘棋子数
+    pub white_count: usize,
+    /// 电脑难度等级（旧索引缺省为 None）
+    #[serde(default)]
+    pub ai_level: Option<u8>,
+    /// 对局结果（尚未结束时为 None）
+    #[serde(default)]
+    pub result: Option<GameResult>,
+}
+
+/// 存档槽索引（`saves/slots.json`），空槽为 None
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlotIndex {
+    pub slots: Vec<Option<SlotInfo>>,
+}
+
+impl Default for SlotIndex {
+    fn default() -> Self {
+        Self { slots: vec![None; SAVE_SLOT_COUNT] }
+    }
+}
+
+impl SlotIndex {
+    /// 从配置目录加载索引，不存在或解析失败时返回全空的索引
+    pub fn load() -> Self {
+        slots_dir()
+            .map(|dir| Self::load_from(&dir.join(SLOT_INDEX_FILE)))
+            .unwrap_or_default()
+    }
+
+    /// 从指定文件加载索引，槽数量不符时截断或补齐
+    pub fn load_from(path: &Path) -> Self {
+        let mut index: Self = fs::read_to_string(path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        index.slots.resize(SAVE_SLOT_COUNT, None);
+        index
+    }
+
+    /// 保存索引到指定文件
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).context("序列化存档槽索引失败")?;
+        fs::write(path, json).context("写入存档槽索引失败")?;
+        Ok(())
+    }
+}
+
+/// 保存棋局到存档槽，并更新索引（槽名称即元数据中的玩家名称）
+///
+/// 返回更新后的索引
+pub fn save_to_slot(
+    slot: usize,
+    board: &Board,
+    player_side: Side,
+    current_turn: Side,
+    move_history: &[MoveRecord],
+    metadata: &SaveMetadata,
+) -> Result<SlotIndex> {
+    if slot >= SAVE_SLOT_COUNT {
+        anyhow::bail!("存档槽编号超出范围: {}", slot + 1);
+    }
+    let dir = slots_dir().context("无法确定配置目录")?;
+    fs::create_dir_all(&dir).context("创建存档目录失败")?;
+
+    let path = slot_path(slot).context("无法确定配置目录")?;
+    save_game(board, player_side, current_turn, move_history, Some(metadata), &path)?;
+
+    let index_path = dir.join(SLOT_INDEX_FILE);
+    let mut index = SlotIndex::load_from(&index_path);
+    index.slots[slot] = Some(SlotInfo {
+        name: metadata.player_name.clone(),
+        saved_at: metadata.saved_at().unwrap_or_else(Local::now),
+        black_count: board.count_active(Side::Black),
+        white_count: board.count_active(Side::White),
+        ai_level: Some(metadata.ai_level),
+        result: metadata.result,
+    });
+    index.save_to(&index_path)?;
+    Ok(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn loaded(notation: &str) -> LoadedGame {
+        LoadedGame {
+            board: Board::from_notation(notation).unwrap(),
+            player_side: Side::Black,
+            current_turn: Side::Black,
+            move_history: Vec::new(),
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn resumable_autosave_follows_small_material_setting() {
+        // 双方各两子且一步之内无法困毙对方：启用少子判和时已是和局
+        let game = loaded("W..W/..../..../B..B");
+        let mut settings = GameSettings::default();
+        assert!(!is_resumable(&game, &settings));
+        settings.small_material_draw = false;
+        assert!(is_resumable(&game, &settings));
+    }
+
+    /// 测试用的临时存档路径（按进程区分，避免并行测试互相覆盖）
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("six-rush-{}-{}", std::process::id(), name))
+    }
+
+    /// 走几步（含一次吃子）后的对局
+    fn played_game() -> crate::game::Game {
+        let mut game = crate::game::Game::new();
+        game.handle_event(crate::game::state::GameEvent::StartNewGame { player_first: true, ai_level: 2 }).unwrap();
+        for (from, to) in [((0, 1), (1, 1)), ((0, 2), (0, 1)), ((1, 1), (1, 2)), ((3, 2), (2, 2)), ((1, 0), (1, 1))] {
+            game.play_move(from, to).unwrap();
+        }
+        assert!(game.move_history.iter().any(|record| !record.captured.is_empty()));
+        game
+    }
+
+    #[test]
+    fn binary_save_round_trips() {
+        let game = played_game();
+        let metadata = game.save_metadata("tester");
+        let path = temp_path("round-trip.6zb");
+        save_game(&game.board, game.player_side, game.current_turn, &game.move_history, Some(&metadata), &path).unwrap();
+        let bytes = fs::read(&path).unwrap();
+        let loaded = load_game(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert!(bytes.starts_with(BINARY_MAGIC));
+        let loaded = loaded.unwrap();
+        assert!(loaded.board.positions_equal(&game.board));
+        assert_eq!(loaded.board.pieces, game.board.pieces);
+        assert_eq!(loaded.move_history, game.move_history);
+        assert_eq!(loaded.player_side, game.player_side);
+        assert_eq!(loaded.current_turn, game.current_turn);
+        assert_eq!(loaded.metadata.map(|m| m.player_name), Some("tester".to_string()));
+    }
+
+    #[test]
+    fn json_save_round_trips_and_matches_binary() {
+        let game = played_game();
+        let metadata = game.save_metadata("tester");
+        let json_path = temp_path("round-trip.6zc");
+        let binary_path = temp_path("round-trip-json.6zb");
+        save_game(&game.board, game.player_side, game.current_turn, &game.move_history, Some(&metadata), &json_path).unwrap();
+        save_game(&game.board, game.player_side, game.current_turn, &game.move_history, Some(&metadata), &binary_path).unwrap();
+        let text = fs::read_to_string(&json_path).unwrap();
+        let from_json = load_game(&json_path);
+        let from_binary = load_game(&binary_path);
+        fs::remove_file(&json_path).unwrap();
+        fs::remove_file(&binary_path).unwrap();
+
+        assert!(text.trim_start().starts_with('{'));
+        let from_json = from_json.unwrap();
+        assert_eq!(from_json.board.size, game.board.size);
+        assert_eq!(from_json.board.pieces, game.board.pieces);
+        assert_eq!(from_json.board.to_notation(), game.board.to_notation());
+        assert_eq!(from_json.move_history, game.move_history);
+        assert_eq!(from_json.player_side, game.player_side);
+        assert_eq!(from_json.current_turn, game.current_turn);
+        assert_eq!(from_json.metadata.as_ref(), Some(&metadata));
+
+        // 两种格式加载出同样的棋局
+        let from_binary = from_binary.unwrap();
+        assert_eq!(from_binary.board.pieces, from_json.board.pieces);
+        assert_eq!(from_binary.move_history, from_json.move_history);
+        assert_eq!(from_binary.metadata, from_json.metadata);
+    }
+
+    #[test]
+    fn undo_after_loading_moves_the_right_pieces_back() {
+        let game = played_game();
+        let last = game.move_history.last().unwrap().clone();
+        let mut expected = game.board.clone();
+        expected.undo_move(&last).unwrap();
+
+        let path = temp_path("undo.6zb");
+        save_game(&game.board, game.player_side, game.current_turn, &game.move_history, None, &path).unwrap();
+        let from_binary = load_game(&path);
+        fs::remove_file(&path).unwrap();
+        let json = save_to_string(&game.board, game.player_side, game.current_turn, &game.move_history, None).unwrap();
+
+        for loaded in [from_binary.unwrap(), load_from_string(&json).unwrap()] {
+            let mut restored = crate::game::Game::new();
+            restored.board = loaded.board;
+            restored.player_side = loaded.player_side;
+            restored.current_turn = loaded.current_turn;
+            restored.move_history = loaded.move_history;
+            restored.state = crate::game::GameState::WaitingForPlayer;
+
+            // 最后一步是玩家吃子：撤销后走子的黑子退回起点，被吃的白子回到原位
+            restored.perform_undo().unwrap();
+            assert_eq!(restored.board.pieces, expected.pieces);
+            assert_eq!(restored.board.piece_by_id(last.piece_id).unwrap().position, last.from);
+            for captured in &last.captured {
+                let piece = restored.board.piece_by_id(captured.piece_id).unwrap();
+                assert!(piece.active);
+                assert_eq!(piece.position, captured.position);
+            }
+            assert_eq!(restored.move_history, game.move_history[..game.move_history.len() - 1]);
+            assert_eq!(restored.current_turn, game.player_side);
+        }
+    }
+
+    #[test]
+    fn binary_save_rejects_bad_magic_and_truncation() {
+        let game = played_game();
+        let path = temp_path("corrupt.6zb");
+        save_game(&game.board, game.player_side, game.current_turn, &game.move_history, None, &path).unwrap();
+        let bytes = fs::read(&path).unwrap();
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[2] = b'X';
+        let corrupted = [
+            bad_magic,
+            bytes[..bytes.len() / 2].to_vec(),
+            bytes[..BINARY_MAGIC.len()].to_vec(),
+            bytes[..bytes.len() - 1].to_vec(),
+        ];
+        for data in corrupted {
+            fs::write(&path, &data).unwrap();
+            assert!(load_game(&path).is_err(), "{} 字节", data.len());
+        }
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn metadata_round_trips_through_json_saves() {
+        let board = Board::initial();
+        let metadata = SaveMetadata::new("tester", 4, Some(GameResult::AiWin));
+        let json = save_to_string(&board, Side::Black, Side::Black, &[], Some(&metadata)).unwrap();
+        let loaded = load_from_string(&json).unwrap().metadata.unwrap();
+
+        assert_eq!(loaded, metadata);
+        assert_eq!(loaded.saved_at().map(|time| time.to_rfc3339()), Some(metadata.saved_at.clone()));
+
+        // 没有元数据的存档加载后为 None
+        let json = save_to_string(&board, Side::Black, Side::Black, &[], None).unwrap();
+        assert!(load_from_string(&json).unwrap().metadata.is_none());
+    }
+
+    #[test]
+    fn v1_save_without_metadata_still_loads() {
+        // v1 存档只有 4x4 棋盘格子、行棋方与执子方，没有棋子ID、行棋历史和元数据
+        let json = r#"{
+            "version": 1,
+            "board": [1, 1, 1, 1, 1, 0, 0, 1, 2, 0, 0, 2, 2, 2, 2, 2],
+            "current_turn": "White",
+            "player_side": "White"
+        }"#;
+        let loaded = load_from_string(json).unwrap();
+        assert!(loaded.metadata.is_none());
+        assert!(loaded.move_history.is_empty());
+        assert!(loaded.board.positions_equal(&Board::initial()));
+        assert_eq!(loaded.player_side, Side::White);
+        assert_eq!(loaded.current_turn, Side::Black);
+    }
+
+    #[test]
+    fn slot_index_round_trips() {
+        let mut index = SlotIndex::default();
+        index.slots[1] = Some(SlotInfo {
+            name: "tester".to_string(),
+            saved_at: Local::now(),
+            black_count: 5,
+            white_count: 3,
+            ai_level: Some(4),
+            result: Some(GameResult::PlayerWin),
+        });
+        let path = temp_path("slots.json");
+        index.save_to(&path).unwrap();
+        let loaded = SlotIndex::load_from(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, index);
+        assert_eq!(loaded.slots.iter().flatten().count(), 1);
+        // 存档文件名从1开始编号，与对话框中显示的槽号一致
+        if let (Some(dir), Some(path)) = (slots_dir(), slot_path(1)) {
+            assert_eq!(path, dir.join("slot2.6zc"));
+        }
+    }
+
+    #[test]
+    fn missing_or_corrupt_slot_index_loads_empty() {
+        let path = temp_path("missing-slots.json");
+        assert_eq!(SlotIndex::load_from(&path), SlotIndex::default());
+
+        for text in ["", "{", "not json", r#"{"slots": 3}"#] {
+            fs::write(&path, text).unwrap();
+            assert_eq!(SlotIndex::load_from(&path), SlotIndex::default(), "{text:?}");
+        }
+
+        // 槽数量不符时截断或补齐
+        fs::write(&path, r#"{"slots": [null, null]}"#).unwrap();
+        assert_eq!(SlotIndex::load_from(&path).slots.len(), SAVE_SLOT_COUNT);
+        fs::remove_file(&path).unwrap();
+    }
+
+    /// 初始局面存档的JSON，由 `edit` 修改后加载
+    fn load_edited(edit: impl FnOnce(&mut serde_json::Value)) -> Result<LoadedGame> {
+        let board = Board::initial();
+        let json = save_to_string(&board, Side::Black, Side::Black, &[], None).unwrap();
+        let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        edit(&mut value);
+        load_from_string(&value.to_string())
+    }
+
+    #[test]
+    fn load_accepts_unmodified_save() {
+        assert!(load_edited(|_| {}).is_ok());
+    }
+
+    #[test]
+    fn load_rejects_malformed_saves() {
+        type Edit = fn(&mut serde_json::Value);
+        let cases: [(&str, Edit); 9] = [
+            ("size 1", |v| v["size"] = 1.into()),
+            ("size 0", |v| v["size"] = 0.into()),
+            ("size 200", |v| v["size"] = 200.into()),
+            ("棋子在棋盘外", |v| v["pieces"][0]["position"] = serde_json::json!([4, 0])),
+            ("棋子坐标溢出", |v| v["pieces"][0]["position"] = serde_json::json!([255, 255])),
+            ("棋子重叠", |v| v["pieces"][1]["position"] = serde_json::json!([0, 0])),
+            ("棋子ID重复", |v| v["pieces"][1]["id"] = 1.into()),
+            ("v1 棋盘数据长度", |v| {
+                v["version"] = 1.into();
+                v["board"] = serde_json::json!([0, 1, 2]);
+            }),
+            ("v1 非法格子值", |v| {
+                v["version"] = 1.into();
+                v["board"][0] = 7.into();
+            }),
+        ];
+        for (name, edit) in cases {
+            assert!(load_edited(edit).is_err(), "{name}");
+        }
+    }
+
+    #[test]
+    fn position_string_round_trips() {
+        let game = played_game();
+        for side in [Side::Black, Side::White] {
+            let text = position_to_string(&game.board, side);
+            let loaded = load_from_string(&text).unwrap();
+            assert_eq!(loaded.board.to_notation(), game.board.to_notation());
+            assert!(loaded.board.positions_equal(&game.board));
+            assert_eq!(loaded.current_turn, side);
+            assert_eq!(loaded.player_side, side);
+            assert!(loaded.move_history.is_empty());
+        }
+
+        assert_eq!(position_to_string(&Board::initial(), Side::Black), "WWWW/W..W/B..B/BBBB B");
+        // 前后空白与小写行棋方均可接受，省略行棋方时为黑方
+        assert_eq!(load_from_string("  WWWW/W..W/B..B/BBBB w\n").unwrap().current_turn, Side::White);
+        assert_eq!(load_from_string("WWWW/W..W/B..B/BBBB").unwrap().current_turn, Side::Black);
+
+        // 完整JSON同样可以还原行棋历史
+        let json = save_to_string(&game.board, game.player_side, game.current_turn, &game.move_history, None).unwrap();
+        let loaded = load_from_string(&json).unwrap();
+        assert_eq!(loaded.board.pieces, game.board.pieces);
+        assert_eq!(loaded.move_history, game.move_history);
+    }
+
+    #[test]
+    fn load_from_string_rejects_malformed_text() {
+        for text in [
+            "",
+            "   ",
+            "WWWW/W..W/B..B/BBBB X",
+            "WWWW/W..W/B..B/BBBB B extra",
+            "WWWW/W..W/B..B B",
+            "WWWW/W..W/B..B/BBB B",
+            "WWWW/W.?W/B..B/BBBB B",
+            "{\"version\": 2",
+            "{}",
+        ] {
+            assert!(load_from_string(text).is_err(), "{text:?}");
+        }
+    }
+
+    #[test]
+    fn save_data_ignores_pieces_outside_the_board() {
+        let mut board = Board::initial();
+        board.pieces[0].position = (200, 200);
+        let data = SaveData::new(&board, Side::Black, Side::Black, &[], None);
+        assert_eq!(data.board.iter().filter(|&&cell| cell != 0).count(), board.pieces.len() - 1);
+    }
+
+    #[test]
+    fn initial_position_check_handles_tiny_boards() {
+        let mut board = Board::initial();
+        board.size = 1;
+        assert!(!is_initial_position(&board));
+    }
+
+    #[test]
+    fn initial_position_is_not_resumable() {
+        let game = loaded(&Board::initial().to_notation());
+        assert!(!is_resumable(&game, &GameSettings::default()));
+    }
+}
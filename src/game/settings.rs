@@ -0,0 +1,515 @@
+//! 游戏设置
+//!
+//! 设置以JSON格式保存在用户配置目录下（`six-rush/settings.json`）
+
+use crate::game::ai::AiStyle;
+use crate::game::clock::TimeControl;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// 开启无吃子判和时的默认步数（半回合）
+pub const DEFAULT_NO_CAPTURE_DRAW_LIMIT: u32 = 40;
+
+/// 无吃子判和步数的可选范围（半回合）
+pub const MIN_NO_CAPTURE_DRAW_LIMIT: u32 = 10;
+pub const MAX_NO_CAPTURE_DRAW_LIMIT: u32 = 200;
+
+/// 默认的电脑难度等级
+pub const DEFAULT_AI_LEVEL: u8 = 3;
+
+/// 演示模式关闭无吃子判和时仍使用的步数上限（半回合），避免电脑对弈无休止进行
+pub const DEMO_NO_CAPTURE_DRAW_LIMIT: u32 = 100;
+
+/// 同一局面出现多少次判和
+pub const REPETITION_DRAW_COUNT: usize = 3;
+
+/// 动画速度倍率范围（倍率越大动画越快）
+pub const MIN_ANIMATION_SPEED: f32 = 0.5;
+pub const MAX_ANIMATION_SPEED: f32 = 2.0;
+
+/// 落子容差范围（占格距的比例）：鼠标离交叉点在此范围内即吸附到该点；
+/// 超过0.5会与相邻交叉点的范围重叠
+pub const MIN_DROP_TOLERANCE: f32 = 0.2;
+pub const MAX_DROP_TOLERANCE: f32 = 0.5;
+
+/// 默认的落子容差
+pub const DEFAULT_DROP_TOLERANCE: f32 = 0.4;
+
+/// 设置文件名
+const SETTINGS_FILE: &str = "settings.json";
+
+/// 可用的界面语言（与 locales/ 下的文件对应）
+pub const SUPPORTED_LOCALES: [&str; 4] = ["zh-CN", "en", "ja", "ko"];
+
+/// 无法识别系统语言时使用的界面语言
+pub const DEFAULT_LOCALE: &str = "zh-CN";
+
+/// 棋盘主题
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Theme {
+    /// 木纹（默认）
+    #[default]
+    Wood,
+    /// 深色
+    Dark,
+    /// 高对比度
+    HighContrast,
+}
+
+impl Theme {
+    /// 所有主题
+    pub const ALL: [Theme; 3] = [Theme::Wood, Theme::Dark, Theme::HighContrast];
+
+    /// 获取本地化的显示文本
+    pub fn display_text(&self) -> String {
+        match self {
+            Theme::Wood => crate::t!("theme.wood"),
+            Theme::Dark => crate::t!("theme.dark"),
+            Theme::HighContrast => crate::t!("theme.high_contrast"),
+        }
+    }
+}
+
+/// 可自定义快捷键的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ShortcutAction {
+    NewGame,
+    LoadGame,
+    SaveGame,
+    Undo,
+    /// 切换"显示全部可走位置"
+    ToggleAllMoves,
+    /// 暂停/继续
+    Pause,
+}
+
+impl ShortcutAction {
+    /// 所有操作
+    pub const ALL: [ShortcutAction; 6] = [
+        ShortcutAction::NewGame,
+        ShortcutAction::LoadGame,
+        ShortcutAction::SaveGame,
+        ShortcutAction::Undo,
+        ShortcutAction::ToggleAllMoves,
+        ShortcutAction::Pause,
+    ];
+
+    /// 获取本地化的显示文本
+    pub fn display_text(&self) -> String {
+        match self {
+            ShortcutAction::NewGame => crate::t!("shortcuts.new_game"),
+            ShortcutAction::LoadGame => crate::t!("shortcuts.load_game"),
+            ShortcutAction::SaveGame => crate::t!("shortcuts.save_game"),
+            ShortcutAction::Undo => crate::t!("shortcuts.undo"),
+            ShortcutAction::ToggleAllMoves => crate::t!("shortcuts.toggle_all_moves"),
+            ShortcutAction::Pause => crate::t!("shortcuts.pause"),
+        }
+    }
+}
+
+/// 快捷键：按键名（与 egui 的按键名称一致，如 `F2`、`Z`）加修饰键
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyBinding {
+    pub key: String,
+    #[serde(default)]
+    pub ctrl: bool,
+    #[serde(default)]
+    pub shift: bool,
+    #[serde(default)]
+    pub alt: bool,
+}
+
+impl KeyBinding {
+    /// 不带修饰键的快捷键
+    pub fn plain(key: &str) -> Self {
+        Self { key: key.to_string(), ctrl: false, shift: false, alt: false }
+    }
+
+    /// Ctrl + 按键
+    pub fn ctrl(key: &str) -> Self {
+        Self { ctrl: true, ..Self::plain(key) }
+    }
+
+    /// 是否与按下的按键（含修饰键）完全一致
+    pub fn matches(&self, key: &str, ctrl: bool, shift: bool, alt: bool) -> bool {
+        self.key == key && self.ctrl == ctrl && self.shift == shift && self.alt == alt
+    }
+
+    /// 显示文本，如 `Ctrl+Z`
+    pub fn label(&self) -> String {
+        let mut parts = Vec::new();
+        if self.ctrl {
+            parts.push("Ctrl");
+        }
+        if self.shift {
+            parts.push("Shift");
+        }
+        if self.alt {
+            parts.push("Alt");
+        }
+        parts.push(&self.key);
+        parts.join("+")
+    }
+}
+
+/// 快捷键设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Shortcuts {
+    pub new_game: KeyBinding,
+    pub load_game: KeyBinding,
+    pub save_game: KeyBinding,
+    pub undo: KeyBinding,
+    pub toggle_all_moves: KeyBinding,
+    pub pause: KeyBinding,
+}
+
+impl Default for Shortcuts {
+    fn default() -> Self {
+        Self {
+            new_game: KeyBinding::plain("F2"),
+            load_game: KeyBinding::plain("F3"),
+            save_game: KeyBinding::plain("F4"),
+            undo: KeyBinding::ctrl("Z"),
+            toggle_all_moves: KeyBinding::plain("H"),
+            pause: KeyBinding::plain("P"),
+        }
+    }
+}
+
+impl Shortcuts {
+    /// 某操作的快捷键
+    pub fn get(&self, action: ShortcutAction) -> &KeyBinding {
+        match action {
+            ShortcutAction::NewGame => &self.new_game,
+            ShortcutAction::LoadGame => &self.load_game,
+            ShortcutAction::SaveGame => &self.save_game,
+            ShortcutAction::Undo => &self.undo,
+            ShortcutAction::ToggleAllMoves => &self.toggle_all_moves,
+            ShortcutAction::Pause => &self.pause,
+        }
+    }
+
+    /// 修改某操作的快捷键
+    pub fn set(&mut self, action: ShortcutAction, binding: KeyBinding) {
+        let slot = match action {
+            ShortcutAction::NewGame => &mut self.new_game,
+            ShortcutAction::LoadGame => &mut self.load_game,
+            ShortcutAction::SaveGame => &mut self.save_game,
+            ShortcutAction::Undo => &mut self.undo,
+            ShortcutAction::ToggleAllMoves => &mut self.toggle_all_moves,
+            ShortcutAction::Pause => &mut self.pause,
+        };
+        *slot = binding;
+    }
+
+    /// 按下的按键对应的操作（多个操作绑定同一按键时取第一个）
+    pub fn action_for(&self, key: &str, ctrl: bool, shift: bool, alt: bool) -> Option<ShortcutAction> {
+        ShortcutAction::ALL
+            .into_iter()
+            .find(|&action| self.get(action).matches(key, ctrl, shift, alt))
+    }
+
+    /// 与其他操作使用相同快捷键的操作
+    pub fn conflicts(&self) -> Vec<ShortcutAction> {
+        ShortcutAction::ALL
+            .into_iter()
+            .filter(|&action| {
+                ShortcutAction::ALL
+                    .into_iter()
+                    .any(|other| other != action && self.get(other) == self.get(action))
+            })
+            .collect()
+    }
+}
+
+/// 游戏设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameSettings {
+    /// 无吃子判和：连续N步（半回合）无吃子则判平局，None 表示关闭此规则（默认关闭）
+    pub no_capture_draw_limit: Option<u32>,
+    /// 少子判和：双方均不超过2枚棋子且无法困毙对方时判平局
+    pub small_material_draw: bool,
+    /// 重复局面判和：同一局面（同一方刚走完）第三次出现时判平局
+    pub repetition_draw: bool,
+    /// 棋盘主题
+    pub theme: Theme,
+    /// 是否在棋盘边缘显示坐标（A-D / 1-4）
+    pub show_coordinates: bool,
+    /// 执白时是否翻转棋盘，使己方棋子位于下方
+    pub flip_board_for_white: bool,
+    /// 轮到玩家时是否显示己方所有棋子的可走位置（教学辅助）
+    pub show_all_moves: bool,
+    /// 确认落子：选定目标点后需确认才真正行棋
+    pub confirm_moves: bool,
+    /// 新手提示：走法不合法时简要说明原因
+    pub beginner_hints: bool,
+    /// 是否在状态栏显示双方思考用时
+    pub show_clocks: bool,
+    /// 限时模式（None 表示不限时，默认不限时）
+    pub time_control: Option<TimeControl>,
+    /// 轮到玩家时是否显示电脑对当前局面的评估（分析辅助）
+    pub show_evaluation: bool,
+    /// 动画速度倍率（0.5x - 2x）
+    pub animation_speed: f32,
+    /// 是否跳过所有动画（立即完成）
+    pub instant_animations: bool,
+    /// 落子容差（占格距的比例）：点击或松开的位置离交叉点多远仍算落在该点上
+    pub drop_tolerance: f32,
+    /// 是否减弱吃子闪烁（适合对闪烁敏感的用户）
+    pub reduce_flashing: bool,
+    /// 是否减少动画：棋子直接落到目标点，被吃棋子不闪烁、直接移除，悔棋直接复位
+    pub reduce_motion: bool,
+    /// 下一局的电脑难度等级（1-5），新局对话框以此为初始值，对局中途修改不影响当前对局
+    pub default_ai_level: u8,
+    /// 开局变化：电脑在开局库的多个同样强的应着中随机选择（否则总是同一开局）
+    pub opening_variety: bool,
+    /// 电脑的行棋风格
+    pub ai_style: AiStyle,
+    /// 是否启用音效
+    pub sound_enabled: bool,
+    /// 音效音量（0.0 - 1.0）
+    pub sound_volume: f32,
+    /// 是否播放背景音乐
+    pub music_enabled: bool,
+    /// 背景音乐音量（0.0 - 1.0）
+    pub music_volume: f32,
+    /// 用户手动选择的界面语言，None 表示跟随系统语言
+    pub language: Option<String>,
+    /// 快捷键
+    pub shortcuts: Shortcuts,
+    /// 上次关闭时的窗口位置（屏幕坐标），None 表示由系统决定
+    pub window_pos: Option<[f32; 2]>,
+    /// 上次关闭时的窗口内部大小
+    pub window_size: Option<[f32; 2]>,
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        Self {
+            no_capture_draw_limit: None,
+            small_material_draw: true,
+            repetition_draw: true,
+            theme: Theme::default(),
+            show_coordinates: false,
+            flip_board_for_white: true,
+            show_all_moves: false,
+            confirm_moves: false,
+            beginner_hints: true,
+            show_clocks: false,
+            time_control: None,
+            show_evaluation: false,
+            animation_speed: 1.0,
+            instant_animations: false,
+            drop_tolerance: DEFAULT_DROP_TOLERANCE,
+            reduce_flashing: false,
+            reduce_motion: false,
+            default_ai_level: DEFAULT_AI_LEVEL,
+            opening_variety: false,
+            ai_style: AiStyle::default(),
+            sound_enabled: true,
+            sound_volume: 1.0,
+            music_enabled: false,
+            music_volume: 0.5,
+            language: None,
+            shortcuts: Shortcuts::default(),
+            window_pos: None,
+            window_size: None,
+        }
+    }
+}
+
+impl GameSettings {
+    /// 按动画速度换算动画时长，跳过动画时返回0
+    pub fn scale_duration(&self, base_ms: u64) -> u64 {
+        if self.instant_animations {
+            return 0;
+        }
+        let speed = self.animation_speed.clamp(MIN_ANIMATION_SPEED, MAX_ANIMATION_SPEED);
+        (base_ms as f32 / speed).round() as u64
+    }
+
+    /// 限制在合理范围内的落子容差
+    pub fn drop_tolerance(&self) -> f32 {
+        self.drop_tolerance.clamp(MIN_DROP_TOLERANCE, MAX_DROP_TOLERANCE)
+    }
+
+    /// 当前应使用的界面语言：优先使用手动选择的语言，否则检测系统语言
+    pub fn locale(&self) -> &'static str {
+        self.language
+            .as_deref()
+            .and_then(|lang| SUPPORTED_LOCALES.into_iter().find(|&l| l == lang))
+            .unwrap_or_else(detect_system_locale)
+    }
+
+    /// 从用户配置目录加载设置，不存在或解析失败时使用默认设置
+    pub fn load() -> Self {
+        let Some(path) = settings_path() else {
+            return Self::default();
+        };
+        fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// 保存设置到用户配置目录
+    pub fn save(&self) -> Result<()> {
+        let path = settings_path().context("无法确定配置目录")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).context("创建配置目录失败")?;
+        }
+        let json = serde_json::to_string_pretty(self).context("序列化设置失败")?;
+        fs::write(&path, json).context("写入设置文件失败")?;
+        Ok(())
+    }
+}
+
+/// 检测系统语言
+///
+/// 优先取环境变量（`LC_ALL`、`LC_MESSAGES`、`LANG`）中第一个有效的设置，
+/// 未设置或无法识别时按平台接口（如 Windows、macOS 的用户首选语言）依次查询，
+/// 均无法映射到可用的界面语言时返回 [`DEFAULT_LOCALE`]
+pub fn detect_system_locale() -> &'static str {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .into_iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .and_then(|value| map_locale(&value))
+        .or_else(|| sys_locale::get_locales().find_map(|value| map_locale(&value)))
+        .unwrap_or(DEFAULT_LOCALE)
+}
+
+/// 将系统语言字符串（如 `en_US.UTF-8`、`ja-JP`、`zh_TW`）映射到可用的界面语言
+///
+/// 只比较语言部分，中文的各地区变体均映射到 zh-CN；不支持的语言返回 None
+pub fn map_locale(system: &str) -> Option<&'static str> {
+    let language = system
+        .split(['.', '@'])
+        .next()?
+        .split(['_', '-'])
+        .next()?
+        .to_ascii_lowercase();
+    match language.as_str() {
+        "zh" => Some("zh-CN"),
+        "en" => Some("en"),
+        "ja" => Some("ja"),
+        "ko" => Some("ko"),
+        _ => None,
+    }
+}
+
+/// 应用配置目录
+///
+/// - Windows: `%APPDATA%/six-rush`
+/// - 其他平台: `$XDG_CONFIG_HOME/six-rush` 或 `~/.config/six-rush`
+pub fn config_dir() -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    };
+    base.map(|dir| dir.join("six-rush"))
+}
+
+/// 设置文件路径
+fn settings_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(SETTINGS_FILE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_locale_matches_language_part() {
+        for (system, expected) in [
+            ("zh_CN.UTF-8", "zh-CN"),
+            ("zh-TW", "zh-CN"),
+            ("zh-Hans-CN", "zh-CN"),
+            ("en_US.UTF-8", "en"),
+            ("en-GB", "en"),
+            ("EN", "en"),
+            ("ja_JP.eucJP", "ja"),
+            ("ja-JP", "ja"),
+            ("ko_KR.UTF-8", "ko"),
+            ("ko@euro", "ko"),
+        ] {
+            assert_eq!(map_locale(system), Some(expected), "{system}");
+        }
+    }
+
+    #[test]
+    fn map_locale_rejects_unsupported_languages() {
+        for system in ["fr_FR.UTF-8", "de-DE", "C", "POSIX", "", "zhx_CN"] {
+            assert_eq!(map_locale(system), None, "{system}");
+        }
+    }
+
+    #[test]
+    fn drop_tolerance_is_clamped() {
+        let with = |drop_tolerance| GameSettings { drop_tolerance, ..Default::default() }.drop_tolerance();
+        assert_eq!(with(MIN_DROP_TOLERANCE), 0.2);
+        assert_eq!(with(MAX_DROP_TOLERANCE), 0.5);
+        assert_eq!(with(0.3), 0.3);
+        assert_eq!(with(0.1), MIN_DROP_TOLERANCE);
+        assert_eq!(with(-1.0), MIN_DROP_TOLERANCE);
+        assert_eq!(with(0.8), MAX_DROP_TOLERANCE);
+    }
+
+    #[test]
+    fn old_settings_without_drop_tolerance_use_default() {
+        // 旧版设置文件没有 drop_tolerance 字段
+        let mut json = serde_json::to_value(GameSettings { drop_tolerance: 0.25, ..Default::default() }).unwrap();
+        json.as_object_mut().unwrap().remove("drop_tolerance");
+        let settings: GameSettings = serde_json::from_value(json).unwrap();
+        assert_eq!(settings.drop_tolerance, DEFAULT_DROP_TOLERANCE);
+        assert_eq!(settings.drop_tolerance(), DEFAULT_DROP_TOLERANCE);
+    }
+
+    #[test]
+    fn default_shortcuts_resolve_to_their_actions() {
+        let shortcuts = Shortcuts::default();
+        let cases = [
+            ("F2", false, ShortcutAction::NewGame),
+            ("F3", false, ShortcutAction::LoadGame),
+            ("F4", false, ShortcutAction::SaveGame),
+            ("Z", true, ShortcutAction::Undo),
+            ("H", false, ShortcutAction::ToggleAllMoves),
+            ("P", false, ShortcutAction::Pause),
+        ];
+        for (key, ctrl, action) in cases {
+            assert_eq!(shortcuts.action_for(key, ctrl, false, false), Some(action), "{key}");
+        }
+        // 修饰键必须完全一致
+        assert_eq!(shortcuts.action_for("Z", false, false, false), None);
+        assert_eq!(shortcuts.action_for("F2", true, false, false), None);
+        assert_eq!(shortcuts.action_for("F2", false, true, false), None);
+        assert_eq!(shortcuts.action_for("F5", false, false, false), None);
+        assert!(shortcuts.conflicts().is_empty());
+    }
+
+    #[test]
+    fn rebinding_replaces_the_default_shortcut() {
+        let mut shortcuts = Shortcuts::default();
+        shortcuts.set(ShortcutAction::Undo, KeyBinding { alt: true, ..KeyBinding::plain("U") });
+        assert_eq!(shortcuts.action_for("U", false, false, true), Some(ShortcutAction::Undo));
+        assert_eq!(shortcuts.action_for("Z", true, false, false), None);
+        assert_eq!(shortcuts.get(ShortcutAction::Undo).label(), "Alt+U");
+
+        // 与其他操作相同的快捷键被标记为冲突
+        shortcuts.set(ShortcutAction::Pause, KeyBinding::plain("F2"));
+        assert_eq!(shortcuts.conflicts(), vec![ShortcutAction::NewGame, ShortcutAction::Pause]);
+
+        // 重新绑定随设置文件保存
+        let settings = GameSettings { shortcuts: shortcuts.clone(), ..Default::default() };
+        let json = serde_json::to_string(&settings).unwrap();
+        let loaded: GameSettings = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.shortcuts, shortcuts);
+    }
+}
@@ -2,6 +2,7 @@
 //!
 //! 按照 specification.md 中的状态流转图实现
 
+use crate::game::piece::Side;
 use serde::{Deserialize, Serialize};
 
 /// 游戏状态
@@ -25,6 +26,18 @@ pub enum GameState {
     /// - 等待玩家点击目标点或取消
     PieceSelected,
     
+    /// 棋子拖拽中 - 玩家按住左键拖动选中的棋子，棋子跟随鼠标
+    /// 松开到合法目标点则移动，否则进入"等待点击目标点"状态
+    PieceDragging,
+    
+    /// 等待点击目标点 - 按下棋子后未拖动即松开（或拖拽未落到合法目标点）
+    /// 棋子保持选中，等待玩家点击目标点、右键或点击其他位置取消
+    WaitingForTargetClick,
+    
+    /// 等待确认落子 - 启用"确认落子"时，玩家选定合法目标点后等待确认或取消
+    /// 确认后进入棋子移动动画，取消则棋子留在原位并回到初始状态
+    ConfirmingMove,
+
     /// 棋子移动动画 - 棋子以动画方式移动到目标位置
     PieceMoving,
     
@@ -39,6 +52,10 @@ pub enum GameState {
     
     /// 胜负平局弹框 - 显示结果对话框
     /// 此状态下可操作UI（悔棋、新局）
+    ///
+    /// 终局不变式：棋局结束（`Game::last_result` 为 Some）后，状态机拒绝一切行棋事件
+    /// （见 [`GameEvent::is_gameplay`]），棋盘只能经由悔棋或新局改变，
+    /// 动画收尾或残留的输入事件不会在终局后改动棋盘
     GameOverDialog(GameResult),
     
     /// 悔棋动画中 - 棋子回退动画
@@ -67,6 +84,72 @@ impl GameResult {
     }
 }
 
+/// 对局结束的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameEndReason {
+    /// 一方棋子全部被吃
+    NoPieces,
+    /// 行棋方无子可动（困毙）
+    Stalemate,
+    /// 双方子力不足，判和
+    InsufficientMaterial,
+    /// 连续多步无吃子，判和
+    NoCaptureLimit,
+    /// 同一局面（同一方刚走完）第三次出现，判和
+    Repetition,
+    /// 玩家认输
+    Resignation,
+    /// 电脑接受提和
+    DrawAgreed,
+    /// 限时模式下一方用完时间
+    Timeout,
+}
+
+impl GameEndReason {
+    /// 获取本地化的说明文本
+    pub fn display_text(&self) -> String {
+        match self {
+            GameEndReason::NoPieces => crate::t!("end_reason.no_pieces"),
+            GameEndReason::Stalemate => crate::t!("end_reason.stalemate"),
+            GameEndReason::InsufficientMaterial => crate::t!("end_reason.insufficient_material"),
+            GameEndReason::NoCaptureLimit => crate::t!("end_reason.no_capture_limit"),
+            GameEndReason::Repetition => crate::t!("end_reason.repetition"),
+            GameEndReason::Resignation => crate::t!("end_reason.resignation"),
+            GameEndReason::DrawAgreed => crate::t!("end_reason.draw_agreed"),
+            GameEndReason::Timeout => crate::t!("end_reason.timeout"),
+        }
+    }
+}
+
+/// 悔棋是否可用及不可用的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UndoStatus {
+    /// 可以悔棋
+    Available,
+    /// 历史中没有可回退的玩家棋步
+    NoHistory,
+    /// 当前状态不允许悔棋（如电脑思考中、对局已结束或演示中）
+    WrongState,
+    /// 动画进行中
+    Animating,
+}
+
+impl UndoStatus {
+    pub fn is_available(&self) -> bool {
+        *self == UndoStatus::Available
+    }
+
+    /// 不能悔棋的本地化原因，可以悔棋时返回 None
+    pub fn reason_text(&self) -> Option<String> {
+        match self {
+            UndoStatus::Available => None,
+            UndoStatus::NoHistory => Some(crate::t!("tooltip.undo_no_history")),
+            UndoStatus::WrongState => Some(crate::t!("tooltip.undo_wrong_state")),
+            UndoStatus::Animating => Some(crate::t!("tooltip.undo_animating")),
+        }
+    }
+}
+
 impl GameState {
     /// 检查当前状态是否可操作UI
     /// 
@@ -76,13 +159,23 @@ impl GameState {
         matches!(self, 
             GameState::WaitingForPlayer | 
             GameState::PieceSelected |
+            GameState::WaitingForTargetClick |
             GameState::GameOverDialog(_)
         )
     }
     
     /// 检查当前状态是否可以悔棋
     pub fn can_undo(&self) -> bool {
-        matches!(self, GameState::WaitingForPlayer | GameState::PieceSelected)
+        matches!(self, GameState::WaitingForPlayer | GameState::PieceSelected | GameState::WaitingForTargetClick)
+    }
+
+    /// 检查当前状态下是否有选中的棋子（需要高亮并标注合法目标点）
+    pub fn has_selected_piece(&self) -> bool {
+        matches!(self,
+            GameState::PieceSelected |
+            GameState::PieceDragging |
+            GameState::WaitingForTargetClick
+        )
     }
     
     /// 检查当前状态是否可以点击棋子
@@ -99,6 +192,27 @@ impl GameState {
         )
     }
     
+    /// 获取本地化的状态说明（用于状态栏）
+    pub fn status_text(&self) -> String {
+        match self {
+            GameState::NewGame => crate::t!("status.new_game"),
+            GameState::AiThinking => crate::t!("status.ai_thinking"),
+            GameState::WaitingForPlayer => crate::t!("status.your_move"),
+            GameState::PieceSelected => crate::t!("status.piece_selected"),
+            GameState::PieceDragging => crate::t!("status.dragging"),
+            GameState::WaitingForTargetClick => crate::t!("status.piece_selected"),
+            GameState::ConfirmingMove => crate::t!("status.confirming"),
+            GameState::PieceMoving
+            | GameState::CheckingCapture
+            | GameState::CaptureAnimating
+            | GameState::CheckingGameEnd => crate::t!("status.moving"),
+            GameState::GameOverDialog(result) => {
+                format!("{} - {}", crate::t!("status.game_over"), result.display_text())
+            }
+            GameState::UndoAnimating => crate::t!("status.undoing"),
+        }
+    }
+    
     /// 检查当前状态是否需要AI行动
     pub fn needs_ai_move(&self) -> bool {
         matches!(self, GameState::AiThinking)
@@ -140,8 +254,14 @@ pub enum GameEvent {
     PlayerClickTarget { target_pos: (u8, u8) },
     /// 玩家点击无效位置（取消选择）
     PlayerClickInvalid,
-    /// 玩家右键取消
+    /// 玩家右键取消（等待确认落子时为取消落子）
     PlayerCancel,
+    /// 玩家确认落子
+    ConfirmMove,
+    /// 玩家按住选中的棋子开始拖动
+    PlayerStartMoving,
+    /// 玩家松开鼠标，但未落在合法目标点上
+    PlayerReleaseWithoutMove,
     /// 棋子移动动画完成
     PieceMoveAnimationComplete { moved: bool },
     /// 吃子检查完成
@@ -149,13 +269,36 @@ pub enum GameEvent {
     /// 吃子动画完成
     CaptureAnimationComplete,
     /// 胜负判断完成
-    GameEndCheckComplete { result: Option<GameResult> },
+    GameEndCheckComplete { result: Option<(GameResult, GameEndReason)> },
     /// 点击对话框按钮
     DialogAction(DialogAction),
     /// 开始悔棋
     StartUndo,
     /// 悔棋动画完成
     UndoAnimationComplete,
+    /// 玩家认输
+    Resign,
+    /// 玩家提和（由电脑决定是否接受）
+    OfferDraw,
+    /// 限时模式下某方用完时间
+    FlagFall { side: Side },
+}
+
+impl GameEvent {
+    /// 是否为行棋事件（选子、落子、行棋各阶段的推进、认输、提和、超时）
+    ///
+    /// 棋局结束后行棋事件一律被忽略；新局、对话框操作、悔棋与右键取消不受影响
+    pub fn is_gameplay(&self) -> bool {
+        !matches!(
+            self,
+            GameEvent::StartNewGame { .. }
+                | GameEvent::DialogAction(_)
+                | GameEvent::StartUndo
+                | GameEvent::UndoAnimationComplete
+                | GameEvent::PlayerCancel
+                | GameEvent::PlayerClickInvalid
+        )
+    }
 }
 
 /// 对话框操作
@@ -167,6 +310,37 @@ pub enum DialogAction {
     NewGame,
     /// 确定/返回
     Confirm,
+    /// 交换先后手开启新局
+    SwapSides,
+}
+
+/// 状态流转结果
+///
+/// 由 `Game::handle_event` 返回，描述本次事件引起的变化
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransitionOutcome {
+    /// 事件是否被状态流转表接受（当前状态不处理该事件、或棋局结束后的行棋事件为 false）
+    pub accepted: bool,
+    /// 流转前的状态
+    pub previous: GameState,
+    /// 流转后的状态
+    pub state: GameState,
+    /// 是否执行了一步走棋
+    pub moved: bool,
+    /// 本次走棋吃掉的棋子ID列表
+    pub captured: Vec<u8>,
+}
+
+impl TransitionOutcome {
+    /// 状态是否发生了变化
+    pub fn state_changed(&self) -> bool {
+        self.previous != self.state
+    }
+
+    /// 是否产生了吃子
+    pub fn has_capture(&self) -> bool {
+        !self.captured.is_empty()
+    }
 }
 
 /// 移动结果
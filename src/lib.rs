@@ -10,11 +10,15 @@ i18n!("locales", fallback = "zh-CN");
 // 导出 t! 宏供外部使用
 pub use rust_i18n::t;
 
+pub mod cli;
 pub mod game;
+pub mod logging;
+#[cfg(feature = "gui")]
 pub mod ui;
 pub mod utils;
 
 pub use game::*;
+#[cfg(feature = "gui")]
 pub use ui::*;
 
 /// 设置当前语言区域
@@ -0,0 +1,88 @@
+//! 日志
+//!
+//! 启用 `logging` 特性时，日志经 `log` 输出，级别由环境变量 [`LOG_ENV`] 配置
+//! （如 `SIX_RUSH_LOG=debug`，语法同 `env_logger`），默认 [`DEFAULT_LOG_LEVEL`]；
+//! 未启用时错误与警告直接打印到标准错误，信息与调试日志忽略。
+//!
+//! 统一使用 [`log_error!`](crate::log_error)、[`log_warn!`](crate::log_warn)、
+//! [`log_info!`](crate::log_info)、[`log_debug!`](crate::log_debug) 四个宏，调用方无需区分特性
+
+/// 配置日志级别的环境变量
+pub const LOG_ENV: &str = "SIX_RUSH_LOG";
+
+/// 未设置 [`LOG_ENV`] 时的日志级别
+pub const DEFAULT_LOG_LEVEL: &str = "info";
+
+#[cfg(feature = "logging")]
+#[doc(hidden)]
+pub use log;
+
+/// 初始化日志输出，程序启动时调用一次；未启用 `logging` 特性时不做任何事
+pub fn init() {
+    #[cfg(feature = "logging")]
+    env_logger::Builder::from_env(env_logger::Env::new().filter_or(LOG_ENV, DEFAULT_LOG_LEVEL)).init();
+}
+
+/// 错误日志：操作失败
+#[cfg(feature = "logging")]
+#[macro_export]
+macro_rules! log_error {
+    ($($arg:tt)+) => { $crate::logging::log::error!($($arg)+) };
+}
+
+/// 警告日志：可恢复的异常（如资源缺失时使用后备方案）
+#[cfg(feature = "logging")]
+#[macro_export]
+macro_rules! log_warn {
+    ($($arg:tt)+) => { $crate::logging::log::warn!($($arg)+) };
+}
+
+/// 信息日志：存档、读档等用户操作
+#[cfg(feature = "logging")]
+#[macro_export]
+macro_rules! log_info {
+    ($($arg:tt)+) => { $crate::logging::log::info!($($arg)+) };
+}
+
+/// 调试日志：AI 选择走法等内部细节
+#[cfg(feature = "logging")]
+#[macro_export]
+macro_rules! log_debug {
+    ($($arg:tt)+) => { $crate::logging::log::debug!($($arg)+) };
+}
+
+/// 错误日志：操作失败
+#[cfg(not(feature = "logging"))]
+#[macro_export]
+macro_rules! log_error {
+    ($($arg:tt)+) => { eprintln!($($arg)+) };
+}
+
+/// 警告日志：可恢复的异常（如资源缺失时使用后备方案）
+#[cfg(not(feature = "logging"))]
+#[macro_export]
+macro_rules! log_warn {
+    ($($arg:tt)+) => { eprintln!($($arg)+) };
+}
+
+/// 信息日志：存档、读档等用户操作
+#[cfg(not(feature = "logging"))]
+#[macro_export]
+macro_rules! log_info {
+    ($($arg:tt)+) => {
+        if false {
+            let _ = format_args!($($arg)+);
+        }
+    };
+}
+
+/// 调试日志：AI 选择走法等内部细节
+#[cfg(not(feature = "logging"))]
+#[macro_export]
+macro_rules! log_debug {
+    ($($arg:tt)+) => {
+        if false {
+            let _ = format_args!($($arg)+);
+        }
+    };
+}
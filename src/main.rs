@@ -2,24 +2,60 @@
 
 use eframe::NativeOptions;
 use egui::{FontData, FontDefinitions, FontFamily};
+use six_rush::cli::CliOptions;
+use six_rush::game::settings::GameSettings;
 use six_rush::ui::MainApp;
 
+/// 默认窗口大小
+const WINDOW_SIZE: [f32; 2] = [900.0, 700.0];
+
+/// 最小窗口大小
+const MIN_WINDOW_SIZE: [f32; 2] = [560.0, 480.0];
+
+/// 内嵌的中文后备字体（子集化，见 `src/assets/fonts/README.md`），系统中找不到中文字体时使用
+#[cfg(embedded_cjk_font)]
+const EMBEDDED_CJK_FONT: &[u8] = include_bytes!("assets/fonts/fallback_cjk.ttf");
+
 // 在二进制 crate 中也初始化 i18n，并导出 t! 宏
 rust_i18n::i18n!("locales", fallback = "zh-CN");
 pub use rust_i18n::t;
 
 fn main() -> eframe::Result<()> {
-    // 设置当前区域为中文
-    six_rush::set_locale("zh-CN");
+    six_rush::logging::init();
+    let settings = GameSettings::load();
+
+    // 按设置（或系统语言）设置当前区域，窗口标题需要在创建窗口前确定
+    six_rush::set_locale(settings.locale());
+
+    // 命令行模式：在终端中对弈，不创建窗口
+    match CliOptions::from_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => {
+            if let Err(e) = six_rush::cli::run(options) {
+                eprintln!("{:#}", e);
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("{:#}\n\n{}", e, t!("cli.usage"));
+            std::process::exit(2);
+        }
+    }
+
+    let mut viewport = egui::ViewportBuilder::default()
+        .with_inner_size(settings.window_size.unwrap_or(WINDOW_SIZE))
+        .with_min_inner_size(MIN_WINDOW_SIZE)
+        .with_resizable(true)
+        .with_maximize_button(true)
+        .with_decorations(true);
+    // 恢复上次关闭时的窗口位置，首次启动由系统决定
+    if let Some(pos) = settings.window_pos {
+        viewport = viewport.with_position(pos);
+    }
 
     let options = NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([900.0, 700.0])
-            .with_min_inner_size([900.0, 700.0])
-            .with_max_inner_size([900.0, 700.0])
-            .with_resizable(false)
-            .with_maximize_button(false)
-            .with_decorations(true),
+        viewport,
         ..Default::default()
     };
 
@@ -70,7 +106,7 @@ fn setup_fonts(ctx: &egui::Context) {
 
             fonts.font_data.insert(
                 font_name.clone(),
-                FontData::from_owned(font_data).into(),
+                FontData::from_owned(font_data),
             );
 
             // 将中文字体添加到 Proportional 和 Monospace 字体族
@@ -92,11 +128,40 @@ fn setup_fonts(ctx: &egui::Context) {
         }
     }
 
+    // 系统字体优先；都加载失败时使用内嵌的后备字体，避免常驻一份用不到的字体数据
+    #[cfg(embedded_cjk_font)]
+    if !loaded {
+        add_fallback_font(&mut fonts, "EmbeddedCjk", FontData::from_static(EMBEDDED_CJK_FONT));
+        loaded = true;
+    }
+
     if !loaded {
-        // 如果系统字体加载失败，尝试使用 egui 的默认字体配置
-        // 或者可以在这里嵌入一个备用字体
-        eprintln!("警告：未能加载中文字体，中文可能显示为方块");
+        six_rush::log_warn!("警告：未能加载中文字体，中文可能显示为方块（构建时缺少内嵌字体 src/assets/fonts/fallback_cjk.ttf）");
+    }
+
+    // 中文字体通常不含韩文字符，额外加载一个韩文字体作为最后的后备
+    let korean_font_sources = [
+        "C:/Windows/Fonts/malgun.ttf",                          // Windows 맑은 고딕
+        "/usr/share/fonts/truetype/nanum/NanumGothic.ttf",      // Linux
+        "/System/Library/Fonts/AppleSDGothicNeo.ttc",           // macOS
+    ];
+
+    for path in korean_font_sources {
+        if let Ok(font_data) = std::fs::read(path) {
+            add_fallback_font(&mut fonts, "KoreanFallback", FontData::from_owned(font_data));
+            break;
+        }
     }
 
     ctx.set_fonts(fonts);
 }
+
+/// 将字体追加到 Proportional 和 Monospace 字体族末尾，作为缺字时的后备
+fn add_fallback_font(fonts: &mut FontDefinitions, font_name: &str, font_data: FontData) {
+    fonts.font_data.insert(font_name.to_string(), font_data);
+    for family in [FontFamily::Proportional, FontFamily::Monospace] {
+        if let Some(fonts_for_family) = fonts.families.get_mut(&family) {
+            fonts_for_family.push(font_name.to_string());
+        }
+    }
+}
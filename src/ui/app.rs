@@ -1,29 +1,83 @@
 //! 主应用
 
 use eframe::CreationContext;
-use egui::{CentralPanel, Context, Key, TopBottomPanel};
+use egui::{CentralPanel, Context, Key, RichText, SidePanel, TopBottomPanel};
 use rust_i18n::t;
 use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
-use crate::game::audio::SoundPlayer;
-use crate::game::board::Board;
-use crate::game::piece::Side;
-use crate::game::save::{is_initial_position, load_game, save_game};
-use crate::game::state::{DialogAction, GameEvent, GameResult, GameState};
+use crate::game::ai::{adapt_level, evaluation_bar, AiPlayer, AiStyle};
+use crate::game::audio::{SoundPlayer, SoundType};
+use crate::game::clock::format_clock;
+use crate::game::piece::{pieces_per_side, Side};
+use crate::game::replay::{Replay, BLUNDER_REVIEW_MOVES};
+use crate::game::rules::{is_valid_move_detailed, IllegalMoveReason};
+use crate::game::settings::{
+    GameSettings, KeyBinding, ShortcutAction, Theme, DEFAULT_NO_CAPTURE_DRAW_LIMIT, MAX_ANIMATION_SPEED,
+    MAX_DROP_TOLERANCE, MAX_NO_CAPTURE_DRAW_LIMIT, MIN_ANIMATION_SPEED, MIN_DROP_TOLERANCE, MIN_NO_CAPTURE_DRAW_LIMIT,
+};
+use crate::game::save::{
+    find_resumable_autosave, load_from_string, load_game, position_to_string, remove_autosave, save_game,
+    save_to_slot, save_to_string, slot_path, write_autosave, LoadedGame, SlotIndex, BINARY_SAVE_EXTENSION,
+};
+use crate::game::state::{DialogAction, GameEvent, GameResult, GameState, TransitionOutcome, UndoStatus};
 use crate::game::Game;
-use crate::ui::board_view::BoardView;
-use crate::ui::dialogs::{AboutDialog, GameOverAction, GameOverDialog, NewGameDialog, NewGameResult, RulesDialog};
+use crate::ui::board_view::{render_board_image, step_cursor, BoardView, DIMMED_PIECE_ALPHA};
+use crate::ui::dialogs::{
+    AboutDialog, DemoDialog, DemoOverAction, DemoOverDialog, DemoSetup, GameOverAction, GameOverDialog, NewGameDialog,
+    level_name, NewGameResult, RulesDialog, SaveSlotsDialog, ShortcutsDialog, SlotAction,
+};
+use crate::ui::editor::{EditorAction, PositionEditor};
+use crate::utils::{flash_alpha, Animation, AnimationSequence, Easing, RisingEdge};
 
 /// 动画常量
 const PIECE_MOVE_DURATION_MS: u64 = 300;
-#[allow(dead_code)]
 const PIECE_RETURN_DURATION_MS: u64 = 200;
 const CAPTURE_FLASH_DURATION_MS: u64 = 600;
 const CAPTURE_REMOVE_DURATION_MS: u64 = 400;
+
+/// 吃子闪烁次数（在 CAPTURE_FLASH_DURATION_MS 内完成）
+const CAPTURE_FLASH_COUNT: u32 = 3;
+/// 减弱闪烁时的闪烁次数与最暗时的不透明度
+const REDUCED_FLASH_COUNT: u32 = 1;
+const REDUCED_FLASH_MIN_ALPHA: f32 = 0.6;
+
 const UNDO_STEP_DURATION_MS: u64 = 400;
 const AI_MIN_THINKING_TIME_MS: u64 = 100;
 
+/// AI思考指示器：延迟出现的时间（避免瞬间完成的走法闪烁）与旋转一周的时长
+const AI_INDICATOR_DELAY_MS: u64 = 50;
+const AI_INDICATOR_SPIN_MS: u64 = 800;
+
+/// 新手提示（非法走法说明）的显示时长
+const MOVE_HINT_DURATION_MS: u64 = 2500;
+
+/// 切换调试用棋子ID显示的隐藏快捷键（Ctrl+Shift+F12，不可自定义）
+const PIECE_IDS_TOGGLE_KEY: Key = Key::F12;
+
+/// 棋盘四周保留的空白（容纳网格外侧的坐标标签）
+const BOARD_PADDING: f32 = 24.0;
+/// 棋盘最小尺寸
+const MIN_BOARD_SIZE: f32 = 200.0;
+
+/// 按可用区域计算棋盘尺寸：取短边并留出四周空白，保持正方形
+fn fit_board_size(available: egui::Vec2) -> f32 {
+    (available.min_elem() - 2.0 * BOARD_PADDING).max(MIN_BOARD_SIZE)
+}
+
+/// 工具栏按钮提示：在说明后附上当前快捷键
+fn shortcut_tooltip(text: String, binding: &KeyBinding) -> String {
+    format!("{} ({})", text, binding.label())
+}
+
+/// 可用语言：(语言区域, 语言名称的翻译键)，与 locales/ 下的文件对应
+const LANGUAGES: [(&str, &str); 4] = [
+    ("zh-CN", "menu.lang_zh"),
+    ("en", "menu.lang_en"),
+    ("ja", "menu.lang_ja"),
+    ("ko", "menu.lang_ko"),
+];
+
 /// 主应用结构
 pub struct MainApp {
     /// 游戏状态
@@ -38,6 +92,17 @@ pub struct MainApp {
     about_dialog: AboutDialog,
     /// 规则对话框
     rules_dialog: RulesDialog,
+    shortcuts_dialog: ShortcutsDialog,
+    /// 存档管理对话框
+    save_slots_dialog: SaveSlotsDialog,
+    /// 演示模式设置对话框
+    demo_dialog: DemoDialog,
+    /// 演示结束对话框
+    demo_over_dialog: DemoOverDialog,
+    /// 最近一次演示的双方AI等级
+    demo_setup: DemoSetup,
+    /// 演示单步模式
+    demo_stepper: DemoStepper,
     /// 动画状态
     animations: AnimationController,
     /// 音效播放器
@@ -48,10 +113,67 @@ pub struct MainApp {
     pending_load_file: Option<PathBuf>,
     /// 待处理的保存文件路径
     pending_save_file: Option<PathBuf>,
+    /// 启动时发现的可恢复自动存档（非空时弹出恢复对话框）
+    pending_resume: Option<PathBuf>,
     /// 确认覆盖对话框状态
     confirm_overwrite: bool,
+    /// 确认认输对话框状态
+    confirm_resign: bool,
+    /// 粘贴局面对话框中的文本（None 表示对话框关闭）
+    paste_text: Option<String>,
+    /// 电脑拒绝提和的提示框状态
+    draw_declined: bool,
+    /// 待显示的错误信息（保存、加载或导出失败时）
+    pending_error: Option<String>,
+    /// 新手提示：非法走法的说明及出现时刻
+    move_hint: Option<(String, Instant)>,
+    /// 单子状态的上升沿检测（任一方刚剩一枚棋子时提示担吃规则）
+    single_piece_mode: RisingEdge,
+    /// 调试：在棋子上显示ID（隐藏快捷键切换，默认关闭）
+    show_piece_ids: bool,
+    /// 确认放弃当前对局（开始新局前）对话框状态
+    confirm_new_game: bool,
+    /// 确认放弃当前对局后打开演示设置（而非新局对话框）
+    pending_demo: bool,
     /// AI思考开始时间（用于确保最小思考时间）
     ai_think_start: Option<Instant>,
+    /// AI思考指示器的旋转动画（思考超过延迟后出现）
+    thinking_indicator: Option<Animation>,
+    /// 暂停中：最近一次结算暂停时长的时刻（None 表示未暂停）
+    paused_at: Option<Instant>,
+    /// 局面评估缓存：(局面记谱, 玩家视角的评估分)
+    evaluation: Option<(String, i32)>,
+    /// 棋局回放（Some 表示处于只读回放模式）
+    replay: Option<ReplayView>,
+    /// 局面编辑器（Some 表示处于局面编辑模式）
+    editor: Option<PositionEditor>,
+    /// 最近一步已完成的行棋（起点, 终点），下一步开始移动时隐藏
+    last_move: Option<((u8, u8), (u8, u8))>,
+    /// 是否启用自适应难度
+    adaptive_level: bool,
+    /// 本次运行中最近的对局结果（用于自适应难度，调整等级后清空）
+    recent_results: Vec<GameResult>,
+    /// 当前对局由存档、粘贴的文本或自动存档恢复而来（结果不计入自适应难度）
+    restored_game: bool,
+    /// 拖拽中的棋子当前所在的屏幕位置
+    drag_pos: Option<egui::Pos2>,
+    /// 键盘光标所在的棋盘坐标（按方向键后出现，使用鼠标时隐藏）
+    keyboard_cursor: Option<(u8, u8)>,
+    /// 当前窗口位置与内部大小（退出时保存到设置）
+    window_geometry: Option<(egui::Pos2, egui::Vec2)>,
+}
+
+/// 回放视图状态
+#[derive(Debug)]
+struct ReplayView {
+    /// 回放数据
+    replay: Replay,
+    /// 正在播放的前进一步动画
+    step_anim: Option<PieceMoveAnimation>,
+    /// 失误复盘：被标为失误的行棋下标（None 表示普通回放或未发现失误）
+    blunder: Option<usize>,
+    /// 是否为失误复盘
+    reviewing: bool,
 }
 
 /// 动画控制器
@@ -74,15 +196,132 @@ struct PieceMoveAnimation {
     to: egui::Pos2,
     start_time: Instant,
     duration_ms: u64,
+    /// 缓动曲线
+    easing: Easing,
     is_ai: bool,
 }
 
+impl PieceMoveAnimation {
+    /// 按设置创建棋子移动动画（减少动画时时长为 0，棋子直接落到终点）
+    fn new(piece_id: u8, from: egui::Pos2, to: egui::Pos2, base_duration_ms: u64, is_ai: bool, settings: &GameSettings) -> Self {
+        let base_duration_ms = if settings.reduce_motion { 0 } else { base_duration_ms };
+        Self {
+            piece_id,
+            from,
+            to,
+            start_time: Instant::now(),
+            duration_ms: settings.scale_duration(base_duration_ms),
+            easing: Easing::InOutQuad,
+            is_ai,
+        }
+    }
+
+    /// 按当前进度绘制移动中的棋子
+    fn draw(&self, ui: &mut egui::Ui, view: &BoardView, piece: &crate::game::piece::Piece) {
+        let elapsed = self.start_time.elapsed().as_millis() as f64;
+        let progress = (elapsed / self.duration_ms.max(1) as f64).min(1.0) as f32;
+
+        let current_pos = crate::utils::lerp_pos2(self.from, self.to, self.easing.apply(progress));
+        view.draw_animated_piece(ui, piece, current_pos);
+    }
+}
+
+impl AnimationController {
+    /// 推迟所有进行中动画的开始时刻（暂停期间调用）
+    fn postpone(&mut self, by: Duration) {
+        if let Some(anim) = self.piece_move.as_mut() {
+            anim.start_time += by;
+        }
+        if let Some(anim) = self.capture.as_mut() {
+            anim.start_time += by;
+        }
+        if let Some(anim) = self.undo.as_mut() {
+            anim.sequence.postpone(by);
+        }
+    }
+
+    /// 让进行中的移动与吃子动画在下一次更新时立即完成
+    fn finish_now(&mut self) {
+        if let Some(anim) = self.piece_move.as_mut() {
+            anim.duration_ms = 0;
+        }
+        if let Some(anim) = self.capture.as_mut() {
+            anim.flash_ms = 0;
+            anim.remove_ms = 0;
+        }
+    }
+}
+
+/// 演示单步模式：开启后电脑每次只在点击"下一步"后走一步
+#[derive(Debug, Clone, Copy, Default)]
+struct DemoStepper {
+    /// 是否开启单步模式
+    enabled: bool,
+    /// 已请求走下一步（电脑走出该步后清除）
+    requested: bool,
+}
+
+impl DemoStepper {
+    /// 请求走下一步
+    fn request(&mut self) {
+        self.requested = true;
+    }
+
+    /// 清除尚未执行的请求（切换单步模式或开始新的演示时）
+    fn clear(&mut self) {
+        self.requested = false;
+    }
+
+    /// 电脑现在能否走棋：未开启单步模式时总是可以，开启时消耗一次请求
+    fn take_turn(&mut self) -> bool {
+        !self.enabled || std::mem::take(&mut self.requested)
+    }
+}
+
+/// 悔棋动画每个阶段的时长（减少动画时为 0，棋子直接复位）
+fn undo_step_duration(settings: &GameSettings) -> u64 {
+    if settings.reduce_motion { 0 } else { settings.scale_duration(UNDO_STEP_DURATION_MS) }
+}
+
 /// 吃子动画
 #[derive(Debug, Clone)]
 struct CaptureAnimation {
     piece_ids: Vec<u8>,
     start_time: Instant,
     stage: CaptureStage,
+    /// 闪烁阶段时长
+    flash_ms: u64,
+    /// 闪烁次数
+    flash_count: u32,
+    /// 闪烁最暗时的不透明度（0..=1）
+    flash_min_alpha: f32,
+    /// 移除阶段时长
+    remove_ms: u64,
+}
+
+impl CaptureAnimation {
+    /// 按设置创建吃子动画（减少动画时不闪烁，被吃棋子直接移除）
+    fn new(piece_ids: Vec<u8>, settings: &GameSettings) -> Self {
+        let (flash_count, flash_min_alpha) = if settings.reduce_flashing {
+            (REDUCED_FLASH_COUNT, REDUCED_FLASH_MIN_ALPHA)
+        } else {
+            (CAPTURE_FLASH_COUNT, 0.0)
+        };
+        let (flash_ms, remove_ms) = if settings.reduce_motion {
+            (0, 0)
+        } else {
+            (CAPTURE_FLASH_DURATION_MS, CAPTURE_REMOVE_DURATION_MS)
+        };
+        Self {
+            piece_ids,
+            start_time: Instant::now(),
+            stage: CaptureStage::Flashing,
+            flash_ms: settings.scale_duration(flash_ms),
+            flash_count,
+            flash_min_alpha,
+            remove_ms: settings.scale_duration(remove_ms),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -91,25 +330,32 @@ enum CaptureStage {
     Removing,
 }
 
-/// 悔棋动画
+/// 悔棋动画：AI 回退 →（被吃棋子复位）→ 玩家回退，按阶段依次播放
 #[allow(dead_code)]
 #[derive(Debug)]
 struct UndoAnimation {
-    step: UndoStep,
-    ai_move: PieceMoveAnimation,
-    player_move: PieceMoveAnimation,
+    sequence: AnimationSequence<UndoStep>,
+    ai_move: UndoMove,
+    player_move: UndoMove,
     ai_record: crate::game::MoveRecord,
     player_record: crate::game::MoveRecord,
     captured_piece: Option<CapturedPieceInfo>,
 }
 
+/// 悔棋中回退的一步
+#[derive(Debug, Clone)]
+struct UndoMove {
+    piece_id: u8,
+    from: egui::Pos2,
+    to: egui::Pos2,
+}
+
 #[derive(Debug, Clone)]
 struct CapturedPieceInfo {
     record: crate::game::CapturedRecord,
     screen_pos: egui::Pos2,
 }
 
-#[allow(dead_code)]
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum UndoStep {
     AiUndoing,
@@ -122,8 +368,27 @@ impl MainApp {
     /// 程序启动时自动开始一局玩家先行的新游戏
     pub fn new(_cc: &CreationContext<'_>) -> Self {
         let mut game = Game::new();
+        game.settings = GameSettings::load();
         // 自动开始新局，玩家执黑先行
-        let _ = game.handle_event(GameEvent::StartNewGame { player_first: true, ai_level: game.ai_level });
+        if let Err(e) = game.handle_event(GameEvent::StartNewGame { player_first: true, ai_level: game.settings.default_ai_level }) {
+            crate::log_error!("开始新局失败: {:#}", e);
+        }
+
+        let pending_resume = find_resumable_autosave(&game.settings);
+        Self { pending_resume, ..Self::with_game(game, SoundPlayer::new()) }
+    }
+
+    /// 以给定对局和音效播放器创建应用（不读取设置与自动存档，音效按对局设置初始化）
+    fn with_game(game: Game, mut sound: SoundPlayer) -> Self {
+        sound.set_enabled(game.settings.sound_enabled);
+        sound.set_volume(game.settings.sound_volume);
+        sound.set_music_volume(game.settings.music_volume);
+        if game.settings.music_enabled {
+            sound.start_music();
+        }
+
+        let language = game.settings.locale().to_string();
+        rust_i18n::set_locale(&language);
 
         Self {
             game,
@@ -132,21 +397,175 @@ impl MainApp {
             game_over_dialog: GameOverDialog::Closed,
             about_dialog: AboutDialog::Closed,
             rules_dialog: RulesDialog::Closed,
+            shortcuts_dialog: ShortcutsDialog::Closed,
+            save_slots_dialog: SaveSlotsDialog::Closed,
+            demo_dialog: DemoDialog::Closed,
+            demo_over_dialog: DemoOverDialog::Closed,
+            demo_setup: DemoSetup::default(),
+            demo_stepper: DemoStepper::default(),
             animations: AnimationController::default(),
-            sound: SoundPlayer::new(),
-            language: "zh-CN".to_string(),
+            sound,
+            language,
             pending_load_file: None,
             pending_save_file: None,
+            pending_resume: None,
             confirm_overwrite: false,
+            confirm_resign: false,
+            paste_text: None,
+            draw_declined: false,
+            pending_error: None,
+            move_hint: None,
+            single_piece_mode: RisingEdge::default(),
+            show_piece_ids: false,
+            confirm_new_game: false,
+            pending_demo: false,
             ai_think_start: None,
-
+            thinking_indicator: None,
+            paused_at: None,
+            evaluation: None,
+            replay: None,
+            editor: None,
+            last_move: None,
+            adaptive_level: false,
+            recent_results: Vec::new(),
+            restored_game: false,
+            drag_pos: None,
+            keyboard_cursor: None,
+            window_geometry: None,
         }
     }
 
-    /// 切换语言
+    /// 切换语言（记录到设置中，下次启动不再检测系统语言）
     fn switch_language(&mut self, lang: &str) {
         self.language = lang.to_string();
         rust_i18n::set_locale(lang);
+        self.game.settings.language = Some(lang.to_string());
+        self.save_settings();
+    }
+
+    /// 显示语言列表（菜单栏和工具栏共用），当前语言带选中标记
+    fn show_language_menu(&mut self, ui: &mut egui::Ui) {
+        for (locale, name_key) in LANGUAGES {
+            if ui.selectable_label(self.language == locale, t!(name_key)).clicked() {
+                self.switch_language(locale);
+                ui.close_menu();
+            }
+        }
+    }
+
+    /// 记录当前窗口位置与大小（部分平台无法获取窗口位置，此时不记录）
+    fn track_window_geometry(&mut self, ctx: &Context) {
+        let (outer, inner) = ctx.input(|i| (i.viewport().outer_rect, i.viewport().inner_rect));
+        if let (Some(outer), Some(inner)) = (outer, inner) {
+            self.window_geometry = Some((outer.min, inner.size()));
+        }
+    }
+
+    /// 保存设置
+    fn save_settings(&self) {
+        if let Err(e) = self.game.settings.save() {
+            crate::log_error!("保存设置失败: {:#}", e);
+        }
+    }
+
+    /// 音效设置：开关、音量以及各音效试听
+    fn show_sound_settings(&mut self, ui: &mut egui::Ui) {
+        if ui.checkbox(&mut self.game.settings.sound_enabled, t!("menu.sound_enabled")).changed() {
+            self.sound.set_enabled(self.game.settings.sound_enabled);
+            self.save_settings();
+        }
+        let volume_slider = egui::Slider::new(&mut self.game.settings.sound_volume, 0.0..=1.0)
+            .text(t!("menu.sound_volume"))
+            .show_value(false);
+        let volume_response = ui.add(volume_slider);
+        if volume_response.changed() {
+            self.sound.set_volume(self.game.settings.sound_volume);
+        }
+        // 调整结束后试听一次，便于即时感受音量变化
+        let adjusted = volume_response.drag_stopped() || (volume_response.changed() && !volume_response.dragged());
+        if adjusted {
+            self.sound.click();
+            self.save_settings();
+        }
+
+        if ui.button(t!("menu.sound_reload")).clicked() {
+            self.sound.reload_sounds();
+        }
+
+        ui.separator();
+        if ui.checkbox(&mut self.game.settings.music_enabled, t!("menu.music_enabled")).changed() {
+            if self.game.settings.music_enabled {
+                self.sound.start_music();
+            } else {
+                self.sound.stop_music();
+            }
+            self.save_settings();
+        }
+        let music_slider = egui::Slider::new(&mut self.game.settings.music_volume, 0.0..=1.0)
+            .text(t!("menu.music_volume"))
+            .show_value(false);
+        let music_response = ui.add(music_slider);
+        if music_response.changed() {
+            self.sound.set_music_volume(self.game.settings.music_volume);
+        }
+        if music_response.drag_stopped() || (music_response.changed() && !music_response.dragged()) {
+            self.save_settings();
+        }
+
+        ui.separator();
+        ui.label(t!("menu.sound_preview"));
+        for sound_type in SoundType::ALL {
+            if ui.button(format!("▶ {}", sound_type.display_text())).clicked() {
+                self.sound.play(sound_type);
+            }
+        }
+    }
+
+    /// 根据行棋历史同步最近一步标记
+    ///
+    /// 新局时历史为空自动清除，悔棋后指向新的最后一步
+    fn sync_last_move(&mut self) {
+        self.last_move = if self.animations.piece_move.is_some() || self.animations.undo.is_some() {
+            None
+        } else {
+            self.game.move_history.last().map(|r| (r.from, r.to))
+        };
+    }
+
+    /// 主面板是否显示当前对局（回放与局面编辑模式下为否）
+    fn showing_game(&self) -> bool {
+        self.replay.is_none() && self.editor.is_none()
+    }
+
+    /// 是否暂停、有对话框打开或处于回放/编辑模式（此时暂停对局计时）
+    fn clock_paused(&self) -> bool {
+        self.is_paused()
+            || !self.showing_game()
+            || !matches!(self.new_game_dialog, NewGameDialog::Closed)
+            || !matches!(self.save_slots_dialog, SaveSlotsDialog::Closed)
+            || !matches!(self.demo_dialog, DemoDialog::Closed)
+            || !matches!(self.about_dialog, AboutDialog::Closed)
+            || !matches!(self.rules_dialog, RulesDialog::Closed)
+            || !matches!(self.shortcuts_dialog, ShortcutsDialog::Closed)
+            || self.pending_load_file.is_some()
+            || self.pending_resume.is_some()
+            || self.pending_error.is_some()
+            || self.confirm_overwrite
+            || self.confirm_resign
+            || self.paste_text.is_some()
+            || self.confirm_new_game
+            || self.draw_declined
+    }
+
+    /// 悔棋是否可用：在对局判断之外，界面动画、暂停与非对局画面同样阻止悔棋
+    fn undo_status(&self) -> UndoStatus {
+        if self.has_active_animation() {
+            UndoStatus::Animating
+        } else if !self.showing_game() || self.is_paused() {
+            UndoStatus::WrongState
+        } else {
+            self.game.undo_status()
+        }
     }
 
     /// 检查是否有动画正在进行
@@ -156,28 +575,96 @@ impl MainApp {
             || self.animations.undo.is_some()
     }
 
+    /// 执行快捷键对应的操作
+    fn run_shortcut(&mut self, action: ShortcutAction) {
+        match action {
+            ShortcutAction::NewGame => self.request_new_game(),
+            ShortcutAction::LoadGame => self.handle_load_game(),
+            ShortcutAction::SaveGame => self.handle_save_game(),
+            ShortcutAction::Undo => {
+                self.send_event(GameEvent::StartUndo);
+            }
+            ShortcutAction::ToggleAllMoves => {
+                self.game.settings.show_all_moves = !self.game.settings.show_all_moves;
+                self.save_settings();
+            }
+            ShortcutAction::Pause => self.toggle_pause(),
+        }
+    }
+
+    /// 是否处于暂停中
+    fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// 能否暂停：对局进行中（未结束）才可暂停
+    fn can_pause(&self) -> bool {
+        self.showing_game() && !matches!(self.game.state, GameState::GameOverDialog(_))
+    }
+
+    /// 切换暂停：恢复时先结算暂停时长，使动画与电脑思考从暂停时的进度继续
+    fn toggle_pause(&mut self) {
+        if self.is_paused() {
+            self.account_pause();
+            self.paused_at = None;
+        } else if self.can_pause() {
+            self.paused_at = Some(Instant::now());
+        }
+    }
+
+    /// 将上次结算以来的暂停时长计入所有计时起点（每帧调用，暂停期间画面保持静止）
+    fn account_pause(&mut self) {
+        let Some(since) = self.paused_at else {
+            return;
+        };
+        let now = Instant::now();
+        let by = now.saturating_duration_since(since);
+        self.animations.postpone(by);
+        if let Some(anim) = self.thinking_indicator.as_mut() {
+            anim.postpone(by);
+        }
+        if let Some(start) = self.ai_think_start.as_mut() {
+            *start += by;
+        }
+        self.paused_at = Some(now);
+    }
+
     /// 处理菜单栏
     fn handle_menu(&mut self, ctx: &Context) {
-        // 只有在可操作UI的状态下才显示/处理菜单
-        let can_interact = self.game.state.can_interact_with_ui();
+        // 只有在可操作UI的状态下才显示/处理菜单（回放模式下只读）
+        let can_interact = self.game.state.can_interact_with_ui() && self.showing_game() && !self.is_paused();
         
-        // 处理全局快捷键（当菜单可操作且没有动画时）
-        if can_interact && !self.has_active_animation() {
-            ctx.input(|i| {
-                // F2: 新局, F3: 加载, F4: 保存, Ctrl+Z: 悔棋
-                if i.key_pressed(Key::F2) {
-                    self.new_game_dialog = NewGameDialog::Open { ai_level: self.game.ai_level };
-                }
-                if i.key_pressed(Key::F3) {
-                    self.handle_load_game();
-                }
-                if i.key_pressed(Key::F4) {
-                    self.handle_save_game();
-                }
-                if i.modifiers.ctrl && i.key_pressed(Key::Z) {
-                    let _ = self.game.handle_event(GameEvent::StartUndo);
-                }
+        // 处理全局快捷键（当菜单可操作且没有动画时；设置快捷键时不响应）
+        // 暂停快捷键在电脑思考与动画中同样可用，暂停期间只响应暂停快捷键
+        if self.showing_game() && self.shortcuts_dialog == ShortcutsDialog::Closed {
+            let can_run = can_interact && !self.has_active_animation();
+            let typing = ctx.wants_keyboard_input();
+            let shortcuts = &self.game.settings.shortcuts;
+            let actions: Vec<ShortcutAction> = ctx.input(|i| {
+                i.events
+                    .iter()
+                    .filter_map(|event| match event {
+                        egui::Event::Key { key, pressed: true, repeat: false, modifiers, .. } => {
+                            // 输入文字时不响应不带 Ctrl/Alt 的字符按键
+                            let printable = key.name().chars().count() == 1;
+                            if typing && printable && !modifiers.ctrl && !modifiers.alt {
+                                return None;
+                            }
+                            shortcuts.action_for(key.name(), modifiers.ctrl, modifiers.shift, modifiers.alt)
+                        }
+                        _ => None,
+                    })
+                    .collect()
             });
+            for action in actions {
+                if can_run || action == ShortcutAction::Pause {
+                    self.run_shortcut(action);
+                }
+            }
+
+            if ctx.input_mut(|i| i.consume_key(egui::Modifiers::CTRL | egui::Modifiers::SHIFT, PIECE_IDS_TOGGLE_KEY)) {
+                self.show_piece_ids = !self.show_piece_ids;
+            }
         }
 
         TopBottomPanel::top("menu_bar").show(ctx, |ui| {
@@ -187,24 +674,92 @@ impl MainApp {
                 ui.menu_button(t!("menu.game"), |ui| {
                         let can_click = can_interact && !self.has_active_animation();
                         
-                        if ui.add_enabled(can_click, egui::Button::new(t!("menu.new_game"))).clicked() {
-                            self.new_game_dialog = NewGameDialog::Open { ai_level: self.game.ai_level };
+                        if ui.add_enabled(can_click, egui::Button::new(t!("menu.new_game")).shortcut_text(self.game.settings.shortcuts.new_game.label())).clicked() {
+                            self.request_new_game();
                             ui.close_menu();
                         }
-                        if ui.add_enabled(can_click, egui::Button::new(t!("menu.load_game"))).clicked() {
+                        if ui.add_enabled(can_click, egui::Button::new(t!("menu.load_game")).shortcut_text(self.game.settings.shortcuts.load_game.label())).clicked() {
                             self.handle_load_game();
                             ui.close_menu();
                         }
-                        if ui.add_enabled(can_click, egui::Button::new(t!("menu.save_game"))).clicked() {
+                        if ui.add_enabled(can_click, egui::Button::new(t!("menu.save_game")).shortcut_text(self.game.settings.shortcuts.save_game.label())).clicked() {
                             self.handle_save_game();
                             ui.close_menu();
                         }
+                        if ui.add_enabled(can_click, egui::Button::new(t!("menu.save_slots"))).clicked() {
+                            let can_save = !self.game.is_at_start();
+                            self.save_slots_dialog = SaveSlotsDialog::open(SlotIndex::load(), can_save);
+                            ui.close_menu();
+                        }
+                        if ui.add_enabled(can_click, egui::Button::new(t!("menu.export_image"))).clicked() {
+                            self.handle_export_image();
+                            ui.close_menu();
+                        }
+                        if ui.button(t!("menu.copy_position"))
+                            .on_hover_text(t!("menu.copy_position_hint"))
+                            .clicked()
+                        {
+                            ui.ctx().copy_text(position_to_string(&self.game.board, self.game.current_turn));
+                            ui.close_menu();
+                        }
+                        if ui.button(t!("menu.copy_game"))
+                            .on_hover_text(t!("menu.copy_game_hint"))
+                            .clicked()
+                        {
+                            self.copy_game(ui.ctx());
+                            ui.close_menu();
+                        }
+                        if ui.add_enabled(can_click, egui::Button::new(t!("menu.paste_position"))).clicked() {
+                            self.paste_text = Some(String::new());
+                            ui.close_menu();
+                        }
                         ui.separator();
                         
                         // 悔棋按钮
-                        let can_undo = self.game.can_undo() && can_click;
-                        if ui.add_enabled(can_undo, egui::Button::new(t!("menu.undo"))).clicked() {
-                            let _ = self.game.handle_event(GameEvent::StartUndo);
+                        let undo_status = self.undo_status();
+                        let can_undo = undo_status.is_available() && can_click;
+                        let undo_button = egui::Button::new(t!("menu.undo")).shortcut_text(self.game.settings.shortcuts.undo.label());
+                        let undo_response = ui.add_enabled(can_undo, undo_button);
+                        let undo_response = match undo_status.reason_text() {
+                            Some(reason) => undo_response.on_disabled_hover_text(reason),
+                            None => undo_response,
+                        };
+                        if undo_response.clicked() {
+                            self.send_event(GameEvent::StartUndo);
+                            ui.close_menu();
+                        }
+
+                        // 暂停/继续（电脑思考与动画中同样可用）
+                        let pause_text = if self.is_paused() { t!("menu.resume") } else { t!("menu.pause") };
+                        let pause_button = egui::Button::new(pause_text).shortcut_text(self.game.settings.shortcuts.pause.label());
+                        if ui.add_enabled(self.is_paused() || self.can_pause(), pause_button).clicked() {
+                            self.toggle_pause();
+                            ui.close_menu();
+                        }
+
+                        // 回放按钮
+                        let can_replay = can_click && !self.game.move_history.is_empty();
+                        if ui.add_enabled(can_replay, egui::Button::new(t!("menu.replay"))).clicked() {
+                            self.start_replay();
+                            ui.close_menu();
+                        }
+
+                        // 演示模式：进行中随时可退出
+                        if self.game.demo {
+                            if ui.button(t!("menu.exit_demo")).clicked() {
+                                self.exit_demo();
+                                ui.close_menu();
+                            }
+                        } else if ui.add_enabled(can_click, egui::Button::new(t!("menu.demo"))).clicked() {
+                            self.request_demo();
+                            ui.close_menu();
+                        }
+                        if ui.add_enabled(can_click, egui::Button::new(t!("menu.edit_position"))).clicked() {
+                            self.editor = Some(PositionEditor::new(
+                                &self.game.board,
+                                self.game.player_side,
+                                self.game.current_turn,
+                            ));
                             ui.close_menu();
                         }
                         ui.separator();
@@ -217,12 +772,135 @@ impl MainApp {
 
                 // 语言菜单 (支持 ALT+L)
                 ui.menu_button(t!("menu.language"), |ui| {
-                        if ui.button(t!("menu.lang_zh")).clicked() {
-                            self.switch_language("zh-CN");
-                            ui.close_menu();
+                        self.show_language_menu(ui);
+                });
+
+                // 设置菜单
+                ui.menu_button(t!("menu.settings"), |ui| {
+                        ui.menu_button(t!("menu.theme"), |ui| {
+                            for theme in Theme::ALL {
+                                let selected = self.game.settings.theme == theme;
+                                if ui.radio(selected, theme.display_text()).clicked() {
+                                    self.game.settings.theme = theme;
+                                    self.save_settings();
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                        if ui.checkbox(&mut self.game.settings.show_coordinates, t!("menu.show_coordinates")).changed() {
+                            self.save_settings();
+                        }
+                        if ui.checkbox(&mut self.game.settings.flip_board_for_white, t!("menu.flip_board_for_white")).changed() {
+                            self.save_settings();
+                        }
+                        let show_all_moves_text = format!(
+                            "{} ({})",
+                            t!("menu.show_all_moves"),
+                            self.game.settings.shortcuts.toggle_all_moves.label()
+                        );
+                        if ui.checkbox(&mut self.game.settings.show_all_moves, show_all_moves_text).changed() {
+                            self.save_settings();
+                        }
+                        if ui.checkbox(&mut self.game.settings.confirm_moves, t!("menu.confirm_moves"))
+                            .on_hover_text(t!("menu.confirm_moves_hint"))
+                            .changed()
+                        {
+                            self.save_settings();
+                        }
+                        if ui.checkbox(&mut self.game.settings.beginner_hints, t!("menu.beginner_hints"))
+                            .on_hover_text(t!("menu.beginner_hints_hint"))
+                            .changed()
+                        {
+                            self.save_settings();
+                        }
+                        if ui.checkbox(&mut self.game.settings.show_clocks, t!("menu.show_clocks")).changed() {
+                            self.save_settings();
+                        }
+                        if ui.checkbox(&mut self.game.settings.show_evaluation, t!("menu.show_evaluation"))
+                            .on_hover_text(t!("menu.show_evaluation_hint"))
+                            .changed()
+                        {
+                            self.save_settings();
+                        }
+                        ui.menu_button(t!("menu.ai_style"), |ui| {
+                            for style in AiStyle::ALL {
+                                let selected = self.game.settings.ai_style == style;
+                                if ui.radio(selected, style.display_text()).clicked() {
+                                    self.game.settings.ai_style = style;
+                                    self.save_settings();
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                        if ui.checkbox(&mut self.game.settings.opening_variety, t!("menu.opening_variety"))
+                            .on_hover_text(t!("menu.opening_variety_hint"))
+                            .changed()
+                        {
+                            self.save_settings();
+                        }
+                        ui.separator();
+                        if ui.checkbox(&mut self.game.settings.small_material_draw, t!("menu.small_material_draw")).changed() {
+                            self.save_settings();
                         }
-                        if ui.button(t!("menu.lang_en")).clicked() {
-                            self.switch_language("en");
+                        if ui.checkbox(&mut self.game.settings.repetition_draw, t!("menu.repetition_draw")).changed() {
+                            self.save_settings();
+                        }
+                        let mut no_capture_draw = self.game.settings.no_capture_draw_limit.is_some();
+                        if ui.checkbox(&mut no_capture_draw, t!("menu.no_capture_draw")).changed() {
+                            self.game.settings.no_capture_draw_limit =
+                                no_capture_draw.then_some(DEFAULT_NO_CAPTURE_DRAW_LIMIT);
+                            self.save_settings();
+                        }
+                        if let Some(limit) = self.game.settings.no_capture_draw_limit.as_mut() {
+                            let limit_slider = egui::Slider::new(limit, MIN_NO_CAPTURE_DRAW_LIMIT..=MAX_NO_CAPTURE_DRAW_LIMIT)
+                                .text(t!("menu.no_capture_draw_limit"));
+                            if ui.add(limit_slider).changed() {
+                                self.save_settings();
+                            }
+                        }
+                        ui.separator();
+                        let speed_slider = egui::Slider::new(
+                            &mut self.game.settings.animation_speed,
+                            MIN_ANIMATION_SPEED..=MAX_ANIMATION_SPEED,
+                        )
+                        .text(t!("menu.animation_speed"))
+                        .suffix("×");
+                        let speed_changed = ui
+                            .add_enabled(!self.game.settings.instant_animations, speed_slider)
+                            .changed();
+                        if speed_changed {
+                            self.save_settings();
+                        }
+                        if ui.checkbox(&mut self.game.settings.instant_animations, t!("menu.instant_animations")).changed() {
+                            self.save_settings();
+                        }
+                        let tolerance_slider = egui::Slider::new(
+                            &mut self.game.settings.drop_tolerance,
+                            MIN_DROP_TOLERANCE..=MAX_DROP_TOLERANCE,
+                        )
+                        .text(t!("menu.drop_tolerance"))
+                        .custom_formatter(|value, _| format!("{:.0}%", value * 100.0));
+                        if ui.add(tolerance_slider).on_hover_text(t!("menu.drop_tolerance_hint")).changed() {
+                            self.save_settings();
+                        }
+                        if ui.checkbox(&mut self.game.settings.reduce_motion, t!("menu.reduce_motion"))
+                            .on_hover_text(t!("menu.reduce_motion_hint"))
+                            .changed()
+                        {
+                            self.save_settings();
+                        }
+                        if ui.checkbox(&mut self.game.settings.reduce_flashing, t!("menu.reduce_flashing"))
+                            .on_hover_text(t!("menu.reduce_flashing_hint"))
+                            .changed()
+                        {
+                            self.save_settings();
+                        }
+                        ui.separator();
+                        ui.menu_button(t!("menu.sound"), |ui| {
+                            self.show_sound_settings(ui);
+                        });
+                        if ui.button(t!("menu.shortcuts")).clicked() {
+                            self.shortcuts_dialog = ShortcutsDialog::Open { capturing: None };
                             ui.close_menu();
                         }
                 });
@@ -244,66 +922,103 @@ impl MainApp {
 
     /// 处理快捷工具栏
     fn handle_toolbar(&mut self, ctx: &Context) {
-        let can_interact = self.game.state.can_interact_with_ui();
+        let can_interact = self.game.state.can_interact_with_ui() && self.showing_game() && !self.is_paused();
 
         TopBottomPanel::top("toolbar").show(ctx, |ui| {
             ui.add_space(4.0);
-            ui.horizontal(|ui| {
+            // 窗口较窄时按钮自动换行
+            ui.horizontal_wrapped(|ui| {
                 let button_size = egui::vec2(72.0, 32.0);
                 let can_click = can_interact && !self.has_active_animation();
 
                 // 新局按钮
-                let new_game_text = if self.language == "zh-CN" { "🎮 新局" } else { "🎮 New" };
-                if ui.add_enabled(can_click, egui::Button::new(new_game_text).min_size(button_size)).clicked() {
-                    self.new_game_dialog = NewGameDialog::Open { ai_level: self.game.ai_level };
+                let new_game_text = format!("🎮 {}", t!("toolbar.new_game"));
+                if ui.add_enabled(can_click, egui::Button::new(new_game_text).min_size(button_size)).on_hover_text(shortcut_tooltip(t!("tooltip.new_game"), &self.game.settings.shortcuts.new_game)).clicked() {
+                    self.request_new_game();
                 }
 
                 // 保存按钮
-                let is_initial = is_initial_position(&self.game.board);
+                let is_initial = self.game.is_at_start();
                 let can_save = !is_initial && can_click;
-                let save_text = if self.language == "zh-CN" { "💾 保存" } else { "💾 Save" };
-                if ui.add_enabled(can_save, egui::Button::new(save_text).min_size(button_size)).clicked() {
+                let save_text = format!("💾 {}", t!("toolbar.save"));
+                if ui.add_enabled(can_save, egui::Button::new(save_text).min_size(button_size)).on_hover_text(shortcut_tooltip(t!("tooltip.save"), &self.game.settings.shortcuts.save_game)).clicked() {
                     self.handle_save_game();
                 }
 
                 // 加载按钮
-                let load_text = if self.language == "zh-CN" { "📂 加载" } else { "📂 Load" };
-                if ui.add_enabled(can_click, egui::Button::new(load_text).min_size(button_size)).clicked() {
+                let load_text = format!("📂 {}", t!("toolbar.load"));
+                if ui.add_enabled(can_click, egui::Button::new(load_text).min_size(button_size)).on_hover_text(shortcut_tooltip(t!("tooltip.load"), &self.game.settings.shortcuts.load_game)).clicked() {
                     self.handle_load_game();
                 }
 
                 ui.separator();
 
                 // 悔棋按钮
-                let can_undo = self.game.can_undo() && can_click;
-                let undo_text = if self.language == "zh-CN" { "↩️ 悔棋" } else { "↩️ Undo" };
-                if ui.add_enabled(can_undo, egui::Button::new(undo_text).min_size(button_size)).clicked() {
-                    let _ = self.game.handle_event(GameEvent::StartUndo);
+                let undo_status = self.undo_status();
+                let can_undo = undo_status.is_available() && can_click;
+                let undo_text = format!("↩️ {}", t!("toolbar.undo"));
+                let undo_response = ui
+                    .add_enabled(can_undo, egui::Button::new(undo_text).min_size(button_size))
+                    .on_hover_text(shortcut_tooltip(t!("tooltip.undo"), &self.game.settings.shortcuts.undo));
+                let undo_response = match undo_status.reason_text() {
+                    Some(reason) => undo_response.on_disabled_hover_text(reason),
+                    None => undo_response,
+                };
+                if undo_response.clicked() {
+                    self.send_event(GameEvent::StartUndo);
+                }
+
+                // 认输按钮
+                let can_resign = self.game.state == GameState::WaitingForPlayer && can_click;
+                let resign_text = format!("🏳 {}", t!("toolbar.resign"));
+                if ui.add_enabled(can_resign, egui::Button::new(resign_text).min_size(button_size)).on_hover_text(t!("tooltip.resign")).clicked() {
+                    self.confirm_resign = true;
+                }
+
+                // 提和按钮
+                let draw_text = format!("🤝 {}", t!("toolbar.offer_draw"));
+                if ui.add_enabled(can_resign, egui::Button::new(draw_text).min_size(button_size)).on_hover_text(t!("tooltip.offer_draw")).clicked() {
+                    self.offer_draw();
                 }
 
                 ui.separator();
 
-                // 语言切换按钮
-                let lang_text = if self.language == "zh-CN" { "🌐 EN" } else { "🌐 中文" };
-                if ui.add_sized(button_size, egui::Button::new(lang_text)).clicked() {
-                    if self.language == "zh-CN" {
-                        self.switch_language("en");
-                    } else {
-                        self.switch_language("zh-CN");
-                    }
+                // 下一局难度：只改设置，当前对局不受影响
+                let mut next_level = self.game.settings.default_ai_level;
+                egui::ComboBox::from_id_salt("next_ai_level")
+                    .selected_text(format!("🤖 {}", level_name(next_level)))
+                    .show_ui(ui, |ui| {
+                        for level in 1..=5 {
+                            ui.selectable_value(&mut next_level, level, level_name(level));
+                        }
+                    })
+                    .response
+                    .on_hover_text(t!("tooltip.next_ai_level"));
+                if next_level != self.game.settings.default_ai_level {
+                    self.game.settings.default_ai_level = next_level;
+                    self.save_settings();
                 }
 
                 ui.separator();
 
+                // 语言切换按钮（弹出全部可用语言）
+                ui.menu_button(format!("🌐 {}", t!("toolbar.language")), |ui| {
+                    self.show_language_menu(ui);
+                })
+                .response
+                .on_hover_text(t!("tooltip.language"));
+
+                ui.separator();
+
                 // 规则按钮
-                let rules_text = if self.language == "zh-CN" { "📖 规则" } else { "📖 Rules" };
-                if ui.add_sized(button_size, egui::Button::new(rules_text)).clicked() {
+                let rules_text = format!("📖 {}", t!("toolbar.rules"));
+                if ui.add_sized(button_size, egui::Button::new(rules_text)).on_hover_text(t!("tooltip.rules")).clicked() {
                     self.rules_dialog = RulesDialog::Open;
                 }
 
                 // 关于按钮
-                let about_text = if self.language == "zh-CN" { "ℹ️ 关于" } else { "ℹ️ About" };
-                if ui.add_sized(button_size, egui::Button::new(about_text)).clicked() {
+                let about_text = format!("ℹ️ {}", t!("toolbar.about"));
+                if ui.add_sized(button_size, egui::Button::new(about_text)).on_hover_text(t!("tooltip.about")).clicked() {
                     self.about_dialog = AboutDialog::Open;
                 }
             });
@@ -311,79 +1026,527 @@ impl MainApp {
         });
     }
 
-    /// 处理新局对话框
-    fn handle_new_game_dialog(&mut self, ctx: &Context) {
-        if let Some(NewGameResult { player_first, ai_level }) = self.new_game_dialog.show(ctx) {
-            let _ = self.game.handle_event(GameEvent::StartNewGame { player_first, ai_level });
-            self.animations = AnimationController::default();
-            self.ai_think_start = None;
+    /// 显示底部状态栏（当前回合、双方剩余棋子数、游戏状态）
+    fn handle_status_bar(&mut self, ctx: &Context) {
+        // 回放模式下底部显示回放控制条，局面编辑模式下不显示
+        if !self.showing_game() {
+            return;
         }
-    }
 
-    /// 处理游戏结束对话框
-    fn handle_game_over_dialog(&mut self, ctx: &Context) {
-        if let Some(action) = self.game_over_dialog.show(ctx) {
-            match action {
-                GameOverAction::Undo => {
-                    let _ = self.game.handle_event(GameEvent::DialogAction(DialogAction::Undo));
-                    self.game_over_dialog = GameOverDialog::Closed;
+        let turn_text = self.game.turn_text();
+
+        let evaluation = self.current_evaluation();
+
+        TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(turn_text);
+                ui.separator();
+                let summary = self.game.board.summary();
+                for side in [Side::Black, Side::White] {
+                    let name = match side {
+                        Side::Black => t!("game.black"),
+                        Side::White => t!("game.white"),
+                    };
+                    ui.label(format!("{}: {}", name, summary.count(side)));
                 }
-                GameOverAction::NewGame => {
-                    self.new_game_dialog = NewGameDialog::Open { ai_level: self.game.ai_level };
+                ui.separator();
+                if self.is_paused() {
+                    ui.strong(t!("status.paused"));
+                } else {
+                    ui.label(self.game.state.status_text());
                 }
-                GameOverAction::BackToMenu => {
-                    let _ = self.game.handle_event(GameEvent::DialogAction(DialogAction::Confirm));
-                    self.game_over_dialog = GameOverDialog::Closed;
+                if let Some(ref anim) = self.thinking_indicator {
+                    Self::draw_thinking_spinner(ui, anim.value());
                 }
-            }
-        }
+                if self.clocks_visible() {
+                    ui.separator();
+                    self.show_clocks(ui);
+                }
+                if let Some(score) = evaluation {
+                    ui.separator();
+                    ui.label(t!("status.evaluation", score = format!("{:+}", score)));
+                    Self::draw_evaluation_bar(ui, evaluation_bar(score));
+                }
+                if self.can_skip_ai_animation() {
+                    ui.separator();
+                    if ui.button(t!("status.skip_animation")).on_hover_text(t!("status.skip_animation_hint")).clicked() {
+                        self.skip_ai_animations();
+                    }
+                }
+                if self.game.demo {
+                    ui.separator();
+                    if ui.checkbox(&mut self.demo_stepper.enabled, t!("demo.step_mode"))
+                        .on_hover_text(t!("demo.step_mode_hint"))
+                        .changed()
+                    {
+                        self.demo_stepper.clear();
+                    }
+                    if self.demo_stepper.enabled {
+                        let can_step = self.game.state == GameState::AiThinking && !self.demo_stepper.requested;
+                        if ui.add_enabled(can_step, egui::Button::new(t!("demo.step"))).clicked() {
+                            self.demo_stepper.request();
+                        }
+                    }
+                    if ui.button(t!("demo.exit")).clicked() {
+                        self.exit_demo();
+                    }
+                }
+            });
+        });
     }
 
-    /// 处理保存游戏
-    fn handle_save_game(&mut self) {
-        if is_initial_position(&self.game.board) {
-            return;
-        }
-
-        let dialog = rfd::FileDialog::new()
-            .add_filter(&t!("dialog.file_filter"), &["6zc"]);
+    /// 是否在状态栏显示计时：手动开启或处于限时模式
+    fn clocks_visible(&self) -> bool {
+        self.game.settings.show_clocks || self.game.clock.time_control().is_some()
+    }
 
-        if let Some(path) = dialog.save_file() {
-            if path.exists() {
-                self.pending_save_file = Some(path);
-                self.confirm_overwrite = true;
+    /// 状态栏中的双方用时（限时模式下为剩余用时），正在计时的一方加粗显示
+    fn show_clocks(&self, ui: &mut egui::Ui) {
+        ui.label("⏱");
+        for side in [Side::Black, Side::White] {
+            let name = match side {
+                Side::Black => t!("game.black"),
+                Side::White => t!("game.white"),
+            };
+            let time = self.game.clock.remaining(side).unwrap_or_else(|| self.game.clock.elapsed(side));
+            let text = RichText::new(format!("{} {}", name, format_clock(time))).monospace();
+            if self.game.clock.running() == Some(side) {
+                ui.label(text.strong());
             } else {
-                self.do_save_game(&path);
+                ui.label(text);
             }
         }
     }
 
-    /// 执行保存游戏
-    fn do_save_game(&mut self, path: &std::path::Path) {
-        match save_game(&self.game.board, self.game.player_side, path) {
-            Ok(()) => {}
-            Err(e) => {
-                eprintln!("保存游戏失败: {}", e);
+    /// 更新AI思考指示器：思考超过延迟后出现并循环旋转，离开思考状态立即移除
+    fn update_thinking_indicator(&mut self) {
+        let thinking_long_enough = self.game.state == GameState::AiThinking
+            && self.ai_think_start
+                .is_some_and(|start| start.elapsed() >= Duration::from_millis(AI_INDICATOR_DELAY_MS));
+
+        if !thinking_long_enough {
+            self.thinking_indicator = None;
+        } else if self.thinking_indicator.as_ref().is_none_or(|anim| anim.is_finished()) {
+            self.thinking_indicator = Some(
+                Animation::new(AI_INDICATOR_SPIN_MS, 0.0, std::f32::consts::TAU).with_easing(Easing::Linear),
+            );
+        }
+    }
+
+    /// 玩家视角的局面评估分（需在设置中开启，仅在轮到玩家时更新）
+    fn current_evaluation(&mut self) -> Option<i32> {
+        if !self.game.settings.show_evaluation || self.game.demo {
+            return None;
+        }
+        if self.game.state == GameState::WaitingForPlayer {
+            let notation = self.game.board.to_notation();
+            if self.evaluation.as_ref().is_none_or(|(key, _)| *key != notation) {
+                let score = AiPlayer::new(self.game.ai_level).evaluate(&self.game.board, self.game.player_side);
+                self.evaluation = Some((notation, score));
             }
         }
-        self.pending_save_file = None;
-        self.confirm_overwrite = false;
+        self.evaluation.as_ref().map(|&(_, score)| score)
     }
 
-    /// 处理加载游戏
-    fn handle_load_game(&mut self) {
-        let is_initial = is_initial_position(&self.game.board);
+    /// 绘制评估条：从中间向右（玩家占优）或向左（电脑占优）填充，`bar` 取值 0.0 到 1.0，均势为 0.5
+    fn draw_evaluation_bar(ui: &mut egui::Ui, bar: f32) {
+        let height = ui.text_style_height(&egui::TextStyle::Body) * 0.6;
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(80.0, height), egui::Sense::hover());
+        let painter = ui.painter();
+        painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+        let center = rect.center().x;
+        let end = rect.left() + bar * rect.width();
+        let color = if bar >= 0.5 {
+            egui::Color32::from_rgb(60, 170, 80)
+        } else {
+            egui::Color32::from_rgb(200, 60, 60)
+        };
+        let fill = egui::Rect::from_x_y_ranges(center.min(end)..=center.max(end), rect.y_range());
+        painter.rect_filled(fill, 2.0, color);
+        painter.vline(center, rect.y_range(), egui::Stroke::new(1.0, ui.visuals().text_color()));
+    }
+
+    /// 绘制旋转的圆弧（AI思考指示器），`angle` 为弧的起始角度
+    fn draw_thinking_spinner(ui: &mut egui::Ui, angle: f32) {
+        let size = ui.text_style_height(&egui::TextStyle::Body);
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(size, size), egui::Sense::hover());
+        let radius = size * 0.4;
+        let points: Vec<egui::Pos2> = (0..=24)
+            .map(|i| {
+                let a = angle + i as f32 / 24.0 * std::f32::consts::TAU * 0.75;
+                rect.center() + egui::vec2(a.cos(), a.sin()) * radius
+            })
+            .collect();
+        let stroke = egui::Stroke::new(2.0, ui.visuals().text_color());
+        ui.painter().add(egui::Shape::line(points, stroke));
+    }
+
+    /// 显示吃子盘（双方被吃棋子数量）
+    fn handle_captured_tray(&self, ctx: &Context) {
+        if self.editor.is_some() {
+            return;
+        }
+        let Some(ref view) = self.board_view else {
+            return;
+        };
+
+        // 吃子动画结束前，被吃棋子仍视为在棋盘上
+        let animating: Vec<u8> = self.animations.capture.as_ref()
+            .map(|anim| anim.piece_ids.clone())
+            .unwrap_or_default();
+        let board = match self.replay {
+            Some(ref r) => r.replay.board(),
+            None => &self.game.board,
+        };
+        let captured_of = |side: Side| {
+            let pending = animating.iter()
+                .filter(|&&id| board.piece_by_id(id).is_some_and(|p| p.side == side))
+                .count();
+            pieces_per_side(board.size).saturating_sub(board.count_active(side) + pending)
+        };
+
+        SidePanel::right("captured_tray")
+            .resizable(false)
+            .exact_width(120.0)
+            .show(ctx, |ui| {
+                ui.add_space(10.0);
+                ui.vertical_centered(|ui| {
+                    ui.heading(t!("game.captured"));
+                });
+                ui.add_space(10.0);
+
+                for side in [Side::Black, Side::White] {
+                    let count = captured_of(side);
+                    let name = match side {
+                        Side::Black => t!("game.black"),
+                        Side::White => t!("game.white"),
+                    };
+                    ui.label(format!("{}: {}", name, count));
+                    ui.horizontal_wrapped(|ui| {
+                        ui.spacing_mut().item_spacing = egui::vec2(2.0, 2.0);
+                        for _ in 0..count {
+                            view.draw_stone_icon(ui, side, 30.0);
+                        }
+                    });
+                    ui.add_space(10.0);
+                }
+            });
+    }
+
+    /// 处理新局对话框
+    fn handle_new_game_dialog(&mut self, ctx: &Context) {
+        if let Some(NewGameResult { player_first, ai_level, adaptive, time_control }) = self.new_game_dialog.show(ctx) {
+            self.adaptive_level = adaptive;
+            if self.game.settings.time_control != time_control || self.game.settings.default_ai_level != ai_level {
+                self.game.settings.time_control = time_control;
+                self.game.settings.default_ai_level = ai_level;
+                self.save_settings();
+            }
+            let ai_level = self.next_ai_level(ai_level);
+            self.send_event(GameEvent::StartNewGame { player_first, ai_level });
+            // 开始新局即放弃上一局，不再提供恢复
+            remove_autosave();
+            self.reset_for_new_game();
+        }
+    }
+
+    /// 新局（含演示）开始时重置音效与动画
+    fn reset_for_new_game(&mut self) {
+        self.sound.stop_all();
+        self.animations = AnimationController::default();
+        self.ai_think_start = None;
+        self.keyboard_cursor = None;
+        self.evaluation = None;
+        self.restored_game = false;
+        self.single_piece_mode.reset(self.game.board.is_single_piece_mode());
+        // 新局开始时若背景音乐已停止则重新播放
+        if self.game.settings.music_enabled && !self.sound.is_music_playing() {
+            self.sound.start_music();
+        }
+    }
+
+    /// 请求开始演示：对局进行中时先确认是否放弃当前对局
+    fn request_demo(&mut self) {
+        if self.game_in_progress() {
+            self.pending_demo = true;
+            self.confirm_new_game = true;
+        } else {
+            self.demo_dialog = DemoDialog::Open(self.demo_setup);
+        }
+    }
+
+    /// 处理演示设置对话框
+    fn handle_demo_dialog(&mut self, ctx: &Context) {
+        if let Some(setup) = self.demo_dialog.show(ctx) {
+            self.demo_setup = setup;
+            self.start_demo();
+        }
+    }
+
+    /// 开始演示：双方均由电脑行棋，不接受玩家输入
+    fn start_demo(&mut self) {
+        remove_autosave();
+        self.game.start_demo();
+        self.demo_stepper.clear();
+        self.game_over_dialog = GameOverDialog::Closed;
+        self.demo_over_dialog = DemoOverDialog::Closed;
+        self.reset_for_new_game();
+    }
+
+    /// 退出演示，以玩家执黑开始一局正常对局
+    fn exit_demo(&mut self) {
+        self.send_event(GameEvent::StartNewGame { player_first: true, ai_level: self.game.settings.default_ai_level });
+        self.demo_over_dialog = DemoOverDialog::Closed;
+        self.reset_for_new_game();
+    }
+
+    /// 处理演示结束对话框（演示已被新局或加载结束时直接关闭）
+    fn handle_demo_over_dialog(&mut self, ctx: &Context) {
+        if !self.game.demo {
+            self.demo_over_dialog = DemoOverDialog::Closed;
+            return;
+        }
+        match self.demo_over_dialog.show(ctx) {
+            Some(DemoOverAction::Restart) => self.start_demo(),
+            Some(DemoOverAction::Exit) => self.exit_demo(),
+            None => {}
+        }
+    }
+
+    /// 打开新局对话框
+    fn open_new_game_dialog(&mut self) {
+        self.new_game_dialog = NewGameDialog::Open {
+            ai_level: self.game.settings.default_ai_level,
+            adaptive: self.adaptive_level,
+            time_control: self.game.settings.time_control,
+        };
+    }
+
+    /// 开始新局前确定AI等级：记录上一局结果，自适应模式下据此调整等级
+    ///
+    /// 只在两局之间调用，对局中途不会改变难度；调整后的等级同步为下一局的默认等级。
+    /// 只记录玩家亲自与电脑下完的对局，演示和加载恢复的对局不计入
+    fn next_ai_level(&mut self, requested: u8) -> u8 {
+        if let Some(result) = self.game.last_result
+            && !self.game.demo
+            && !self.restored_game
+        {
+            self.recent_results.push(result);
+        }
+        self.restored_game = false;
+        if !self.adaptive_level {
+            return requested;
+        }
+        let level = adapt_level(requested, &self.recent_results);
+        if level != requested {
+            self.recent_results.clear();
+        }
+        if self.game.settings.default_ai_level != level {
+            self.game.settings.default_ai_level = level;
+            self.save_settings();
+        }
+        level
+    }
+
+    /// 是否有进行中的对局（演示不算在内）
+    fn game_in_progress(&self) -> bool {
+        !self.game.demo && !self.game.is_at_start() && self.game.last_result.is_none()
+    }
+
+    /// 请求开始新局：对局进行中时先确认是否放弃当前对局
+    fn request_new_game(&mut self) {
+        if self.game_in_progress() {
+            self.confirm_new_game = true;
+        } else {
+            self.open_new_game_dialog();
+        }
+    }
+
+    /// 显示确认放弃当前对局对话框
+    fn show_confirm_new_game_dialog(&mut self, ctx: &Context) {
+        let mut should_open = false;
+        let mut should_cancel = ctx.input(|i| i.key_pressed(Key::Escape));
+
+        egui::Window::new(t!("dialog.confirm_new_game"))
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(t!("dialog.confirm_new_game_msg"));
+                ui.horizontal(|ui| {
+                    if ui.button(t!("dialog.yes")).clicked() {
+                        should_open = true;
+                    }
+                    if ui.button(t!("dialog.no")).clicked() {
+                        should_cancel = true;
+                    }
+                });
+            });
+
+        if should_open {
+            self.confirm_new_game = false;
+            if std::mem::take(&mut self.pending_demo) {
+                self.demo_dialog = DemoDialog::Open(self.demo_setup);
+            } else {
+                self.open_new_game_dialog();
+            }
+        } else if should_cancel {
+            self.confirm_new_game = false;
+            self.pending_demo = false;
+        }
+    }
+
+    /// 处理游戏结束对话框
+    fn handle_game_over_dialog(&mut self, ctx: &Context) {
+        // 超时判负后悔棋没有意义（时间已用完），不提供悔棋
+        let can_undo = self.game.clock.flagged().is_none();
+        if let Some(action) = self.game_over_dialog.show(ctx, can_undo) {
+            match action {
+                GameOverAction::Undo => {
+                    self.send_event(GameEvent::DialogAction(DialogAction::Undo));
+                    self.game_over_dialog = GameOverDialog::Closed;
+                }
+                GameOverAction::NewGame => {
+                    self.open_new_game_dialog();
+                }
+                GameOverAction::SwapSides => {
+                    self.game.ai_level = self.next_ai_level(self.game.settings.default_ai_level);
+                    self.send_event(GameEvent::DialogAction(DialogAction::SwapSides));
+                    self.reset_for_new_game();
+                    self.game_over_dialog = GameOverDialog::Closed;
+                }
+                GameOverAction::ReviewBlunder => self.start_blunder_review(),
+                GameOverAction::BackToMenu => {
+                    self.game.ai_level = self.next_ai_level(self.game.settings.default_ai_level);
+                    self.send_event(GameEvent::DialogAction(DialogAction::Confirm));
+                    self.sound.stop_all();
+                    self.game_over_dialog = GameOverDialog::Closed;
+                }
+            }
+        }
+    }
+
+    /// 处理保存游戏
+    fn handle_save_game(&mut self) {
+        if self.game.is_at_start() {
+            return;
+        }
+
+        // 默认保存为JSON格式，选择 .6zb 时保存为二进制格式
+        let dialog = rfd::FileDialog::new()
+            .add_filter(t!("dialog.json_file_filter"), &["6zc"])
+            .add_filter(t!("dialog.binary_file_filter"), &[BINARY_SAVE_EXTENSION]);
+
+        if let Some(path) = dialog.save_file() {
+            if path.exists() {
+                self.pending_save_file = Some(path);
+                self.confirm_overwrite = true;
+            } else {
+                self.do_save_game(&path);
+            }
+        }
+    }
+
+    /// 执行保存游戏
+    fn do_save_game(&mut self, path: &std::path::Path) {
+        // 文件名作为存档标签
+        let label = path.file_stem().map(|stem| stem.to_string_lossy()).unwrap_or_default();
+        match save_game(
+            &self.game.board,
+            self.game.player_side,
+            self.game.current_turn,
+            &self.game.move_history,
+            Some(&self.game.save_metadata(&label)),
+            path,
+        ) {
+            Ok(()) => {}
+            Err(e) => {
+                crate::log_error!("保存游戏失败 {}: {:#}", path.display(), e);
+                self.pending_error = Some(t!("dialog.save_failed_msg", reason = format!("{:#}", e)));
+            }
+        }
+        self.pending_save_file = None;
+        self.confirm_overwrite = false;
+    }
+
+    /// 处理存档管理对话框
+    fn handle_save_slots_dialog(&mut self, ctx: &Context) {
+        match self.save_slots_dialog.show(ctx) {
+            Some(SlotAction::Save { slot, name }) => {
+                match save_to_slot(
+                    slot,
+                    &self.game.board,
+                    self.game.player_side,
+                    self.game.current_turn,
+                    &self.game.move_history,
+                    &self.game.save_metadata(&name),
+                ) {
+                    Ok(index) => self.save_slots_dialog = SaveSlotsDialog::open(index, true),
+                    Err(e) => {
+                        self.pending_error = Some(t!("dialog.save_failed_msg", reason = format!("{:#}", e)));
+                    }
+                }
+            }
+            Some(SlotAction::Load(slot)) => {
+                // 与从文件加载相同：非初始局面时先确认
+                if let Some(path) = slot_path(slot) {
+                    if self.game.is_at_start() {
+                        self.do_load_game(&path);
+                    } else {
+                        self.pending_load_file = Some(path);
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// 处理导出局面图片
+    fn handle_export_image(&mut self) {
+        let dialog = rfd::FileDialog::new()
+            .add_filter(t!("dialog.image_filter"), &["png"])
+            .set_file_name("six-rush.png");
+
+        if let Some(path) = dialog.save_file() {
+            let flip = self.game.settings.flip_board_for_white && self.game.player_side == Side::White;
+            let image = render_board_image(&self.game.board, flip, self.game.settings.theme);
+            if let Err(e) = image.save_with_format(&path, image::ImageFormat::Png) {
+                self.pending_error = Some(t!("dialog.export_failed_msg", reason = e));
+            }
+        }
+    }
+
+    /// 写入自动存档（失败时仅输出错误）
+    ///
+    /// 恢复对话框尚未答复时不写入，以免覆盖待恢复的棋局；演示对局不写入
+    fn write_autosave(&self) {
+        if self.pending_resume.is_some() || self.game.demo {
+            return;
+        }
+        if let Err(e) = write_autosave(
+            &self.game.board,
+            self.game.player_side,
+            self.game.current_turn,
+            &self.game.move_history,
+            &self.game.save_metadata(""),
+        ) {
+            crate::log_error!("自动存档失败: {:#}", e);
+        }
+    }
+
+    /// 处理加载游戏
+    fn handle_load_game(&mut self) {
+        let is_initial = self.game.is_at_start();
 
         if !is_initial {
             let dialog = rfd::FileDialog::new()
-                .add_filter(&t!("dialog.file_filter"), &["6zc"]);
+                .add_filter(t!("dialog.file_filter"), &["6zc", BINARY_SAVE_EXTENSION]);
 
             if let Some(path) = dialog.pick_file() {
                 self.pending_load_file = Some(path);
             }
         } else {
             let dialog = rfd::FileDialog::new()
-                .add_filter(&t!("dialog.file_filter"), &["6zc"]);
+                .add_filter(t!("dialog.file_filter"), &["6zc", BINARY_SAVE_EXTENSION]);
 
             if let Some(path) = dialog.pick_file() {
                 self.do_load_game(&path);
@@ -394,26 +1557,124 @@ impl MainApp {
     /// 执行加载游戏
     fn do_load_game(&mut self, path: &std::path::Path) {
         match load_game(path) {
-            Ok((board, player_side)) => {
-                self.game.board = board;
-                self.game.player_side = player_side;
-                self.game.current_turn = Side::Black;
-                self.game.state = GameState::WaitingForPlayer;
-                self.game.move_history.clear();
-                self.game.selected_piece = None;
-                self.game.pending_move = None;
-                self.game.last_captured.clear();
-                self.game.last_result = None;
-                self.animations = AnimationController::default();
-                self.ai_think_start = None;
-            }
+            Ok(loaded) => self.apply_loaded_game(loaded),
             Err(e) => {
-                eprintln!("加载游戏失败: {}", e);
+                crate::log_error!("加载游戏失败 {}: {:#}", path.display(), e);
+                self.pending_error = Some(t!("dialog.load_failed_msg", reason = format!("{:#}", e)));
             }
         }
         self.pending_load_file = None;
     }
 
+    /// 以加载的棋局（存档文件或粘贴的文本）替换当前对局
+    fn apply_loaded_game(&mut self, loaded: LoadedGame) {
+        self.game.board = loaded.board;
+        self.game.player_side = loaded.player_side;
+        self.game.current_turn = loaded.current_turn;
+        self.game.move_history = loaded.move_history;
+        if let Some(metadata) = &loaded.metadata {
+            self.game.ai_level = metadata.ai_level.clamp(1, 5);
+        }
+        self.game.selected_piece = None;
+        self.game.pending_move = None;
+        self.game.last_captured.clear();
+        self.game.last_result = None;
+        self.game.end_reason = None;
+        self.game.ended_without_move = false;
+        self.game.demo = false;
+        self.restored_game = true;
+        self.game.started_at = Some(Instant::now());
+        self.game.reset_clock();
+        self.sound.stop_all();
+        self.animations = AnimationController::default();
+        self.ai_think_start = None;
+        self.replay = None;
+        // 加载的局面已是单子状态时不再提示
+        self.single_piece_mode.reset(self.game.board.is_single_piece_mode());
+
+        // 已结束的棋局直接进入结果状态，便于回放；
+        // 棋盘上未分胜负但元数据记有结果的（认输或议和）同样恢复该结果
+        let recorded = loaded.metadata.and_then(|metadata| metadata.result);
+        let ended = match self.game.check_game_end() {
+            Some((result, reason)) => {
+                self.game.end_reason = Some(reason);
+                Some(result)
+            }
+            None => {
+                self.game.ended_without_move = recorded.is_some();
+                recorded
+            }
+        };
+        self.game.state = if let Some(result) = ended {
+            self.game.last_result = Some(result);
+            self.game_over_dialog = GameOverDialog::Open(result, self.game.end_reason, self.game.stats());
+            GameState::GameOverDialog(result)
+        } else if self.game.current_turn == self.game.player_side {
+            GameState::WaitingForPlayer
+        } else {
+            GameState::AiThinking
+        };
+    }
+
+    /// 将完整棋局（JSON）复制到剪贴板
+    fn copy_game(&mut self, ctx: &Context) {
+        match save_to_string(
+            &self.game.board,
+            self.game.player_side,
+            self.game.current_turn,
+            &self.game.move_history,
+            Some(&self.game.save_metadata("")),
+        ) {
+            Ok(text) => ctx.copy_text(text),
+            Err(e) => {
+                self.pending_error = Some(t!("dialog.save_failed_msg", reason = format!("{:#}", e)));
+            }
+        }
+    }
+
+    /// 显示粘贴局面对话框：接受局面记谱或复制的棋局JSON
+    fn show_paste_dialog(&mut self, ctx: &Context) {
+        let Some(text) = self.paste_text.as_mut() else {
+            return;
+        };
+        let mut should_load = false;
+        let mut should_cancel = false;
+
+        egui::Window::new(t!("dialog.paste_position"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(t!("dialog.paste_position_msg"));
+                ui.add(
+                    egui::TextEdit::multiline(text)
+                        .hint_text("WWWW/W..W/B..B/BBBB B")
+                        .desired_rows(6)
+                        .code_editor(),
+                );
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(!text.trim().is_empty(), egui::Button::new(t!("dialog.ok"))).clicked() {
+                        should_load = true;
+                    }
+                    if ui.button(t!("dialog.cancel")).clicked() {
+                        should_cancel = true;
+                    }
+                });
+            });
+
+        if should_load {
+            let text = self.paste_text.take().unwrap_or_default();
+            match load_from_string(&text) {
+                Ok(loaded) => self.apply_loaded_game(loaded),
+                Err(e) => {
+                    self.pending_error = Some(t!("dialog.load_failed_msg", reason = format!("{:#}", e)));
+                }
+            }
+        } else if should_cancel {
+            self.paste_text = None;
+        }
+    }
+
     /// 显示确认加载对话框
     fn show_confirm_load_dialog(&mut self, ctx: &Context) {
         if let Some(ref path) = self.pending_load_file.clone() {
@@ -443,6 +1704,37 @@ impl MainApp {
         }
     }
 
+    /// 显示恢复上一局对话框（启动时存在未完成的自动存档）
+    fn show_resume_dialog(&mut self, ctx: &Context) {
+        if let Some(ref path) = self.pending_resume.clone() {
+            let mut should_resume = false;
+            let mut should_discard = false;
+
+            egui::Window::new(t!("dialog.resume_game"))
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.label(t!("dialog.resume_game_msg"));
+                    ui.horizontal(|ui| {
+                        if ui.button(t!("dialog.yes")).clicked() {
+                            should_resume = true;
+                        }
+                        if ui.button(t!("dialog.no")).clicked() {
+                            should_discard = true;
+                        }
+                    });
+                });
+
+            if should_resume {
+                self.do_load_game(path);
+                self.pending_resume = None;
+            } else if should_discard {
+                remove_autosave();
+                self.pending_resume = None;
+            }
+        }
+    }
+
     /// 显示确认覆盖对话框
     fn show_confirm_overwrite_dialog(&mut self, ctx: &Context) {
         if let Some(ref path) = self.pending_save_file.clone() {
@@ -473,6 +1765,345 @@ impl MainApp {
         }
     }
 
+    /// 显示确认认输对话框
+    fn show_confirm_resign_dialog(&mut self, ctx: &Context) {
+        let mut should_resign = false;
+        let mut should_cancel = false;
+
+        egui::Window::new(t!("dialog.confirm_resign"))
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(t!("dialog.confirm_resign_msg"));
+                ui.horizontal(|ui| {
+                    if ui.button(t!("dialog.yes")).clicked() {
+                        should_resign = true;
+                    }
+                    if ui.button(t!("dialog.no")).clicked() {
+                        should_cancel = true;
+                    }
+                });
+            });
+
+        if should_resign {
+            if let Some(GameState::GameOverDialog(result)) = self.send_event(GameEvent::Resign).map(|o| o.state) {
+                remove_autosave();
+                self.sound.lose();
+                self.game_over_dialog = GameOverDialog::Open(result, self.game.end_reason, self.game.stats());
+            }
+            self.confirm_resign = false;
+        } else if should_cancel {
+            self.confirm_resign = false;
+        }
+    }
+
+    /// 限时模式下一方超时：结束对局并显示结果
+    fn handle_flag_fall(&mut self, result: GameResult) {
+        remove_autosave();
+        self.drag_pos = None;
+        match result {
+            GameResult::PlayerWin => self.sound.win(),
+            GameResult::AiWin => self.sound.lose(),
+            GameResult::Draw => self.sound.draw(),
+        }
+        self.game_over_dialog = GameOverDialog::Open(result, self.game.end_reason, self.game.stats());
+    }
+
+    /// 向电脑提和：接受则以和棋结束，拒绝则提示后继续对局
+    fn offer_draw(&mut self) {
+        if let Some(GameState::GameOverDialog(result)) = self.send_event(GameEvent::OfferDraw).map(|o| o.state) {
+            remove_autosave();
+            self.sound.draw();
+            self.game_over_dialog = GameOverDialog::Open(result, self.game.end_reason, self.game.stats());
+        } else {
+            self.draw_declined = true;
+        }
+    }
+
+    /// 显示电脑拒绝提和的提示框
+    fn show_draw_declined_dialog(&mut self, ctx: &Context) {
+        let mut close = ctx.input(|i| i.key_pressed(Key::Escape) || i.key_pressed(Key::Enter));
+
+        egui::Window::new(t!("dialog.draw_declined"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(t!("dialog.draw_declined_msg"));
+                if ui.button(t!("dialog.ok")).clicked() {
+                    close = true;
+                }
+            });
+
+        if close {
+            self.draw_declined = false;
+        }
+    }
+
+    /// 新手提示：说明为何不能走到 target（None 表示落在棋盘外），关闭提示时不显示
+    fn explain_illegal_move(&mut self, from: (u8, u8), target: Option<(u8, u8)>) {
+        if !self.game.settings.beginner_hints {
+            return;
+        }
+        let reason = match target {
+            Some(to) => is_valid_move_detailed(&self.game.board, from, to, self.game.player_side).err(),
+            None => Some(IllegalMoveReason::OutOfBoard),
+        };
+        if let Some(reason) = reason {
+            self.move_hint = Some((reason.display_text(), Instant::now()));
+        }
+    }
+
+    /// 任一方刚剩一枚棋子时提示规则切换为担吃（悔棋回到非单子局面后再次进入会重新提示）
+    fn announce_single_piece_mode(&mut self) {
+        if self.single_piece_mode.update(self.game.board.is_single_piece_mode()) {
+            self.move_hint = Some((t!("status.single_piece_mode"), Instant::now()));
+        }
+    }
+
+    /// 在棋盘下方短暂显示新手提示，超时后自动消失
+    fn show_move_hint(&mut self, ctx: &Context) {
+        let Some((ref text, shown_at)) = self.move_hint else {
+            return;
+        };
+        let duration = Duration::from_millis(MOVE_HINT_DURATION_MS);
+        let elapsed = shown_at.elapsed();
+        if elapsed >= duration || !self.showing_game() {
+            self.move_hint = None;
+            return;
+        }
+
+        egui::Area::new(egui::Id::new("move_hint"))
+            .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -48.0])
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(format!("💡 {}", text));
+                });
+            });
+        ctx.request_repaint_after(duration - elapsed);
+    }
+
+    /// 显示错误提示框
+    fn show_error_dialog(&mut self, ctx: &Context) {
+        let Some(message) = self.pending_error.clone() else {
+            return;
+        };
+        let mut close = ctx.input(|i| i.key_pressed(Key::Escape) || i.key_pressed(Key::Enter));
+
+        egui::Window::new(t!("dialog.error"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(message);
+                if ui.button(t!("dialog.ok")).clicked() {
+                    close = true;
+                }
+            });
+
+        if close {
+            self.pending_error = None;
+        }
+    }
+
+    /// 进入回放模式（从起始局面开始）
+    fn start_replay(&mut self) {
+        let mut replay = Replay::new(&self.game.board, &self.game.move_history);
+        replay.seek(0);
+        self.replay = Some(ReplayView {
+            replay,
+            step_anim: None,
+            blunder: None,
+            reviewing: false,
+        });
+    }
+
+    /// 开始失误复盘：定位到失误之前的局面；未发现失误时从最近几步之前开始回放
+    fn start_blunder_review(&mut self) {
+        let mut replay = Replay::new(&self.game.board, &self.game.move_history);
+        let blunder = replay.find_blunder(self.game.player_side, &AiPlayer::new(self.game.ai_level));
+        replay.seek(blunder.unwrap_or_else(|| replay.len().saturating_sub(BLUNDER_REVIEW_MOVES * 2)));
+        self.replay = Some(ReplayView {
+            replay,
+            step_anim: None,
+            blunder,
+            reviewing: true,
+        });
+    }
+
+    /// 处理回放控制栏
+    fn handle_replay_bar(&mut self, ctx: &Context) {
+        let Some(view) = self.replay.as_mut() else {
+            return;
+        };
+        let mut exit = false;
+
+        TopBottomPanel::bottom("replay_bar").show(ctx, |ui| {
+            ui.add_space(4.0);
+            ui.horizontal(|ui| {
+                let idle = view.step_anim.is_none();
+                let at_start = view.replay.index() == 0;
+                let at_end = view.replay.index() == view.replay.len();
+
+                if ui.add_enabled(idle && !at_start, egui::Button::new("⏮")).clicked() {
+                    view.replay.seek(0);
+                }
+                if ui.add_enabled(idle && !at_start, egui::Button::new("◀")).clicked() {
+                    view.replay.step_backward();
+                }
+                if ui.add_enabled(idle && !at_end, egui::Button::new("▶")).clicked()
+                    && let (Some(record), Some(board_view)) = (view.replay.next_record(), self.board_view.as_ref())
+                {
+                    view.step_anim = Some(PieceMoveAnimation::new(
+                        record.piece_id,
+                        board_view.board_to_screen(record.from),
+                        board_view.board_to_screen(record.to),
+                        PIECE_MOVE_DURATION_MS,
+                        false,
+                        &self.game.settings,
+                    ));
+                }
+                if ui.add_enabled(idle && !at_end, egui::Button::new("⏭")).clicked() {
+                    view.replay.seek(view.replay.len());
+                }
+
+                ui.separator();
+                ui.label(t!("replay.progress", current = view.replay.index(), total = view.replay.len()));
+                ui.separator();
+                if view.reviewing {
+                    match view.blunder {
+                        Some(index) => {
+                            // 步数从1开始显示；点击跳转到失误之前的局面
+                            let text = format!("⚠ {}", t!("replay.blunder_at", n = index + 1));
+                            if ui.add_enabled(idle, egui::Button::new(text)).clicked() {
+                                view.replay.seek(index);
+                            }
+                        }
+                        None => {
+                            ui.label(t!("replay.no_blunder"));
+                        }
+                    }
+                    ui.separator();
+                }
+
+                if ui.button(t!("replay.exit")).clicked() {
+                    exit = true;
+                }
+            });
+            ui.add_space(4.0);
+        });
+
+        // 前进动画结束后才真正执行这一步
+        if let Some(ref anim) = view.step_anim
+            && anim.start_time.elapsed().as_millis() as u64 >= anim.duration_ms
+        {
+            view.replay.step_forward();
+            view.step_anim = None;
+        }
+
+        if exit {
+            self.replay = None;
+        }
+    }
+
+    /// 渲染回放画面（只读，不处理任何棋盘输入）
+    fn render_replay(&mut self, ui: &mut egui::Ui) {
+        let Some(replay_view) = self.replay.as_ref() else {
+            return;
+        };
+
+        let board_size = fit_board_size(ui.available_size());
+        let center = ui.available_rect_before_wrap().center();
+        let flip = self.game.settings.flip_board_for_white && self.game.player_side == Side::White;
+        let dimension = replay_view.replay.board().size;
+        let mut view = BoardView::new(center, board_size, dimension, flip, self.game.settings.theme, ui.ctx());
+        view.show_coordinates = self.game.settings.show_coordinates;
+
+        view.draw_board(ui);
+
+        // 停在失误之前的局面时标出失误的走法
+        if replay_view.step_anim.is_none()
+            && replay_view.blunder == Some(replay_view.replay.index())
+            && let Some(record) = replay_view.replay.next_record()
+        {
+            view.draw_blunder_marker(ui, record.from, record.to);
+        }
+
+        for piece in replay_view.replay.board().pieces.iter().filter(|p| p.active) {
+            match replay_view.step_anim {
+                Some(ref anim) if anim.piece_id == piece.id => anim.draw(ui, &view, piece),
+                _ => view.draw_piece(ui, piece, false),
+            }
+        }
+
+        self.board_view = Some(view);
+    }
+
+    /// 显示局面编辑面板
+    fn handle_editor_panel(&mut self, ctx: &Context) {
+        let Some(editor) = self.editor.as_mut() else {
+            return;
+        };
+
+        let action = SidePanel::left("editor_panel")
+            .resizable(false)
+            .show(ctx, |ui| editor.show(ui, &self.game.settings))
+            .inner;
+
+        match action {
+            Some(EditorAction::Start { board, player_side, side_to_move }) => {
+                match self.game.start_from_position(board, player_side, side_to_move) {
+                    Ok(()) => {
+                        // 从新局面开始即放弃上一局
+                        remove_autosave();
+                        self.game_over_dialog = GameOverDialog::Closed;
+                        self.reset_for_new_game();
+                        self.editor = None;
+                    }
+                    Err(e) => {
+                        crate::log_warn!("自定义局面不合法: {:?}", e);
+                        if let Some(editor) = self.editor.as_mut() {
+                            editor.show_setup_error(e);
+                        }
+                    }
+                }
+            }
+            Some(EditorAction::Cancel) => self.editor = None,
+            None => {}
+        }
+    }
+
+    /// 渲染局面编辑画面：点击交叉点按当前画笔放置或移除棋子
+    fn render_editor(&mut self, ui: &mut egui::Ui) {
+        let Some(editor) = self.editor.as_mut() else {
+            return;
+        };
+
+        let board_size = fit_board_size(ui.available_size());
+        let center = ui.available_rect_before_wrap().center();
+        let flip = self.game.settings.flip_board_for_white && self.game.player_side == Side::White;
+        let mut view = BoardView::new(center, board_size, editor.board.size, flip, self.game.settings.theme, ui.ctx());
+        view.show_coordinates = self.game.settings.show_coordinates;
+
+        let response = view.draw_board(ui);
+        let hovered = response.hover_pos().and_then(|pos| view.screen_to_board(pos, self.game.settings.drop_tolerance()));
+
+        if response.clicked()
+            && let Some(pos) = hovered
+        {
+            editor.paint(pos);
+        }
+        if let Some(pos) = hovered {
+            view.draw_cursor(ui, pos);
+        }
+        for piece in &editor.board.pieces {
+            view.draw_piece(ui, piece, false);
+        }
+
+        self.board_view = Some(view);
+    }
+
     /// 处理AI回合
     fn handle_ai_turn(&mut self) {
         // 确保有动画正在进行时等待
@@ -480,64 +2111,67 @@ impl MainApp {
             return;
         }
 
-        // 记录AI思考开始时间
-        if self.ai_think_start.is_none() {
-            self.ai_think_start = Some(Instant::now());
-        }
-
-        let elapsed = self.ai_think_start.unwrap().elapsed();
+        // 记录AI思考开始时间（加载或恢复的棋局首次进入时同样在此记录）
+        let elapsed = self.ai_think_start.get_or_insert_with(Instant::now).elapsed();
         
         // 确保最小思考时间（100ms）
         if elapsed < Duration::from_millis(AI_MIN_THINKING_TIME_MS) {
             return;
         }
 
-        // 执行AI移动
-        use crate::game::ai::AiPlayer;
-        let ai = AiPlayer::new(self.game.ai_level);
-        
-        match ai.select_move(&self.game.board, self.game.player_side.opposite()) {
-            Ok((from, to)) => {
-                let _ = self.game.handle_event(GameEvent::AiMoveSelected { from, to });
-                
-                // 触发移动动画
-                if let Some(ref view) = self.board_view {
-                    let from_pos = view.board_to_screen(from);
-                    let to_pos = view.board_to_screen(to);
-                    
-                    if let Some(pending) = self.game.pending_move {
-                        self.animations.piece_move = Some(PieceMoveAnimation {
-                            piece_id: self.game.board.piece_at(to.0, to.1)
-                                .map(|p| p.id)
-                                .unwrap_or(0),
-                            from: from_pos,
-                            to: to_pos,
-                            start_time: Instant::now(),
-                            duration_ms: PIECE_MOVE_DURATION_MS,
-                            is_ai: pending.is_ai,
-                        });
-                    }
-                }
-                
-                // 播放落子音效
-                self.sound.place();
-            }
+        // 演示单步模式：等待点击"下一步"，每次请求只走一步
+        if self.game.demo && !self.demo_stepper.take_turn() {
+            return;
+        }
+
+        // 执行AI移动（演示模式下按当前回合方选择等级）
+        let level = if self.game.demo {
+            self.demo_setup.level_for(self.game.current_turn)
+        } else {
+            self.game.ai_level
+        };
+        let ai = self.game.ai_for_turn(level);
+
+        match ai.select_move(&self.game.board, self.game.current_turn) {
+            Ok((from, to)) => self.start_ai_move(from, to),
             Err(e) => {
-                eprintln!("AI选择移动失败: {}", e);
+                crate::log_error!("AI选择移动失败: {:#}", e);
                 // 如果AI移动失败，回到玩家回合
-                let _ = self.game.handle_event(GameEvent::GameEndCheckComplete { result: None });
+                self.send_event(GameEvent::GameEndCheckComplete { result: None });
             }
         }
         
         self.ai_think_start = None;
+        self.thinking_indicator = None;
+    }
+
+    /// 走出电脑选定的一步：进入移动动画状态后开始移动动画并播放落子音效
+    fn start_ai_move(&mut self, from: (u8, u8), to: (u8, u8)) {
+        // 棋子在移动动画结束后才真正落到目标点，此时仍在起点
+        let piece_id = self.game.board.piece_at(from.0, from.1).map(|p| p.id);
+        let outcome = self.send_event(GameEvent::AiMoveSelected { from, to });
+
+        if outcome.is_some_and(|o| o.state == GameState::PieceMoving) {
+            if let (Some(piece_id), Some(view)) = (piece_id, self.board_view.as_ref()) {
+                self.animations.piece_move = Some(PieceMoveAnimation::new(
+                    piece_id,
+                    view.board_to_screen(from),
+                    view.board_to_screen(to),
+                    PIECE_MOVE_DURATION_MS,
+                    true,
+                    &self.game.settings,
+                ));
+            }
+            self.sound.place();
+        }
     }
 
     /// 开始悔棋动画
     fn start_undo_animation(&mut self) {
-        // 需要至少两步历史记录（AI一步 + 玩家一步）
-        if self.game.move_history.len() < 2 {
-            // 历史记录不足，直接完成悔棋
-            let _ = self.game.handle_event(GameEvent::UndoAnimationComplete);
+        // 动画按"AI一步 + 玩家一步"编排
+        if self.game.undo_steps() != 2 {
+            // 只需回退一步（或无法悔棋）时，直接完成悔棋
+            self.send_event(GameEvent::UndoAnimationComplete);
             return;
         }
 
@@ -547,21 +2181,26 @@ impl MainApp {
         };
 
         // 获取最后两步记录
-        let ai_record = self.game.move_history.last().cloned().unwrap();
-        let player_record = self.game.move_history.iter().nth_back(1).cloned().unwrap();
+        let (player_record, ai_record) = match self.game.move_history.as_slice() {
+            [.., player, ai] => (player.clone(), ai.clone()),
+            _ => {
+                self.send_event(GameEvent::UndoAnimationComplete);
+                return;
+            }
+        };
 
         // 获取棋子当前位置
         let ai_piece_current_pos = if let Some(piece) = self.game.board.piece_by_id(ai_record.piece_id) {
             view.board_to_screen(piece.position)
         } else {
-            let _ = self.game.handle_event(GameEvent::UndoAnimationComplete);
+            self.send_event(GameEvent::UndoAnimationComplete);
             return;
         };
 
         let player_piece_current_pos = if let Some(piece) = self.game.board.piece_by_id(player_record.piece_id) {
             view.board_to_screen(piece.position)
         } else {
-            let _ = self.game.handle_event(GameEvent::UndoAnimationComplete);
+            self.send_event(GameEvent::UndoAnimationComplete);
             return;
         };
 
@@ -580,24 +2219,26 @@ impl MainApp {
             None
         };
 
-        // 创建悔棋动画
+        // 创建悔棋动画（被吃棋子复位与每步回退时长相同，减少动画时直接复位）
+        let step_ms = undo_step_duration(&self.game.settings);
+        let stage = || Animation::new(step_ms, 0.0, 1.0).with_easing(Easing::OutQuad);
+        let mut stages = vec![(stage(), UndoStep::AiUndoing)];
+        if captured_piece.is_some() {
+            stages.push((stage(), UndoStep::CapturedReturning));
+        }
+        stages.push((stage(), UndoStep::PlayerUndoing));
+
         self.animations.undo = Some(UndoAnimation {
-            step: UndoStep::AiUndoing,
-            ai_move: PieceMoveAnimation {
+            sequence: AnimationSequence::new(stages),
+            ai_move: UndoMove {
                 piece_id: ai_record.piece_id,
                 from: ai_piece_current_pos,
                 to: ai_target_pos,
-                start_time: Instant::now(),
-                duration_ms: UNDO_STEP_DURATION_MS,
-                is_ai: true,
             },
-            player_move: PieceMoveAnimation {
+            player_move: UndoMove {
                 piece_id: player_record.piece_id,
                 from: player_piece_current_pos,
                 to: player_target_pos,
-                start_time: Instant::now(), // 会在第三步更新
-                duration_ms: UNDO_STEP_DURATION_MS,
-                is_ai: false,
             },
             ai_record,
             player_record,
@@ -606,19 +2247,105 @@ impl MainApp {
     }
 
     /// 处理玩家输入
-    fn handle_player_input(&mut self, _ctx: &Context, response: &egui::Response) {
+    fn handle_player_input(&mut self, ctx: &Context, response: &egui::Response) {
+        // 暂停期间不接受棋盘输入
+        if self.is_paused() {
+            return;
+        }
+        // 使用鼠标时隐藏键盘光标
+        if response.is_pointer_button_down_on() {
+            self.keyboard_cursor = None;
+        }
+        if !self.has_active_animation() && !ctx.wants_keyboard_input() {
+            self.handle_keyboard_input(ctx);
+        }
+
         // 根据当前状态处理不同的输入
         match self.game.state {
             GameState::WaitingForPlayer => {
                 self.handle_waiting_input(response);
             }
-            GameState::PieceSelected => {
+            GameState::PieceSelected | GameState::WaitingForTargetClick => {
                 self.handle_piece_selected_input(response);
             }
+            GameState::PieceDragging => {
+                self.handle_dragging_input(response);
+            }
+            GameState::ConfirmingMove if response.clicked_by(egui::PointerButton::Secondary) => {
+                self.send_event(GameEvent::PlayerCancel);
+            }
             _ => {}
         }
     }
 
+    /// 向状态机发送事件并返回流转结果：处理出错时记录错误并返回 None，
+    /// 当前状态不处理的事件记入调试日志
+    fn send_event(&mut self, event: GameEvent) -> Option<TransitionOutcome> {
+        let sent = event.clone();
+        match self.game.handle_event(event) {
+            Ok(outcome) => {
+                if !outcome.accepted {
+                    crate::log_debug!("状态 {:?} 下忽略事件 {:?}", outcome.previous, sent);
+                }
+                Some(outcome)
+            }
+            Err(e) => {
+                crate::log_error!("处理事件 {:?} 失败: {:#}", sent, e);
+                None
+            }
+        }
+    }
+
+    /// 确认落子：开始棋子从起点移动到目标点的动画
+    fn confirm_pending_move(&mut self) {
+        let Some(pending) = self.game.pending_move else {
+            return;
+        };
+        let piece_id = self.game.board.piece_at(pending.from.0, pending.from.1).map(|p| p.id);
+        let outcome = self.send_event(GameEvent::ConfirmMove);
+        if outcome.is_some_and(|o| o.state == GameState::PieceMoving)
+            && let (Some(piece_id), Some(view)) = (piece_id, self.board_view.as_ref())
+        {
+            let from_pos = view.board_to_screen(pending.from);
+            let to_pos = view.board_to_screen(pending.to);
+            self.start_player_move_animation(piece_id, from_pos, to_pos, PIECE_MOVE_DURATION_MS);
+        }
+    }
+
+    /// 等待确认落子时在棋盘下方显示确认/取消按钮（回车确认，Esc 取消）
+    fn show_confirm_move_bar(&mut self, ctx: &Context) {
+        if self.game.state != GameState::ConfirmingMove || !self.showing_game() || self.is_paused() {
+            return;
+        }
+        let (mut confirm, mut cancel) = if ctx.wants_keyboard_input() {
+            (false, false)
+        } else {
+            ctx.input(|i| (i.key_pressed(Key::Enter), i.key_pressed(Key::Escape)))
+        };
+
+        egui::Area::new(egui::Id::new("confirm_move"))
+            .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -48.0])
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(t!("status.confirming"));
+                        if ui.button(format!("✔ {}", t!("dialog.ok"))).clicked() {
+                            confirm = true;
+                        }
+                        if ui.button(format!("✖ {}", t!("dialog.cancel"))).clicked() {
+                            cancel = true;
+                        }
+                    });
+                });
+            });
+
+        if confirm {
+            self.confirm_pending_move();
+        } else if cancel {
+            self.send_event(GameEvent::PlayerCancel);
+        }
+    }
+
     /// 处理等待玩家行棋状态（初始状态）的输入
     fn handle_waiting_input(&mut self, response: &egui::Response) {
         let view = match self.board_view {
@@ -626,30 +2353,122 @@ impl MainApp {
             None => return,
         };
 
-        // 处理鼠标左键点击（选中棋子）
-        if response.clicked_by(egui::PointerButton::Primary) {
-            if let Some(pos) = response.interact_pointer_pos() {
-                // 查找点击的己方棋子
-                let clicked_piece = self.game.board.active_pieces_of(self.game.player_side)
-                    .into_iter()
-                    .find(|piece| view.hit_test_piece(pos, piece.position));
-                
-                if let Some(piece) = clicked_piece {
-                    // 检查棋子是否可以移动
-                    if self.can_piece_move(piece.id) {
-                        self.sound.click();
-                        // 发送事件进入棋子选中状态
-                        let _ = self.game.handle_event(GameEvent::PlayerSelectPiece {
-                            piece_id: piece.id,
-                            start_pos: piece.position,
-                        });
-                    }
+        // 按住左键拖动时以按下位置为准，否则处理左键点击（选中棋子）
+        let drag_started = response.drag_started_by(egui::PointerButton::Primary);
+        let press_pos = if drag_started {
+            response.ctx.input(|i| i.pointer.press_origin())
+        } else if response.clicked_by(egui::PointerButton::Primary) {
+            response.interact_pointer_pos()
+        } else {
+            None
+        };
+
+        if let Some(pos) = press_pos {
+            // 查找点击的己方棋子
+            let clicked_piece = self.game.board.active_pieces_of(self.game.player_side)
+                .into_iter()
+                .find(|piece| view.hit_test_piece(pos, piece.position));
+
+            // 检查棋子是否可以移动
+            if let Some(piece) = clicked_piece
+                && self.can_piece_move(piece.id)
+            {
+                self.sound.click();
+                // 发送事件进入棋子选中状态
+                let selected = self.send_event(GameEvent::PlayerSelectPiece {
+                    piece_id: piece.id,
+                    start_pos: piece.position,
+                });
+                // 按住拖动则直接进入拖拽状态，未拖动即松开则等待点击目标点
+                if !selected.is_some_and(|o| o.state == GameState::PieceSelected) {
+                    return;
+                }
+                if drag_started {
+                    self.send_event(GameEvent::PlayerStartMoving);
+                    self.drag_pos = Some(pos);
+                } else {
+                    self.send_event(GameEvent::PlayerReleaseWithoutMove);
                 }
             }
         }
     }
     
-    /// 处理棋子已选中状态的输入
+    /// 处理键盘操作：方向键移动光标，回车选中棋子或走到目标点，Esc 取消选中
+    fn handle_keyboard_input(&mut self, ctx: &Context) {
+        let state = self.game.state;
+        if state != GameState::WaitingForPlayer
+            && state != GameState::PieceSelected
+            && state != GameState::WaitingForTargetClick
+        {
+            return;
+        }
+
+        let (dir, enter, escape) = ctx.input(|i| {
+            let dir = [
+                (Key::ArrowUp, (0, 1)),
+                (Key::ArrowDown, (0, -1)),
+                (Key::ArrowLeft, (-1, 0)),
+                (Key::ArrowRight, (1, 0)),
+            ]
+                .into_iter()
+                .find(|&(key, _)| i.key_pressed(key))
+                .map(|(_, dir)| dir);
+            (dir, i.key_pressed(Key::Enter), i.key_pressed(Key::Escape))
+        });
+
+        if let Some(dir) = dir {
+            let flip = self.board_view.as_ref().is_some_and(|v| v.flip);
+            self.keyboard_cursor = Some(match self.keyboard_cursor {
+                Some(pos) => step_cursor(pos, dir, self.game.board.size, flip),
+                // 首次按方向键时光标出现在选中的棋子或己方第一枚棋子上
+                None => self.game.selected_piece.map(|s| s.start_pos)
+                    .or_else(|| self.game.board.active_pieces_of(self.game.player_side).first().map(|p| p.position))
+                    .unwrap_or((0, 0)),
+            });
+        }
+
+        if escape && state != GameState::WaitingForPlayer {
+            self.send_event(GameEvent::PlayerCancel);
+            return;
+        }
+
+        let Some(pos) = self.keyboard_cursor.filter(|_| enter) else {
+            return;
+        };
+
+        // 已选中棋子时，光标在合法目标点上则走棋
+        if let Some(selected) = self.game.selected_piece.filter(|_| state != GameState::WaitingForPlayer) {
+            if self.is_valid_move_for_piece(selected.piece_id, pos) {
+                let outcome = self.send_event(GameEvent::PlayerClickTarget { target_pos: pos });
+                if outcome.is_some_and(|o| o.state == GameState::PieceMoving)
+                    && let Some(ref view) = self.board_view
+                {
+                    let from_pos = view.board_to_screen(selected.start_pos);
+                    let to_pos = view.board_to_screen(pos);
+                    self.start_player_move_animation(selected.piece_id, from_pos, to_pos, PIECE_MOVE_DURATION_MS);
+                }
+                return;
+            }
+            // 否则取消当前选中，再尝试选中光标处的棋子
+            self.send_event(GameEvent::PlayerCancel);
+        }
+
+        let piece = self.game.board.piece_at(pos.0, pos.1)
+            .filter(|p| p.side == self.game.player_side)
+            .map(|p| p.id);
+        match piece {
+            Some(piece_id) if self.can_piece_move(piece_id) => {
+                self.sound.click();
+                let selected = self.send_event(GameEvent::PlayerSelectPiece { piece_id, start_pos: pos });
+                if selected.is_some_and(|o| o.state == GameState::PieceSelected) {
+                    self.send_event(GameEvent::PlayerReleaseWithoutMove);
+                }
+            }
+            _ => self.sound.invalid(),
+        }
+    }
+
+    /// 处理棋子已选中 / 等待点击目标点状态的输入
     fn handle_piece_selected_input(&mut self, response: &egui::Response) {
         let view = match self.board_view {
             Some(ref v) => v.clone(),
@@ -658,109 +2477,153 @@ impl MainApp {
 
         // 处理右键取消，返回初始状态
         if response.clicked_by(egui::PointerButton::Secondary) {
-            let _ = self.game.handle_event(GameEvent::PlayerCancel);
+            self.send_event(GameEvent::PlayerCancel);
             return;
         }
 
-        // 处理左键点击
-        if response.clicked_by(egui::PointerButton::Primary) {
-            if let Some(pos) = response.interact_pointer_pos() {
-                // 获取当前选中的棋子信息
-                let selected = match self.game.selected_piece {
-                    Some(s) => s,
-                    None => {
-                        // 没有选中棋子，返回初始状态
-                        let _ = self.game.handle_event(GameEvent::PlayerCancel);
-                        return;
-                    }
-                };
+        // 按住已选中的棋子拖动，进入拖拽状态
+        if response.drag_started_by(egui::PointerButton::Primary)
+            && let Some(pos) = response.ctx.input(|i| i.pointer.press_origin())
+            && let Some(selected) = self.game.selected_piece
+            && view.hit_test_piece(pos, selected.start_pos)
+        {
+            self.send_event(GameEvent::PlayerStartMoving);
+            self.drag_pos = Some(pos);
+            return;
+        }
 
-                // 检查是否点击了合法目标点
-                if let Some(target_pos) = view.screen_to_board(pos, 0.4) {
-                    if self.is_valid_move_for_piece(selected.piece_id, target_pos) {
-                        let _ = self.game.handle_event(GameEvent::PlayerClickTarget { target_pos });
-                        
-                        if matches!(self.game.state, GameState::PieceMoving) {
-                            let to_pos = view.board_to_screen(target_pos);
-                            let from_pos = view.board_to_screen(selected.start_pos);
-                            
-                            self.animations.piece_move = Some(PieceMoveAnimation {
-                                piece_id: selected.piece_id,
-                                from: from_pos,
-                                to: to_pos,
-                                start_time: Instant::now(),
-                                duration_ms: PIECE_MOVE_DURATION_MS,
-                                is_ai: false,
-                            });
-                            
-                            self.sound.place();
-                        }
-                        return;
-                    }
+        // 处理左键点击
+        if response.clicked_by(egui::PointerButton::Primary)
+            && let Some(pos) = response.interact_pointer_pos()
+        {
+            // 获取当前选中的棋子信息
+            let selected = match self.game.selected_piece {
+                Some(s) => s,
+                None => {
+                    // 没有选中棋子，返回初始状态
+                    self.send_event(GameEvent::PlayerCancel);
+                    return;
                 }
-                
-                // 点击了非目标点，返回初始状态
-                let _ = self.game.handle_event(GameEvent::PlayerClickInvalid);
+            };
+
+            // 检查是否点击了合法目标点
+            if let Some(target_pos) = view.screen_to_board(pos, self.game.settings.drop_tolerance())
+                && self.is_valid_move_for_piece(selected.piece_id, target_pos)
+            {
+                let outcome = self.send_event(GameEvent::PlayerClickTarget { target_pos });
+
+                if outcome.is_some_and(|o| o.state == GameState::PieceMoving) {
+                    let to_pos = view.board_to_screen(target_pos);
+                    let from_pos = view.board_to_screen(selected.start_pos);
+                    self.start_player_move_animation(selected.piece_id, from_pos, to_pos, PIECE_MOVE_DURATION_MS);
+                }
+                return;
+            }
+
+            // 点击了空点但不能走到该处时给出新手提示
+            if let Some(target_pos) = view.screen_to_board(pos, self.game.settings.drop_tolerance())
+                && self.game.board.is_empty(target_pos.0, target_pos.1)
+            {
+                self.explain_illegal_move(selected.start_pos, Some(target_pos));
             }
+
+            // 点击了非目标点，返回初始状态
+            self.send_event(GameEvent::PlayerClickInvalid);
         }
     }
 
-    /// 检查指定棋子是否可以移动
-    fn can_piece_move(&self, piece_id: u8) -> bool {
-        if let Some(piece) = self.game.board.piece_by_id(piece_id) {
-            if piece.side != self.game.player_side || !piece.active {
-                return false;
-            }
+    /// 处理棋子拖拽中状态的输入
+    fn handle_dragging_input(&mut self, response: &egui::Response) {
+        let view = match self.board_view {
+            Some(ref v) => v.clone(),
+            None => return,
+        };
+
+        let Some(selected) = self.game.selected_piece else {
+            self.send_event(GameEvent::PlayerCancel);
+            self.drag_pos = None;
+            return;
+        };
 
-            let (x, y) = piece.position;
-            let directions = [(0, 1), (0, -1), (1, 0), (-1, 0)];
+        // 右键取消拖拽，返回初始状态
+        if response.clicked_by(egui::PointerButton::Secondary) {
+            self.send_event(GameEvent::PlayerCancel);
+            self.drag_pos = None;
+            return;
+        }
 
-            for (dx, dy) in directions {
-                let nx = x as i8 + dx;
-                let ny = y as i8 + dy;
+        // 棋子跟随鼠标
+        if let Some(pos) = response.interact_pointer_pos() {
+            self.drag_pos = Some(pos);
+        }
 
-                if Board::is_valid_pos(nx, ny) && self.game.board.is_empty(nx as u8, ny as u8) {
-                    return true;
-                }
+        if !response.drag_stopped() {
+            return;
+        }
+
+        let start_screen_pos = view.board_to_screen(selected.start_pos);
+        let drop_pos = self.drag_pos.take().unwrap_or(start_screen_pos);
+        let drop_target = view.screen_to_board(drop_pos, self.game.settings.drop_tolerance());
+
+        // 松开在合法目标点上：从松开位置吸附到目标点
+        if let Some(target_pos) = drop_target
+            && self.is_valid_move_for_piece(selected.piece_id, target_pos)
+        {
+            let outcome = self.send_event(GameEvent::PlayerClickTarget { target_pos });
+            if outcome.is_some_and(|o| o.state == GameState::PieceMoving) {
+                let to_pos = view.board_to_screen(target_pos);
+                self.start_player_move_animation(selected.piece_id, drop_pos, to_pos, PIECE_RETURN_DURATION_MS);
             }
+            return;
         }
-        false
+
+        // 未落在合法目标点：棋子放回原位并保持选中，可继续点击目标点
+        if drop_target != Some(selected.start_pos) {
+            self.sound.invalid();
+            self.explain_illegal_move(selected.start_pos, drop_target);
+        }
+        self.send_event(GameEvent::PlayerReleaseWithoutMove);
+    }
+
+    /// 开始玩家棋子的移动动画并播放落子音效
+    fn start_player_move_animation(&mut self, piece_id: u8, from: egui::Pos2, to: egui::Pos2, base_duration_ms: u64) {
+        self.animations.piece_move = Some(PieceMoveAnimation::new(
+            piece_id,
+            from,
+            to,
+            base_duration_ms,
+            false,
+            &self.game.settings,
+        ));
+
+        self.sound.place();
+    }
+
+    /// 检查指定棋子是否可以移动
+    fn can_piece_move(&self, piece_id: u8) -> bool {
+        !self.get_valid_moves_for_piece(piece_id).is_empty()
     }
     
     /// 检查指定棋子是否可以移动到目标位置
     fn is_valid_move_for_piece(&self, piece_id: u8, target_pos: (u8, u8)) -> bool {
-        use crate::game::rules::is_valid_move;
         
         if let Some(piece) = self.game.board.piece_by_id(piece_id) {
-            return is_valid_move(&self.game.board, piece.position, target_pos, self.game.player_side);
+            return self.game.rules.is_valid_move(&self.game.board, piece.position, target_pos, self.game.player_side);
         }
         false
     }
     
+    /// 是否显示全部可走位置（仅在等待玩家行棋且无动画时）
+    fn show_all_moves_overlay(&self) -> bool {
+        self.game.settings.show_all_moves
+            && self.game.state == GameState::WaitingForPlayer
+            && self.showing_game()
+            && !self.has_active_animation()
+    }
+
     /// 获取指定棋子的所有合法移动位置
     fn get_valid_moves_for_piece(&self, piece_id: u8) -> Vec<(u8, u8)> {
-        use crate::game::rules::is_valid_move;
-        
-        let mut moves = Vec::new();
-        
-        if let Some(piece) = self.game.board.piece_by_id(piece_id) {
-            let (x, y) = piece.position;
-            let directions = [(0, 1), (0, -1), (1, 0), (-1, 0)];
-            
-            for (dx, dy) in directions {
-                let nx = x as i8 + dx;
-                let ny = y as i8 + dy;
-                
-                if nx >= 0 && nx < 4 && ny >= 0 && ny < 4 {
-                    let target = (nx as u8, ny as u8);
-                    if is_valid_move(&self.game.board, piece.position, target, self.game.player_side) {
-                        moves.push(target);
-                    }
-                }
-            }
-        }
-        
-        moves
+        self.game.legal_targets(piece_id)
     }
 
     /// 更新所有动画
@@ -771,74 +2634,98 @@ impl MainApp {
             if elapsed >= anim.duration_ms {
                 // 动画完成
                 let moved = anim.from != anim.to;
-                let _ = self.game.handle_event(GameEvent::PieceMoveAnimationComplete { moved });
+                let outcome = self.send_event(GameEvent::PieceMoveAnimationComplete { moved });
                 
                 // 检查是否产生了吃子
-                if moved && !self.game.last_captured.is_empty() {
-                    self.animations.capture = Some(CaptureAnimation {
-                        piece_ids: self.game.last_captured.clone(),
-                        start_time: Instant::now(),
-                        stage: CaptureStage::Flashing,
-                    });
-                    self.sound.capture();
+                if let Some(outcome) = outcome
+                    && outcome.moved
+                    && outcome.has_capture()
+                {
+                    self.animations.capture = Some(CaptureAnimation::new(outcome.captured, &self.game.settings));
+
+                    // 单子担吃（或一次吃掉两枚）使用专门的音效
+                    let is_double = self.game.move_history.last()
+                        .is_some_and(|r| r.was_single_piece_mode || r.captured.len() >= 2);
+                    if is_double {
+                        self.sound.double_capture();
+                    } else {
+                        self.sound.capture();
+                    }
                 }
                 
                 self.animations.piece_move = None;
             }
         }
 
-        // 更新吃子动画
+        // 更新吃子动画（时长为0时两个阶段在同一帧内完成）
         if let Some(ref mut anim) = self.animations.capture {
-            let elapsed = anim.start_time.elapsed().as_millis() as u64;
-            
-            match anim.stage {
-                CaptureStage::Flashing if elapsed >= CAPTURE_FLASH_DURATION_MS => {
-                    anim.stage = CaptureStage::Removing;
-                    anim.start_time = Instant::now();
-                }
-                CaptureStage::Removing if elapsed >= CAPTURE_REMOVE_DURATION_MS => {
-                    let _ = self.game.handle_event(GameEvent::CaptureAnimationComplete);
-                    self.animations.capture = None;
-                }
-                _ => {}
+            let mut elapsed = anim.start_time.elapsed().as_millis() as u64;
+
+            if anim.stage == CaptureStage::Flashing && elapsed >= anim.flash_ms {
+                anim.stage = CaptureStage::Removing;
+                anim.start_time = Instant::now();
+                elapsed = 0;
+            }
+            if anim.stage == CaptureStage::Removing && elapsed >= anim.remove_ms {
+                self.send_event(GameEvent::CaptureAnimationComplete);
+                self.animations.capture = None;
             }
         }
 
-        // 更新悔棋动画
-        if let Some(ref mut anim) = self.animations.undo {
-            let now = Instant::now();
-            
-            match anim.step {
-                UndoStep::AiUndoing => {
-                    let elapsed = now.duration_since(anim.ai_move.start_time).as_millis() as u64;
-                    if elapsed >= anim.ai_move.duration_ms {
-                        if anim.captured_piece.is_some() {
-                            anim.step = UndoStep::CapturedReturning;
-                        } else {
-                            // 没有被吃棋子，直接进入第三步，更新玩家动画开始时间
-                            anim.player_move.start_time = now;
-                            anim.step = UndoStep::PlayerUndoing;
-                        }
-                    }
-                }
-                UndoStep::CapturedReturning => {
-                    let ai_end = anim.ai_move.start_time + Duration::from_millis(anim.ai_move.duration_ms);
-                    let elapsed = now.duration_since(ai_end).as_millis() as u64;
-                    if elapsed >= UNDO_STEP_DURATION_MS {
-                        // 进入第三步时更新玩家动画的开始时间
-                        anim.player_move.start_time = now;
-                        anim.step = UndoStep::PlayerUndoing;
-                    }
-                }
-                UndoStep::PlayerUndoing => {
-                    let elapsed = now.duration_since(anim.player_move.start_time).as_millis() as u64;
-                    if elapsed >= anim.player_move.duration_ms {
-                        let _ = self.game.handle_event(GameEvent::UndoAnimationComplete);
-                        self.animations.undo = None;
-                    }
-                }
+        // 更新悔棋动画（时长为0时同一帧内完成）
+        if self.animations.undo.as_ref().is_some_and(|anim| anim.sequence.is_finished()) {
+            self.send_event(GameEvent::UndoAnimationComplete);
+            self.animations.undo = None;
+        }
+    }
+
+    /// 电脑行棋的移动或吃子动画是否可以跳过
+    fn can_skip_ai_animation(&self) -> bool {
+        !self.is_paused()
+            && matches!(self.game.state, GameState::PieceMoving | GameState::CaptureAnimating)
+            && self.game.is_ai_turn()
+    }
+
+    /// 跳过电脑行棋的剩余动画：立即触发各动画的完成事件并推进状态机，
+    /// 直到轮到下一方行棋或棋局结束
+    ///
+    /// 每一轮都应推进状态；事件处理失败导致状态不变时停止，避免界面卡死
+    fn skip_ai_animations(&mut self) {
+        loop {
+            let before = self.game.state;
+            self.animations.finish_now();
+            self.update_animations();
+            self.process_state_transitions();
+            let animating = self.animations.piece_move.is_some() || self.animations.capture.is_some();
+            let checking = matches!(self.game.state, GameState::CheckingCapture | GameState::CheckingGameEnd);
+            if !animating && !checking {
+                break;
             }
+            if self.game.state == before {
+                crate::log_warn!("跳过动画时状态停在 {:?}，停止跳过", before);
+                break;
+            }
+        }
+    }
+
+    /// 每帧推进对局：电脑行棋、状态流转与动画（暂停期间均不推进）
+    fn advance_frame(&mut self) {
+        if self.is_paused() {
+            return;
+        }
+
+        // 处理AI回合
+        if matches!(self.game.state, GameState::AiThinking) {
+            self.handle_ai_turn();
         }
+
+        // 处理状态流转
+        self.process_state_transitions();
+
+        // 更新动画
+        self.update_animations();
+        self.sync_last_move();
+        self.announce_single_piece_mode();
     }
 
     /// 处理状态流转（非动画驱动的事件）
@@ -847,9 +2734,9 @@ impl MainApp {
             GameState::NewGame => {
                 // 新局开始后自动流转到下一状态
                 if self.game.player_side == self.game.current_turn {
-                    let _ = self.game.handle_event(GameEvent::StartNewGame { player_first: true, ai_level: self.game.ai_level });
+                    self.send_event(GameEvent::StartNewGame { player_first: true, ai_level: self.game.ai_level });
                 } else {
-                    let _ = self.game.handle_event(GameEvent::StartNewGame { player_first: false, ai_level: self.game.ai_level });
+                    self.send_event(GameEvent::StartNewGame { player_first: false, ai_level: self.game.ai_level });
                 }
             }
             GameState::UndoAnimating if self.animations.undo.is_none() => {
@@ -859,34 +2746,40 @@ impl MainApp {
             GameState::CheckingCapture => {
                 let has_capture = !self.game.last_captured.is_empty();
                 let captured = self.game.last_captured.clone();
-                let _ = self.game.handle_event(GameEvent::CaptureCheckComplete { 
+                self.send_event(GameEvent::CaptureCheckComplete { 
                     has_capture, 
                     captured_piece_ids: captured 
                 });
             }
             GameState::CheckingGameEnd => {
-                let result = self.game.check_game_end();
+                // 困毙针对即将行棋的一方，状态机随后切换回合
+                let result = self.game.check_game_end_after_move();
+                self.send_event(GameEvent::GameEndCheckComplete { result });
+                let final_result = result.map(|(result, _)| result);
                 
-                // 检查是否需要切换回合后再检查困毙（AI移动后需要检查人类方）
-                let final_result = if result.is_none() {
-                    // 先发送事件给状态机处理（这会切换回合）
-                    let _ = self.game.handle_event(GameEvent::GameEndCheckComplete { result });
-                    // 切换回合后，检查新回合方是否被困毙
-                    self.game.check_stalemate_for_current_turn()
+                // 每步行棋完成后写入自动存档，棋局结束则删除
+                if final_result.is_none() {
+                    self.write_autosave();
                 } else {
-                    // 已经有结果（无子判负或平局），直接发送事件
-                    let _ = self.game.handle_event(GameEvent::GameEndCheckComplete { result });
-                    result
-                };
-                
+                    remove_autosave();
+                }
+
                 // 如果游戏结束，播放相应音效并显示对话框
-                if let Some(final_result) = final_result {
+                if let Some(final_result) = final_result
+                    && self.game.demo
+                {
+                    match final_result {
+                        GameResult::Draw => self.sound.draw(),
+                        _ => self.sound.win(),
+                    }
+                    self.demo_over_dialog = DemoOverDialog::Open(final_result);
+                } else if let Some(final_result) = final_result {
                     match final_result {
                         GameResult::PlayerWin => self.sound.win(),
                         GameResult::AiWin => self.sound.lose(),
                         GameResult::Draw => self.sound.draw(),
                     }
-                    self.game_over_dialog = GameOverDialog::Open(final_result);
+                    self.game_over_dialog = GameOverDialog::Open(final_result, self.game.end_reason, self.game.stats());
                 }
             }
             _ => {}
@@ -895,27 +2788,48 @@ impl MainApp {
 
     /// 渲染游戏画面
     fn render_game(&mut self, ui: &mut egui::Ui) {
-        let available_size = ui.available_size();
-        let board_size = available_size.min_elem().min(500.0);
+        let board_size = fit_board_size(ui.available_size());
         let center = ui.available_rect_before_wrap().center();
 
-        // 根据玩家执子方决定是否翻转棋盘
-        let flip = self.game.player_side == Side::White;
-        let view = BoardView::new(center, board_size, flip, ui.ctx());
+        // 根据玩家执子方决定是否翻转棋盘（可在设置中关闭）
+        let flip = self.game.settings.flip_board_for_white && self.game.player_side == Side::White;
+        let dimension = self.game.board.size;
+        let mut view = BoardView::new(center, board_size, dimension, flip, self.game.settings.theme, ui.ctx());
+        view.show_coordinates = self.game.settings.show_coordinates;
 
         // 绘制棋盘
         let response = view.draw_board(ui);
 
-        // 在棋子已选中状态下，绘制高亮和合法目标点
-        if let GameState::PieceSelected = self.game.state {
-            if let Some(ref selected) = self.game.selected_piece {
-                // 高亮选中的棋子位置
-                view.draw_selected_piece_highlight(ui, selected.start_pos);
-                
-                // 计算并绘制合法目标点
-                let valid_moves = self.get_valid_moves_for_piece(selected.piece_id);
-                view.draw_valid_move_hints(ui, &valid_moves);
-            }
+        // 标记最近一步
+        if let Some((from, to)) = self.last_move {
+            view.draw_last_move_markers(ui, from, to);
+        }
+
+        // 在棋子已选中或拖拽状态下，绘制高亮和合法目标点
+        if self.game.state.has_selected_piece()
+            && let Some(ref selected) = self.game.selected_piece
+        {
+            // 高亮选中的棋子位置
+            view.draw_selected_piece_highlight(ui, selected.start_pos);
+
+            // 计算并绘制合法目标点
+            let valid_moves = self.get_valid_moves_for_piece(selected.piece_id);
+            view.draw_valid_move_hints(ui, &valid_moves);
+        }
+
+        // 教学辅助：轮到玩家时显示己方所有可走位置，并记录可移动的棋子
+        let movable_positions = self.show_all_moves_overlay().then(|| {
+            let moves = self.game.rules.valid_moves(&self.game.board, self.game.player_side);
+            let mut targets: Vec<(u8, u8)> = moves.iter().map(|&(_, to)| to).collect();
+            targets.sort_unstable();
+            targets.dedup();
+            view.draw_faint_move_hints(ui, &targets);
+            moves.into_iter().map(|(from, _)| from).collect::<Vec<_>>()
+        });
+
+        // 键盘光标
+        if let Some(pos) = self.keyboard_cursor {
+            view.draw_cursor(ui, pos);
         }
 
         // 收集悔棋动画中需要显示的被吃棋子ID
@@ -932,28 +2846,29 @@ impl MainApp {
             }
 
             // 检查是否是选中的棋子（高亮显示）
-            let is_selected = matches!(self.game.state, GameState::PieceSelected)
+            let is_selected = matches!(self.game.state, GameState::PieceSelected | GameState::WaitingForTargetClick)
+                && self.game.selected_piece.as_ref().map(|s| s.piece_id) == Some(piece.id);
+            let is_dragged = matches!(self.game.state, GameState::PieceDragging)
                 && self.game.selected_piece.as_ref().map(|s| s.piece_id) == Some(piece.id);
 
-            if let Some(ref anim) = self.animations.piece_move {
+            if is_dragged && let Some(drag_pos) = self.drag_pos {
+                // 拖拽中的棋子跟随鼠标
+                view.draw_animated_piece(ui, piece, drag_pos);
+            } else if let Some(ref anim) = self.animations.piece_move {
                 // 移动动画中
                 if anim.piece_id == piece.id {
-                    let elapsed = anim.start_time.elapsed().as_millis() as f64;
-                    let progress = (elapsed / anim.duration_ms as f64).min(1.0);
-                    let t = crate::utils::ease_in_out_quad(progress as f32);
-
-                    let current_pos = egui::Pos2::new(
-                        crate::utils::lerp(anim.from.x, anim.to.x, t),
-                        crate::utils::lerp(anim.from.y, anim.to.y, t),
-                    );
-
-                    view.draw_animated_piece(ui, piece, current_pos);
+                    anim.draw(ui, &view, piece);
                 } else {
                     view.draw_piece(ui, piece, is_selected);
                 }
             } else if let Some(ref undo) = self.animations.undo {
                 // 悔棋动画中
                 self.render_undo_animation_piece(ui, &view, piece, undo);
+            } else if piece.side == self.game.player_side
+                && movable_positions.as_ref().is_some_and(|m| !m.contains(&piece.position))
+            {
+                // 不可移动的己方棋子变暗显示
+                view.draw_piece_with_alpha(ui, piece, view.board_to_screen(piece.position), DIMMED_PIECE_ALPHA);
             } else {
                 view.draw_piece(ui, piece, is_selected);
             }
@@ -962,6 +2877,54 @@ impl MainApp {
         // 绘制吃子动画
         self.render_capture_animation(ui, &view);
 
+        // 调试：在盘上棋子的落点显示棋子ID
+        if self.show_piece_ids {
+            for piece in self.game.board.pieces.iter().filter(|p| p.active) {
+                view.draw_piece_id(ui, piece);
+            }
+        }
+
+        // 等待确认落子：目标点显示半透明棋子，并标记将被吃掉的棋子
+        if self.game.state == GameState::ConfirmingMove
+            && let Some(pending) = self.game.pending_move
+            && let Some(piece) = self.game.board.piece_at(pending.from.0, pending.from.1)
+        {
+            view.draw_selected_piece_highlight(ui, pending.from);
+            view.draw_piece_with_alpha(ui, piece, view.board_to_screen(pending.to), DIMMED_PIECE_ALPHA);
+            for id in self.game.board.captures_if_moved(pending.from, pending.to) {
+                if let Some(piece) = self.game.board.piece_by_id(id) {
+                    view.draw_capture_preview(ui, piece.position);
+                }
+            }
+        }
+
+        // 吃子预览：鼠标停在合法目标点上时，标记落子后将被吃掉的棋子
+        if let Some(ref selected) = self.game.selected_piece
+            && self.game.state.has_selected_piece()
+            && let Some(target) = ui.ctx().pointer_latest_pos().and_then(|pos| view.screen_to_board(pos, self.game.settings.drop_tolerance()))
+            && self.is_valid_move_for_piece(selected.piece_id, target)
+        {
+            for id in self.game.board.captures_if_moved(selected.start_pos, target) {
+                if let Some(piece) = self.game.board.piece_by_id(id) {
+                    view.draw_capture_preview(ui, piece.position);
+                }
+            }
+        }
+
+        // 暂停遮罩
+        if self.is_paused() {
+            let rect = response.rect;
+            let painter = ui.painter();
+            painter.rect_filled(rect, 0.0, egui::Color32::from_black_alpha(140));
+            painter.text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                t!("status.paused"),
+                egui::FontId::proportional(32.0),
+                egui::Color32::WHITE,
+            );
+        }
+
         self.board_view = Some(view);
         self.handle_player_input(ui.ctx(), &response);
     }
@@ -972,26 +2935,20 @@ impl MainApp {
         let is_player_piece = undo.player_move.piece_id == piece.id;
         let is_captured_piece = undo.captured_piece.as_ref().map(|c| c.record.piece_id) == Some(piece.id);
 
+        let step = undo.sequence.current_tag().copied().unwrap_or(UndoStep::PlayerUndoing);
+
         if is_ai_piece {
-            // AI棋子回退动画
-            let elapsed = undo.ai_move.start_time.elapsed().as_millis() as f64;
-            let progress = (elapsed / undo.ai_move.duration_ms as f64).min(1.0);
-            let t = crate::utils::ease_out_quad(progress as f32);
-
-            let current_pos = egui::Pos2::new(
-                crate::utils::lerp(undo.ai_move.from.x, undo.ai_move.to.x, t),
-                crate::utils::lerp(undo.ai_move.from.y, undo.ai_move.to.y, t),
-            );
+            // AI棋子回退动画（之后的阶段停在回退后的位置）
+            let t = if step == UndoStep::AiUndoing { undo.sequence.stage_value() } else { 1.0 };
+            let current_pos = crate::utils::lerp_pos2(undo.ai_move.from, undo.ai_move.to, t);
 
             view.draw_animated_piece(ui, piece, current_pos);
         } else if is_captured_piece {
             // 被吃棋子的动画
-            match undo.step {
+            match step {
                 UndoStep::AiUndoing => {
                     // 渐显
-                    let elapsed = undo.ai_move.start_time.elapsed().as_millis() as f64;
-                    let progress = (elapsed / undo.ai_move.duration_ms as f64).min(1.0);
-                    let alpha = (progress * 255.0) as u8;
+                    let alpha = (undo.sequence.stage_progress() * 255.0) as u8;
 
                     if let Some(ref captured) = undo.captured_piece {
                         view.draw_piece_with_alpha(ui, piece, captured.screen_pos, alpha);
@@ -999,17 +2956,9 @@ impl MainApp {
                 }
                 UndoStep::CapturedReturning => {
                     // 回退
-                    let ai_end = undo.ai_move.start_time + Duration::from_millis(undo.ai_move.duration_ms);
-                    let elapsed = std::time::Instant::now().duration_since(ai_end).as_millis() as f64;
-                    let progress = (elapsed / UNDO_STEP_DURATION_MS as f64).min(1.0);
-                    let t = crate::utils::ease_out_quad(progress as f32);
-
                     if let Some(ref captured) = undo.captured_piece {
                         let target_pos = view.board_to_screen(undo.player_record.from);
-                        let current_pos = egui::Pos2::new(
-                            crate::utils::lerp(captured.screen_pos.x, target_pos.x, t),
-                            crate::utils::lerp(captured.screen_pos.y, target_pos.y, t),
-                        );
+                        let current_pos = crate::utils::lerp_pos2(captured.screen_pos, target_pos, undo.sequence.stage_value());
                         view.draw_animated_piece(ui, piece, current_pos);
                     }
                 }
@@ -1017,16 +2966,9 @@ impl MainApp {
                     view.draw_piece(ui, piece, false);
                 }
             }
-        } else if is_player_piece && matches!(undo.step, UndoStep::PlayerUndoing) {
+        } else if is_player_piece && step == UndoStep::PlayerUndoing {
             // 玩家棋子回退动画
-            let elapsed = undo.player_move.start_time.elapsed().as_millis() as f64;
-            let progress = (elapsed / undo.player_move.duration_ms as f64).min(1.0);
-            let t = crate::utils::ease_out_quad(progress as f32);
-
-            let current_pos = egui::Pos2::new(
-                crate::utils::lerp(undo.player_move.from.x, undo.player_move.to.x, t),
-                crate::utils::lerp(undo.player_move.from.y, undo.player_move.to.y, t),
-            );
+            let current_pos = crate::utils::lerp_pos2(undo.player_move.from, undo.player_move.to, undo.sequence.stage_value());
 
             view.draw_animated_piece(ui, piece, current_pos);
         } else {
@@ -1041,23 +2983,20 @@ impl MainApp {
 
             match anim.stage {
                 CaptureStage::Flashing => {
-                    // 闪烁阶段
-                    let flash_count = 3;
-                    let flash_duration = CAPTURE_FLASH_DURATION_MS / flash_count;
-                    let flash_progress = (elapsed % flash_duration) as f32 / flash_duration as f32;
-                    let visible = flash_progress < 0.5;
-
-                    if visible {
-                        for &piece_id in &anim.piece_ids {
-                            if let Some(piece) = self.game.board.piece_by_id(piece_id) {
-                                view.draw_piece(ui, piece, false);
-                            }
+                    // 闪烁阶段：不透明度按余弦曲线平滑变化
+                    let progress = (elapsed as f32 / anim.flash_ms.max(1) as f32).min(1.0);
+                    let alpha = flash_alpha(progress, anim.flash_count, anim.flash_min_alpha);
+
+                    for &piece_id in &anim.piece_ids {
+                        if let Some(piece) = self.game.board.piece_by_id(piece_id) {
+                            let pos = view.board_to_screen(piece.position);
+                            view.draw_piece_with_alpha(ui, piece, pos, (alpha * 255.0).round() as u8);
                         }
                     }
                 }
                 CaptureStage::Removing => {
                     // 移除阶段
-                    let progress = (elapsed as f32 / CAPTURE_REMOVE_DURATION_MS as f32).min(1.0);
+                    let progress = (elapsed as f32 / anim.remove_ms.max(1) as f32).min(1.0);
 
                     for &piece_id in &anim.piece_ids {
                         if let Some(piece) = self.game.board.piece_by_id(piece_id) {
@@ -1071,50 +3010,245 @@ impl MainApp {
 }
 
 impl eframe::App for MainApp {
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // 保存窗口位置与大小，下次启动时恢复
+        if let Some((pos, size)) = self.window_geometry {
+            self.game.settings.window_pos = Some([pos.x, pos.y]);
+            self.game.settings.window_size = Some([size.x, size.y]);
+            self.save_settings();
+        }
+    }
+
     fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+        self.account_pause();
+        if let Some(result) = self.game.tick_clock(self.clock_paused()) {
+            self.handle_flag_fall(result);
+        }
+        self.update_thinking_indicator();
+        self.track_window_geometry(ctx);
+
         // 处理菜单（根据当前状态决定是否可操作）
         self.handle_menu(ctx);
         self.handle_toolbar(ctx);
-
-        // 处理对话框
-        self.handle_new_game_dialog(ctx);
-        self.handle_game_over_dialog(ctx);
+        self.handle_replay_bar(ctx);
+        self.handle_status_bar(ctx);
+        self.handle_captured_tray(ctx);
+        self.handle_editor_panel(ctx);
+
+        // 处理对话框（回放与局面编辑模式下不显示对局相关对话框）
+        if self.showing_game() {
+            self.handle_new_game_dialog(ctx);
+            self.handle_game_over_dialog(ctx);
+            self.handle_save_slots_dialog(ctx);
+            self.handle_demo_dialog(ctx);
+            self.handle_demo_over_dialog(ctx);
+        }
         self.about_dialog.show(ctx);
         self.rules_dialog.show(ctx);
+        if self.shortcuts_dialog.show(ctx, &mut self.game.settings.shortcuts) {
+            self.save_settings();
+        }
 
         // 处理加载确认对话框
         if self.pending_load_file.is_some() {
             self.show_confirm_load_dialog(ctx);
         }
 
+        // 处理恢复上一局对话框
+        if self.pending_resume.is_some() {
+            self.show_resume_dialog(ctx);
+        }
+
         // 处理覆盖确认对话框
         if self.confirm_overwrite {
             self.show_confirm_overwrite_dialog(ctx);
         }
 
-        // 处理AI回合
-        if matches!(self.game.state, GameState::AiThinking) {
-            self.handle_ai_turn();
+        // 处理认输确认对话框
+        if self.confirm_resign {
+            self.show_confirm_resign_dialog(ctx);
         }
 
-        // 处理状态流转
-        self.process_state_transitions();
+        self.show_paste_dialog(ctx);
 
-        // 更新动画
-        self.update_animations();
+        // 处理拒绝提和提示框
+        if self.draw_declined {
+            self.show_draw_declined_dialog(ctx);
+        }
+
+        self.show_move_hint(ctx);
+        self.show_confirm_move_bar(ctx);
+
+        // 处理错误提示框
+        if self.pending_error.is_some() {
+            self.show_error_dialog(ctx);
+        }
+
+        // 处理新局确认对话框
+        if self.confirm_new_game {
+            self.show_confirm_new_game_dialog(ctx);
+        }
+
+        self.advance_frame();
 
         // 主面板
         CentralPanel::default().show(ctx, |ui| {
-            self.render_game(ui);
+            if self.replay.is_some() {
+                self.render_replay(ui);
+            } else if self.editor.is_some() {
+                self.render_editor(ui);
+            } else {
+                self.render_game(ui);
+            }
         });
 
-        // 请求连续更新以支持动画
-        if self.has_active_animation()
+        // 请求连续更新以支持动画（暂停期间画面静止，无需重绘）
+        let animating = self.has_active_animation()
+            || self.replay.as_ref().is_some_and(|r| r.step_anim.is_some())
             || matches!(self.game.state, GameState::AiThinking)
             || matches!(self.game.state, GameState::CheckingCapture)
-            || matches!(self.game.state, GameState::CheckingGameEnd)
-        {
+            || matches!(self.game.state, GameState::CheckingGameEnd);
+        if self.is_paused() {
+            // 暂停期间不推进任何计时
+        } else if animating {
             ctx.request_repaint();
+        } else if self.clocks_visible() && self.game.clock.running().is_some() {
+            // 计时显示精确到秒，无需每帧重绘
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reduced_motion_animations_complete_immediately() {
+        let (from, to) = (egui::pos2(0.0, 0.0), egui::pos2(100.0, 0.0));
+        let mut settings = GameSettings::default();
+        assert!(PieceMoveAnimation::new(1, from, to, PIECE_MOVE_DURATION_MS, false, &settings).duration_ms > 0);
+        let capture = CaptureAnimation::new(vec![5], &settings);
+        assert!(capture.flash_ms > 0 && capture.remove_ms > 0);
+        assert!(undo_step_duration(&settings) > 0);
+
+        settings.reduce_motion = true;
+        for base_ms in [PIECE_MOVE_DURATION_MS, PIECE_RETURN_DURATION_MS] {
+            assert_eq!(PieceMoveAnimation::new(1, from, to, base_ms, true, &settings).duration_ms, 0);
+        }
+        let capture = CaptureAnimation::new(vec![5], &settings);
+        assert_eq!((capture.flash_ms, capture.remove_ms), (0, 0));
+        assert_eq!(undo_step_duration(&settings), 0);
+
+        // 减慢动画速度不会让减少动画的时长重新变长
+        settings.animation_speed = MIN_ANIMATION_SPEED;
+        assert_eq!(PieceMoveAnimation::new(1, from, to, PIECE_MOVE_DURATION_MS, false, &settings).duration_ms, 0);
+    }
+
+    /// 测试用应用：电脑对弈的演示对局（不写自动存档），带棋盘视图以创建动画
+    fn demo_app(notation: &str, side_to_move: Side) -> MainApp {
+        let mut game = Game::new();
+        game.start_demo();
+        game.board = crate::game::board::Board::from_notation(notation).unwrap();
+        game.current_turn = side_to_move;
+        game.settings.small_material_draw = false;
+        game.settings.animation_speed = MAX_ANIMATION_SPEED;
+        let mut app = MainApp::with_game(game, SoundPlayer::silent());
+        let ctx = Context::default();
+        app.board_view = Some(BoardView::new(egui::pos2(250.0, 250.0), 500.0, 4, false, Theme::Wood, &ctx));
+        app
+    }
+
+    /// 按帧推进动画与状态流转，直到 `done` 成立（最多约两秒）
+    fn run_frames(app: &mut MainApp, done: impl Fn(&MainApp) -> bool) -> Vec<GameState> {
+        let mut states = vec![app.game.state];
+        for _ in 0..400 {
+            if done(app) {
+                return states;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+            app.update_animations();
+            app.process_state_transitions();
+            if states.last() != Some(&app.game.state) {
+                states.push(app.game.state);
+            }
+        }
+        panic!("动画未按时完成，停在 {:?}", app.game.state);
+    }
+
+    #[test]
+    fn skipping_ai_animations_matches_the_full_animation() {
+        // 白方 (1,2)→(1,1) 与 (0,1) 二比一吃掉 (2,1) 的黑子，经过移动与吃子两段动画
+        let notation = "..BB/.W../W.B./B...";
+        let (from, to) = ((1, 2), (1, 1));
+
+        // 完整动画：按帧推进到轮到黑方
+        let mut full = demo_app(notation, Side::White);
+        full.start_ai_move(from, to);
+        let states = run_frames(&mut full, |app| app.game.state == GameState::AiThinking);
+        assert_eq!(states, [GameState::PieceMoving, GameState::CaptureAnimating, GameState::AiThinking]);
+
+        // 跳过动画：在吃子动画中途跳过，剩余的完成事件在同一次调用中发出
+        let mut skipped = demo_app(notation, Side::White);
+        skipped.start_ai_move(from, to);
+        run_frames(&mut skipped, |app| app.game.state == GameState::CaptureAnimating);
+        assert!(skipped.animations.capture.is_some());
+        assert!(skipped.can_skip_ai_animation());
+        skipped.skip_ai_animations();
+        assert_eq!(skipped.game.state, GameState::AiThinking);
+        assert!(!skipped.has_active_animation());
+
+        // 与完整动画的结果一致
+        assert_eq!(skipped.game.board.pieces, full.game.board.pieces);
+        assert_eq!(skipped.game.move_history, full.game.move_history);
+        assert_eq!(skipped.game.current_turn, full.game.current_turn);
+        assert!(skipped.game.board.piece_at(2, 1).is_none());
+
+        // 再次调用或之后的帧都不会改变局面
+        skipped.skip_ai_animations();
+        skipped.update_animations();
+        skipped.process_state_transitions();
+        assert_eq!(skipped.game.board.pieces, full.game.board.pieces);
+        assert_eq!(skipped.game.state, GameState::AiThinking);
+    }
+
+    #[test]
+    fn demo_step_plays_exactly_one_move() {
+        let mut app = demo_app("WWWW/W..W/B..B/BBBB", Side::Black);
+        app.game.settings.reduce_motion = true;
+        app.demo_stepper.enabled = true;
+
+        // 逐帧推进；电脑的最短思考时间视为已过
+        let run = |app: &mut MainApp| {
+            for _ in 0..10 {
+                app.ai_think_start = Instant::now().checked_sub(Duration::from_millis(AI_MIN_THINKING_TIME_MS));
+                app.advance_frame();
+            }
+        };
+
+        // 未请求时不走棋
+        run(&mut app);
+        assert!(app.game.move_history.is_empty());
+        assert_eq!(app.game.state, GameState::AiThinking);
+
+        // 每次请求恰好走一步，走完后等待下一次请求
+        for moves in 1..=3 {
+            app.demo_stepper.request();
+            run(&mut app);
+            assert_eq!(app.game.move_history.len(), moves);
+            assert_eq!(app.game.state, GameState::AiThinking);
+            assert!(!app.demo_stepper.requested);
         }
+
+        // 清除的请求不会走棋
+        app.demo_stepper.request();
+        app.demo_stepper.clear();
+        run(&mut app);
+        assert_eq!(app.game.move_history.len(), 3);
+
+        // 关闭单步模式后自动连续走棋
+        app.demo_stepper.enabled = false;
+        run(&mut app);
+        assert!(app.game.move_history.len() > 3 || app.game.last_result.is_some());
     }
 }
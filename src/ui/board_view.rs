@@ -2,8 +2,10 @@
 
 use egui::{Color32, Pos2, Rect, Response, Rounding, Sense, Stroke, Ui, Vec2, Image, TextureHandle, Context};
 
-use crate::game::board::BOARD_SIZE;
+use crate::game::board::Board;
 use crate::game::piece::{Piece, Side};
+use crate::game::settings::{Theme, MAX_DROP_TOLERANCE};
+use crate::ui::theme::{with_alpha, ThemePalette};
 use std::sync::Arc;
 
 /// 棋子图片资源（96x96 像素，按100%原大小显示）
@@ -13,12 +15,37 @@ const WHITE_STONE_PNG: &[u8] = include_bytes!("../assets/images/white_stone.png"
 /// 棋盘背景图（木纹背景）
 const BOARD_BG_PNG: &[u8] = include_bytes!("../assets/images/board_bg.png");
 
-/// 棋子图片尺寸
-const STONE_SIZE: f32 = 96.0;
+/// 棋子直径与格子大小之比（4路棋盘、500像素时恰为图片原大小96像素）
+const STONE_CELL_RATIO: f32 = 0.72;
+
+/// 棋子显示直径的下限（保证小窗口中仍便于点击）与上限（图片原大小的两倍，避免放大后模糊）
+const MIN_STONE_SIZE: f32 = 12.0;
+const MAX_STONE_SIZE: f32 = 192.0;
+
+/// 棋子纹理的采样方式：线性过滤加 mipmap，缩小显示时边缘平滑
+const STONE_TEXTURE_OPTIONS: egui::TextureOptions = egui::TextureOptions {
+    mipmap_mode: Some(egui::TextureFilter::Linear),
+    ..egui::TextureOptions::LINEAR
+};
 
 /// 棋盘边距比例（线条与边缘的距离）
 const BOARD_MARGIN_RATIO: f32 = 0.1; // 10% 边距
 
+/// 坐标标签与最外侧棋子之间的间隙
+const COORD_LABEL_GAP: f32 = 12.0;
+
+/// 坐标标签字号
+const COORD_LABEL_FONT_SIZE: f32 = 16.0;
+
+/// 淡色目标点提示的透明度
+const FAINT_HINT_ALPHA: u8 = 90;
+
+/// 不可移动棋子变暗后的透明度
+pub const DIMMED_PIECE_ALPHA: u8 = 110;
+
+/// 调试用棋子ID标签的字号（相对棋子半径）
+const PIECE_ID_FONT_RATIO: f32 = 0.6;
+
 /// 棋盘视图
 #[derive(Clone)]
 pub struct BoardView {
@@ -26,10 +53,16 @@ pub struct BoardView {
     pub rect: Rect,
     /// 格子大小
     pub cell_size: f32,
+    /// 棋盘每边交叉点数
+    pub dimension: u8,
     /// 棋子半径（用于点击检测）
     pub piece_radius: f32,
+    /// 棋子显示尺寸
+    stone_size: f32,
     /// 是否翻转棋盘（玩家执白时翻转，使白棋在下方）
     pub flip: bool,
+    /// 是否显示坐标标签
+    pub show_coordinates: bool,
     /// 黑子纹理
     black_stone: Option<Arc<TextureHandle>>,
     /// 白子纹理
@@ -38,6 +71,8 @@ pub struct BoardView {
     board_texture: Option<Arc<TextureHandle>>,
     /// 棋盘边距（线条与边缘的距离）
     board_margin: f32,
+    /// 主题调色板
+    palette: ThemePalette,
 }
 
 impl BoardView {
@@ -46,21 +81,24 @@ impl BoardView {
     /// # Arguments
     /// * `center` - 棋盘中心点
     /// * `size` - 棋盘大小
+    /// * `dimension` - 棋盘每边交叉点数（与 `Board::size` 一致）
     /// * `flip` - 是否翻转棋盘（玩家执白时为true，使玩家棋子在下方）
+    /// * `theme` - 棋盘主题
     /// * `ctx` - egui 上下文，用于加载纹理
-    pub fn new(center: Pos2, size: f32, flip: bool, ctx: &Context) -> Self {
+    pub fn new(center: Pos2, size: f32, dimension: u8, flip: bool, theme: Theme, ctx: &Context) -> Self {
         let _half = size / 2.0;
         let rect = Rect::from_center_size(center, Vec2::new(size, size));
 
         // 棋盘边距（线条与边缘的距离）
         let board_margin = size * BOARD_MARGIN_RATIO;
-        // 内部区域大小（用于放置4x4交叉点）
+        // 内部区域大小（用于放置NxN交叉点）
         let inner_size = size - 2.0 * board_margin;
-        // 3x3格子，4x4交叉点，格子大小为内部区域 / 3
-        let cell_size = inner_size / (BOARD_SIZE - 1) as f32;
+        // (N-1)x(N-1)格子，NxN交叉点，格子大小为内部区域 / (N-1)
+        let cell_size = inner_size / (dimension - 1) as f32;
 
-        // 棋子点击检测半径使用图片尺寸的一半
-        let piece_radius = STONE_SIZE / 2.0;
+        // 棋子随格子大小缩放，点击检测半径使用显示尺寸的一半
+        let stone_size = stone_size_for(cell_size);
+        let piece_radius = stone_size / 2.0;
 
         // 加载棋子图片纹理
         let black_stone = Self::load_stone_texture(ctx, BLACK_STONE_PNG, "black_stone");
@@ -71,12 +109,16 @@ impl BoardView {
         Self {
             rect,
             cell_size,
+            dimension,
             piece_radius,
+            stone_size,
             flip,
+            show_coordinates: false,
             black_stone,
             white_stone,
             board_texture,
             board_margin,
+            palette: ThemePalette::of(theme),
         }
     }
 
@@ -90,11 +132,11 @@ impl BoardView {
                 let pixels = image.as_raw();
 
                 let color_image = egui::ColorImage::from_rgba_unmultiplied(size, pixels);
-                let texture = ctx.load_texture(name, color_image, egui::TextureOptions::default());
+                let texture = ctx.load_texture(name, color_image, STONE_TEXTURE_OPTIONS);
                 Some(Arc::new(texture))
             }
             Err(e) => {
-                eprintln!("Failed to load stone texture '{}': {}", name, e);
+                crate::log_warn!("Failed to load stone texture '{}': {}", name, e);
                 None
             }
         }
@@ -105,19 +147,22 @@ impl BoardView {
         let response = ui.allocate_rect(self.rect, Sense::click_and_drag());
 
         // 绘制棋盘背景图
-        if let Some(ref texture) = self.board_texture {
-            let image = Image::from_texture(texture.as_ref())
-                .fit_to_exact_size(self.rect.size());
-            ui.put(self.rect, image);
-        } else {
-            // 如果图片加载失败，使用纯色背景
-            let painter = ui.painter();
-            painter.rect_filled(self.rect, Rounding::ZERO, Color32::from_rgb(240, 217, 181));
+        match self.board_texture {
+            Some(ref texture) if self.palette.use_board_texture => {
+                let image = Image::from_texture(texture.as_ref())
+                    .fit_to_exact_size(self.rect.size());
+                ui.put(self.rect, image);
+            }
+            _ => {
+                // 主题不使用背景图或图片加载失败，使用纯色背景
+                let painter = ui.painter();
+                painter.rect_filled(self.rect, Rounding::ZERO, self.palette.board_background);
+            }
         }
 
         // 绘制网格线（带边距，使线条在棋盘内部）
         let painter = ui.painter();
-        let stroke = Stroke::new(2.5, Color32::from_rgb(60, 40, 20));
+        let stroke = Stroke::new(self.palette.grid_width, self.palette.grid_color);
 
         // 计算线条起始和结束位置（带边距）
         let start_x = self.rect.min.x + self.board_margin;
@@ -125,8 +170,8 @@ impl BoardView {
         let start_y = self.rect.min.y + self.board_margin;
         let end_y = self.rect.max.y - self.board_margin;
 
-        // 横线 (4条，i=0,1,2,3)
-        for i in 0..BOARD_SIZE {
+        // 横线 (N条)
+        for i in 0..self.dimension {
             let y = start_y + i as f32 * self.cell_size;
             painter.line_segment(
                 [Pos2::new(start_x, y), Pos2::new(end_x, y)],
@@ -134,8 +179,8 @@ impl BoardView {
             );
         }
 
-        // 纵线 (4条，i=0,1,2,3)
-        for i in 0..BOARD_SIZE {
+        // 纵线 (N条)
+        for i in 0..self.dimension {
             let x = start_x + i as f32 * self.cell_size;
             painter.line_segment(
                 [Pos2::new(x, start_y), Pos2::new(x, end_y)],
@@ -143,9 +188,49 @@ impl BoardView {
             );
         }
 
+        if self.show_coordinates {
+            self.draw_coordinates(ui);
+        }
+
         response
     }
 
+    /// 绘制坐标标签：底部为列（A-D），左侧为行（1-4）
+    ///
+    /// 标签位于网格外侧，距最外侧网格线的距离取边距与棋子半径中的较大者，
+    /// 以免与边线上的棋子重叠；通过 board_to_screen 计算位置，自动适配翻转
+    fn draw_coordinates(&self, ui: &mut Ui) {
+        let painter = ui.painter();
+        let font = egui::FontId::proportional(COORD_LABEL_FONT_SIZE);
+        let color = ui.visuals().text_color();
+        let offset = self.board_margin.max(self.piece_radius) + COORD_LABEL_GAP;
+
+        let bottom = self.rect.max.y - self.board_margin;
+        let left = self.rect.min.x + self.board_margin;
+
+        for i in 0..self.dimension {
+            // 列标签
+            let x = self.board_to_screen((i, 0)).x;
+            painter.text(
+                Pos2::new(x, bottom + offset),
+                egui::Align2::CENTER_CENTER,
+                char::from(b'A' + i),
+                font.clone(),
+                color,
+            );
+
+            // 行标签
+            let y = self.board_to_screen((0, i)).y;
+            painter.text(
+                Pos2::new(left - offset, y),
+                egui::Align2::CENTER_CENTER,
+                (i + 1).to_string(),
+                font.clone(),
+                color,
+            );
+        }
+    }
+
     /// 渲染单个棋子（使用图片，100%原大小显示）
     ///
     /// # Arguments
@@ -163,8 +248,8 @@ impl BoardView {
         };
 
         if let Some(texture) = texture {
-            // 图片按100%原大小显示，居中于交叉点
-            let image_size = Vec2::new(STONE_SIZE, STONE_SIZE);
+            // 图片按棋盘比例缩放，居中于交叉点
+            let image_size = Vec2::splat(self.stone_size);
             let image_rect = Rect::from_center_size(pos, image_size);
 
             // 绘制棋子图片
@@ -175,14 +260,46 @@ impl BoardView {
         } else {
             // 如果图片加载失败，回退到代码绘制
             let painter = ui.painter();
-            let color = match piece.side {
-                Side::Black => Color32::from_rgb(30, 30, 30),
-                Side::White => Color32::from_rgb(240, 240, 240),
-            };
+            let color = self.palette.piece_color(piece.side);
             painter.circle_filled(pos, self.piece_radius, color);
         }
     }
 
+    /// 在棋子上绘制其ID（调试用，便于对照行棋记录与吃子日志）
+    pub fn draw_piece_id(&self, ui: &mut Ui, piece: &Piece) {
+        let color = match piece.side {
+            Side::Black => Color32::WHITE,
+            Side::White => Color32::BLACK,
+        };
+        ui.painter().text(
+            self.board_to_screen(piece.position),
+            egui::Align2::CENTER_CENTER,
+            piece.id.to_string(),
+            egui::FontId::monospace(self.piece_radius * PIECE_ID_FONT_RATIO),
+            color,
+        );
+    }
+
+    /// 在当前布局位置绘制一枚指定大小的棋子（用于吃子盘等棋盘外区域）
+    pub fn draw_stone_icon(&self, ui: &mut Ui, side: Side, size: f32) {
+        let (rect, _) = ui.allocate_exact_size(Vec2::splat(size), Sense::hover());
+
+        let texture = match side {
+            Side::Black => self.black_stone.as_ref(),
+            Side::White => self.white_stone.as_ref(),
+        };
+
+        if let Some(texture) = texture {
+            let image = Image::from_texture(texture.as_ref())
+                .fit_to_exact_size(rect.size());
+            ui.put(rect, image);
+        } else {
+            let painter = ui.painter();
+            painter.circle_filled(rect.center(), size / 2.0, self.palette.piece_color(side));
+            painter.circle_stroke(rect.center(), size / 2.0, Stroke::new(1.0, self.palette.piece_outline(side)));
+        }
+    }
+
     /// 将棋盘坐标转换为屏幕坐标
     ///
     /// 棋子放在交叉点上（线的交点），考虑边距
@@ -190,7 +307,7 @@ impl BoardView {
     pub fn board_to_screen(&self, pos: (u8, u8)) -> Pos2 {
         let (bx, by) = if self.flip {
             // 翻转：x镜像，y镜像
-            (BOARD_SIZE as u8 - 1 - pos.0, BOARD_SIZE as u8 - 1 - pos.1)
+            (self.dimension - 1 - pos.0, self.dimension - 1 - pos.1)
         } else {
             // 正常：黑棋在下方
             pos
@@ -211,7 +328,7 @@ impl BoardView {
         let rel_x = pos.x - self.rect.min.x - self.board_margin;
         let rel_y = self.rect.max.y - pos.y - self.board_margin;
 
-        // 计算最近的交叉点索引（0-3）
+        // 计算最近的交叉点索引
         let board_x = (rel_x / self.cell_size).round() as i32;
         let board_y = (rel_y / self.cell_size).round() as i32;
 
@@ -221,15 +338,16 @@ impl BoardView {
 
         let dist_x = (rel_x - cross_x).abs();
         let dist_y = (rel_y - cross_y).abs();
-        let max_dist = self.cell_size * tolerance;
+        // 容差超过半个格距会与相邻交叉点重叠，按半格截断
+        let max_dist = self.cell_size * tolerance.min(MAX_DROP_TOLERANCE);
 
         if dist_x <= max_dist && dist_y <= max_dist {
-            if board_x >= 0 && board_x < BOARD_SIZE as i32
-                && board_y >= 0 && board_y < BOARD_SIZE as i32 {
+            let dimension = self.dimension as i32;
+            if (0..dimension).contains(&board_x) && (0..dimension).contains(&board_y) {
                 let (bx, by) = (board_x as u8, board_y as u8);
                 // 如果翻转，需要转换回原始棋盘坐标
                 if self.flip {
-                    Some((BOARD_SIZE as u8 - 1 - bx, BOARD_SIZE as u8 - 1 - by))
+                    Some((self.dimension - 1 - bx, self.dimension - 1 - by))
                 } else {
                     Some((bx, by))
                 }
@@ -257,8 +375,8 @@ impl BoardView {
         };
 
         if let Some(texture) = texture {
-            // 图片按100%原大小显示，居中于当前动画位置
-            let image_size = Vec2::new(STONE_SIZE, STONE_SIZE);
+            // 图片按棋盘比例缩放，居中于当前动画位置
+            let image_size = Vec2::splat(self.stone_size);
             let image_rect = Rect::from_center_size(current_pos, image_size);
 
             // 绘制棋子图片
@@ -269,10 +387,7 @@ impl BoardView {
         } else {
             // 如果图片加载失败，回退到代码绘制
             let painter = ui.painter();
-            let color = match piece.side {
-                Side::Black => Color32::from_rgb(30, 30, 30),
-                Side::White => Color32::from_rgb(240, 240, 240),
-            };
+            let color = self.palette.piece_color(piece.side);
             painter.circle_filled(current_pos, self.piece_radius, color);
         }
     }
@@ -280,7 +395,7 @@ impl BoardView {
     /// 绘制被吃棋子动画（缩小淡出）
     pub fn draw_capturing_piece(&self, ui: &mut Ui, piece: &Piece, progress: f32) {
         let alpha = ((1.0 - progress) * 255.0) as u8;
-        let size = STONE_SIZE * (1.0 - progress);
+        let size = self.stone_size * (1.0 - progress);
         let pos = self.board_to_screen(piece.position);
 
         // 获取对应的棋子纹理
@@ -308,10 +423,7 @@ impl BoardView {
             // 如果图片加载失败，回退到代码绘制
             let painter = ui.painter();
             let radius = self.piece_radius * (1.0 - progress);
-            let color = match piece.side {
-                Side::Black => Color32::from_rgba_premultiplied(30, 30, 30, alpha),
-                Side::White => Color32::from_rgba_premultiplied(240, 240, 240, alpha),
-            };
+            let color = with_alpha(self.palette.piece_color(piece.side), alpha);
             painter.circle_filled(pos, radius, color);
         }
     }
@@ -326,7 +438,7 @@ impl BoardView {
 
         if let Some(texture) = texture {
             // 使用图片，应用透明度
-            let image_size = Vec2::new(STONE_SIZE, STONE_SIZE);
+            let image_size = Vec2::splat(self.stone_size);
             let image_rect = Rect::from_center_size(pos, image_size);
 
             let tint = Color32::from_rgba_premultiplied(255, 255, 255, alpha);
@@ -339,16 +451,10 @@ impl BoardView {
         } else {
             // 如果图片加载失败，回退到代码绘制
             let painter = ui.painter();
-            let color = match piece.side {
-                Side::Black => Color32::from_rgba_premultiplied(30, 30, 30, alpha),
-                Side::White => Color32::from_rgba_premultiplied(240, 240, 240, alpha),
-            };
+            let color = with_alpha(self.palette.piece_color(piece.side), alpha);
 
             let stroke_color = if alpha > 100 {
-                match piece.side {
-                    Side::Black => Color32::from_rgba_premultiplied(80, 80, 80, alpha),
-                    Side::White => Color32::from_rgba_premultiplied(180, 180, 180, alpha),
-                }
+                with_alpha(self.palette.piece_outline(piece.side), alpha)
             } else {
                 Color32::TRANSPARENT
             };
@@ -369,11 +475,7 @@ impl BoardView {
         let screen_pos = self.board_to_screen(pos);
         let radius = self.piece_radius * 0.3;
 
-        painter.circle_filled(
-            screen_pos,
-            radius,
-            Color32::from_rgba_premultiplied(100, 200, 100, 150),
-        );
+        painter.circle_filled(screen_pos, radius, self.palette.hint_color);
     }
 
     /// 绘制选中棋子的高亮效果
@@ -383,19 +485,68 @@ impl BoardView {
 
         // 绘制外圈光晕效果
         let ring_outer_radius = self.piece_radius * 1.02;
-        let color = Color32::from_rgba_unmultiplied(192, 192, 192, 128);
-        painter.circle_filled(screen_pos, ring_outer_radius, color);
+        painter.circle_filled(screen_pos, ring_outer_radius, self.palette.selection_color);
+    }
+
+    /// 绘制键盘光标（交叉点外圈的方框）
+    pub fn draw_cursor(&self, ui: &mut Ui, pos: (u8, u8)) {
+        let painter = ui.painter();
+        let rect = Rect::from_center_size(self.board_to_screen(pos), Vec2::splat(self.piece_radius * 2.2));
+        painter.rect_stroke(rect, Rounding::same(6.0), Stroke::new(3.0, self.palette.selection_color));
+    }
+
+    /// 绘制最近一步的起点与终点标记（半透明方块）
+    pub fn draw_last_move_markers(&self, ui: &mut Ui, from: (u8, u8), to: (u8, u8)) {
+        let painter = ui.painter();
+        let size = Vec2::splat(self.cell_size * 0.5);
+        let color = self.palette.last_move_color;
+
+        for pos in [from, to] {
+            let rect = Rect::from_center_size(self.board_to_screen(pos), size);
+            painter.rect_filled(rect, Rounding::same(4.0), color);
+        }
+    }
+
+    /// 绘制失误走法标记：从起点指向终点的箭头，起点加圈
+    pub fn draw_blunder_marker(&self, ui: &mut Ui, from: (u8, u8), to: (u8, u8)) {
+        let painter = ui.painter();
+        let stroke = Stroke::new(4.0, self.palette.capture_preview_color);
+        let start = self.board_to_screen(from);
+        let end = self.board_to_screen(to);
+
+        painter.circle_stroke(start, self.piece_radius * 1.1, stroke);
+        painter.arrow(start, (end - start) * 0.8, stroke);
+    }
+
+    /// 绘制吃子预览标记（将被吃掉的棋子外圈加叉）
+    pub fn draw_capture_preview(&self, ui: &mut Ui, pos: (u8, u8)) {
+        let painter = ui.painter();
+        let center = self.board_to_screen(pos);
+        let stroke = Stroke::new(3.0, self.palette.capture_preview_color);
+
+        painter.circle_stroke(center, self.piece_radius * 1.05, stroke);
+        let arm = self.piece_radius * 0.35;
+        painter.line_segment([center + Vec2::new(-arm, -arm), center + Vec2::new(arm, arm)], stroke);
+        painter.line_segment([center + Vec2::new(-arm, arm), center + Vec2::new(arm, -arm)], stroke);
     }
 
     /// 绘制合法目标点标注
     /// 使用醒目的绿色标注合法目标点
     pub fn draw_valid_move_hints(&self, ui: &mut Ui, valid_moves: &[(u8, u8)]) {
+        self.draw_move_hints(ui, valid_moves, self.palette.hint_color);
+    }
+
+    /// 绘制淡色的合法目标点标注（用于显示全部可走位置）
+    pub fn draw_faint_move_hints(&self, ui: &mut Ui, valid_moves: &[(u8, u8)]) {
+        self.draw_move_hints(ui, valid_moves, with_alpha(self.palette.hint_color, FAINT_HINT_ALPHA));
+    }
+
+    fn draw_move_hints(&self, ui: &mut Ui, valid_moves: &[(u8, u8)], color: Color32) {
         let painter = ui.painter();
 
         for pos in valid_moves {
             let screen_pos = self.board_to_screen(*pos);
-            let color = Color32::from_rgba_unmultiplied(0, 128, 0, 64);
-            // 绘制绿色圆点表示合法目标点
+            // 绘制圆点表示合法目标点
             painter.circle_filled(
                 screen_pos,
                 self.cell_size * 0.15, // 稍大的圆点
@@ -411,3 +562,247 @@ impl BoardView {
         }
     }
 }
+
+/// 按屏幕方向移动键盘光标
+///
+/// `dir` 为屏幕方向（x 向右为正，y 向上为正），棋盘翻转时换算为相反的棋盘方向；
+/// 结果限制在棋盘范围内
+pub fn step_cursor(pos: (u8, u8), dir: (i8, i8), size: u8, flip: bool) -> (u8, u8) {
+    let (dx, dy) = if flip { (-dir.0, -dir.1) } else { dir };
+    let step = |v: u8, d: i8| (v as i16 + d as i16).clamp(0, size as i16 - 1) as u8;
+    (step(pos.0, dx), step(pos.1, dy))
+}
+
+/// 导出图片的边长（像素，与界面中棋盘的最大尺寸一致）
+pub const EXPORT_IMAGE_SIZE: u32 = 500;
+
+/// 格子大小对应的棋子显示直径
+///
+/// 按比例缩放后限制在 [`MIN_STONE_SIZE`] 到 [`MAX_STONE_SIZE`] 之间，且不超过格子大小（相邻棋子不重叠）
+pub fn stone_size_for(cell_size: f32) -> f32 {
+    (cell_size * STONE_CELL_RATIO).clamp(MIN_STONE_SIZE, MAX_STONE_SIZE).min(cell_size)
+}
+
+/// 将当前局面渲染为图片（背景、网格线与在盘棋子），不依赖 egui
+///
+/// 布局与 [`BoardView`] 一致：同样的边距比例、翻转规则和棋子与格子的比例
+pub fn render_board_image(board: &Board, flip: bool, theme: Theme) -> image::RgbaImage {
+    use image::imageops::{self, FilterType};
+
+    let palette = ThemePalette::of(theme);
+    let size = EXPORT_IMAGE_SIZE;
+    let dimension = board.size;
+    let margin = size as f32 * BOARD_MARGIN_RATIO;
+    let cell_size = (size as f32 - 2.0 * margin) / (dimension - 1) as f32;
+
+    // 背景：木纹图片缩放到图片大小，或主题纯色
+    let background = palette
+        .use_board_texture
+        .then(|| image::load_from_memory(BOARD_BG_PNG).ok())
+        .flatten();
+    let mut canvas = match background {
+        Some(bg) => imageops::resize(&bg.to_rgba8(), size, size, FilterType::Triangle),
+        None => image::RgbaImage::from_pixel(size, size, to_rgba(palette.board_background)),
+    };
+
+    // 网格线
+    let grid = to_rgba(palette.grid_color);
+    let half_width = (palette.grid_width / 2.0).max(0.5);
+    let (start, end) = (margin, size as f32 - margin);
+    for i in 0..dimension {
+        let offset = margin + i as f32 * cell_size;
+        fill_rect(&mut canvas, start, offset - half_width, end, offset + half_width, grid);
+        fill_rect(&mut canvas, offset - half_width, start, offset + half_width, end, grid);
+    }
+
+    // 棋子：按格子大小缩放后居中于交叉点合成
+    let stone_size = stone_size_for(cell_size);
+    let stone_pixels = stone_size.round().max(1.0) as u32;
+    let load_stone = |bytes| {
+        image::load_from_memory(bytes)
+            .ok()
+            .map(|i| imageops::resize(&i.to_rgba8(), stone_pixels, stone_pixels, FilterType::Lanczos3))
+    };
+    let black_stone = load_stone(BLACK_STONE_PNG);
+    let white_stone = load_stone(WHITE_STONE_PNG);
+    for piece in board.pieces.iter().filter(|p| p.active) {
+        let (bx, by) = if flip {
+            (dimension - 1 - piece.position.0, dimension - 1 - piece.position.1)
+        } else {
+            piece.position
+        };
+        let cx = margin + bx as f32 * cell_size;
+        let cy = size as f32 - margin - by as f32 * cell_size;
+
+        let stone = match piece.side {
+            Side::Black => black_stone.as_ref(),
+            Side::White => white_stone.as_ref(),
+        };
+        match stone {
+            Some(stone) => {
+                let x = (cx - stone.width() as f32 / 2.0).round() as i64;
+                let y = (cy - stone.height() as f32 / 2.0).round() as i64;
+                imageops::overlay(&mut canvas, stone, x, y);
+            }
+            None => fill_circle(&mut canvas, cx, cy, stone_size / 2.0, to_rgba(palette.piece_color(piece.side))),
+        }
+    }
+
+    canvas
+}
+
+/// egui 颜色转换为 image 像素
+fn to_rgba(color: Color32) -> image::Rgba<u8> {
+    image::Rgba(color.to_srgba_unmultiplied())
+}
+
+/// 填充矩形区域（超出图片的部分忽略）
+fn fill_rect(canvas: &mut image::RgbaImage, x0: f32, y0: f32, x1: f32, y1: f32, color: image::Rgba<u8>) {
+    let (w, h) = (canvas.width() as f32, canvas.height() as f32);
+    let (x0, x1) = (x0.clamp(0.0, w).round() as u32, x1.clamp(0.0, w).round() as u32);
+    let (y0, y1) = (y0.clamp(0.0, h).round() as u32, y1.clamp(0.0, h).round() as u32);
+    for y in y0..y1 {
+        for x in x0..x1 {
+            canvas.put_pixel(x, y, color);
+        }
+    }
+}
+
+/// 填充圆形区域（棋子图片加载失败时使用）
+fn fill_circle(canvas: &mut image::RgbaImage, cx: f32, cy: f32, radius: f32, color: image::Rgba<u8>) {
+    for (x, y, pixel) in canvas.enumerate_pixels_mut() {
+        let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+        if dx * dx + dy * dy <= radius * radius {
+            *pixel = color;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 像素亮度（RGB 平均值）
+    fn brightness(pixel: &image::Rgba<u8>) -> u32 {
+        pixel.0[..3].iter().map(|&c| c as u32).sum::<u32>() / 3
+    }
+
+    #[test]
+    fn exported_image_has_fixed_size_and_stones_on_their_intersections() {
+        // 黑子在左下角 (0,0)，白子在右下角 (3,0)
+        let board = Board::from_notation("..../..../..../B..W").unwrap();
+        let image = render_board_image(&board, false, Theme::Dark);
+        assert_eq!(image.dimensions(), (EXPORT_IMAGE_SIZE, EXPORT_IMAGE_SIZE));
+
+        // 边距 50 像素，格子 400/3 像素：(0,0) 在 (50,450)，(3,0) 在 (450,450)
+        assert!(brightness(image.get_pixel(50, 450)) < 80);
+        assert!(brightness(image.get_pixel(450, 450)) > 180);
+        // 格子中央是纯色背景
+        let background = to_rgba(ThemePalette::of(Theme::Dark).board_background);
+        assert_eq!(*image.get_pixel(250, 250), background);
+        assert_eq!(*image.get_pixel(50, 50), to_rgba(ThemePalette::of(Theme::Dark).grid_color));
+
+        // 翻转后黑子在右上角，左下角只剩网格线
+        let flipped = render_board_image(&board, true, Theme::Dark);
+        assert!(brightness(flipped.get_pixel(450, 50)) < 80);
+        assert!(brightness(flipped.get_pixel(50, 50)) > 180);
+        assert_eq!(*flipped.get_pixel(50, 450), to_rgba(ThemePalette::of(Theme::Dark).grid_color));
+
+        // 大棋盘的图片尺寸不变
+        let large = render_board_image(&Board::initial_sized(6), false, Theme::Wood);
+        assert_eq!(large.dimensions(), (EXPORT_IMAGE_SIZE, EXPORT_IMAGE_SIZE));
+    }
+
+    #[test]
+    fn keyboard_cursor_stays_on_the_board() {
+        let (left, right, up, down) = ((-1, 0), (1, 0), (0, 1), (0, -1));
+        // 棋盘内部正常移动
+        assert_eq!(step_cursor((1, 1), right, 4, false), (2, 1));
+        assert_eq!(step_cursor((1, 1), up, 4, false), (1, 2));
+        // 每个方向在边缘处停住
+        assert_eq!(step_cursor((0, 2), left, 4, false), (0, 2));
+        assert_eq!(step_cursor((3, 2), right, 4, false), (3, 2));
+        assert_eq!(step_cursor((2, 3), up, 4, false), (2, 3));
+        assert_eq!(step_cursor((2, 0), down, 4, false), (2, 0));
+        // 大棋盘的边缘
+        assert_eq!(step_cursor((5, 5), right, 6, false), (5, 5));
+        assert_eq!(step_cursor((4, 5), right, 6, false), (5, 5));
+
+        // 翻转时屏幕方向对应相反的棋盘方向，同样限制在棋盘内
+        assert_eq!(step_cursor((1, 1), right, 4, true), (0, 1));
+        assert_eq!(step_cursor((1, 1), up, 4, true), (1, 0));
+        assert_eq!(step_cursor((0, 2), right, 4, true), (0, 2));
+        assert_eq!(step_cursor((3, 2), left, 4, true), (3, 2));
+        assert_eq!(step_cursor((2, 0), up, 4, true), (2, 0));
+        assert_eq!(step_cursor((2, 3), down, 4, true), (2, 3));
+    }
+
+    #[test]
+    fn stone_size_scales_with_cells_within_limits() {
+        assert_eq!(stone_size_for(100.0), 72.0);
+        assert_eq!(stone_size_for(50.0), 36.0);
+        // 下限与上限
+        assert_eq!(stone_size_for(15.0), MIN_STONE_SIZE);
+        assert_eq!(stone_size_for(1000.0), MAX_STONE_SIZE);
+        // 格子比下限还小时不超过格子
+        assert_eq!(stone_size_for(8.0), 8.0);
+    }
+
+    #[test]
+    fn piece_radius_tracks_cell_size_and_hit_testing() {
+        let ctx = egui::Context::default();
+        for (size, dimension) in [(500.0, 4), (500.0, 6), (300.0, 4), (900.0, 8)] {
+            let view = BoardView::new(Pos2::new(size / 2.0, size / 2.0), size, dimension, false, Theme::Wood, &ctx);
+            assert_eq!(view.piece_radius, stone_size_for(view.cell_size) / 2.0);
+
+            // 点击检测与显示半径一致
+            let center = view.board_to_screen((1, 1));
+            let inside = center + Vec2::new(view.piece_radius - 0.5, 0.0);
+            let outside = center + Vec2::new(view.piece_radius + 0.5, 0.0);
+            assert!(view.hit_test_piece(center, (1, 1)));
+            assert!(view.hit_test_piece(inside, (1, 1)));
+            assert!(!view.hit_test_piece(outside, (1, 1)));
+        }
+
+        // 棋盘越大、交叉点越少，棋子越大
+        let radius = |size: f32, dimension: u8| BoardView::new(Pos2::ZERO, size, dimension, false, Theme::Wood, &ctx).piece_radius;
+        assert!(radius(600.0, 4) > radius(400.0, 4));
+        assert!(radius(500.0, 4) > radius(500.0, 6));
+    }
+
+    #[test]
+    fn screen_to_board_respects_board_and_grid_boundaries() {
+        let ctx = egui::Context::default();
+        let view = BoardView::new(Pos2::new(250.0, 250.0), 500.0, 4, false, Theme::Wood, &ctx);
+        let tolerance = 0.3;
+        let reach = view.cell_size * tolerance;
+        let at = |pos: (u8, u8), dx: f32, dy: f32| {
+            view.screen_to_board(view.board_to_screen(pos) + Vec2::new(dx, dy), tolerance)
+        };
+
+        // 左下角交叉点：棋盘外侧刚好在容差内、刚好超出
+        assert_eq!(view.board_to_screen((0, 0)), Pos2::new(50.0, 450.0));
+        assert_eq!(at((0, 0), -(reach - 0.5), 0.0), Some((0, 0)));
+        assert_eq!(at((0, 0), -(reach + 0.5), 0.0), None);
+        assert_eq!(at((0, 0), 0.0, reach + 0.5), None);
+        // 右上角交叉点
+        assert_eq!(at((3, 3), reach - 0.5, -(reach - 0.5)), Some((3, 3)));
+        assert_eq!(at((3, 3), reach + 0.5, 0.0), None);
+        assert_eq!(at((3, 3), 0.0, -(reach + 0.5)), None);
+
+        // 棋盘内部两交叉点之间：刚好在容差内归属较近的交叉点，超出则不归属任何交叉点
+        assert_eq!(at((1, 1), reach - 0.5, 0.0), Some((1, 1)));
+        assert_eq!(at((1, 1), reach + 0.5, 0.0), None);
+        assert_eq!(at((2, 1), -(reach - 0.5), 0.0), Some((2, 1)));
+        assert_eq!(at((1, 1), view.cell_size / 2.0, view.cell_size / 2.0), None);
+
+        // 过大的容差按半格截断：网格外超出半格的点仍不归属最外侧交叉点
+        let beyond = view.board_to_screen((3, 3)) + Vec2::new(view.cell_size * 0.6, 0.0);
+        assert_eq!(view.screen_to_board(beyond, 0.9), None);
+
+        // 翻转后左下角对应棋盘的右上角
+        let flipped = BoardView::new(Pos2::new(250.0, 250.0), 500.0, 4, true, Theme::Wood, &ctx);
+        assert_eq!(flipped.screen_to_board(Pos2::new(50.0, 450.0), tolerance), Some((3, 3)));
+        assert_eq!(flipped.screen_to_board(Pos2::new(50.0 - reach - 0.5, 450.0), tolerance), None);
+    }
+}
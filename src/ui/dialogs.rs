@@ -1,41 +1,64 @@
 //! 对话框UI
 
 use rust_i18n::t;
-use egui::{Context, Window};
+use egui::{Context, Key, RichText, Window};
 
-use crate::game::state::GameResult;
+use crate::game::clock::{format_clock, TimeControl};
+use crate::game::piece::Side;
+use crate::game::settings::{KeyBinding, ShortcutAction, Shortcuts, DEFAULT_AI_LEVEL};
+use crate::game::GameStats;
+use crate::game::save::SlotIndex;
+use crate::game::state::{GameEndReason, GameResult};
 
 /// AI等级选择
 pub type AiLevel = u8;
 
+/// AI等级名称
+pub(crate) fn level_name(level: AiLevel) -> String {
+    match level {
+        1 => t!("game.ai_level_1"),
+        2 => t!("game.ai_level_2"),
+        4 => t!("game.ai_level_4"),
+        5 => t!("game.ai_level_5"),
+        _ => t!("game.ai_level_3"),
+    }
+}
+
 /// 新局对话框结果
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct NewGameResult {
     pub player_first: bool,
     pub ai_level: AiLevel,
+    /// 是否启用自适应难度
+    pub adaptive: bool,
+    /// 限时模式（None 表示不限时）
+    pub time_control: Option<TimeControl>,
 }
 
 /// 新局对话框状态
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum NewGameDialog {
     Closed,
-    Open { ai_level: AiLevel },
+    Open { ai_level: AiLevel, adaptive: bool, time_control: Option<TimeControl> },
 }
 
 impl Default for NewGameDialog {
     fn default() -> Self {
-        NewGameDialog::Open { ai_level: 3 }
+        NewGameDialog::Open { ai_level: DEFAULT_AI_LEVEL, adaptive: false, time_control: None }
     }
 }
 
 impl NewGameDialog {
     pub fn show(&mut self, ctx: &Context) -> Option<NewGameResult> {
         match *self {
-            NewGameDialog::Closed => return None,
-            NewGameDialog::Open { ai_level } => {
+            NewGameDialog::Closed => None,
+            NewGameDialog::Open { ai_level, adaptive, time_control } => {
                 let mut result = None;
                 let mut open = true;
                 let mut current_level = ai_level;
+                let mut current_adaptive = adaptive;
+                let mut timed = time_control.is_some();
+                let mut control = time_control.unwrap_or_default();
 
                 Window::new(t!("game.select_side"))
                     .collapsible(false)
@@ -56,15 +79,20 @@ impl NewGameDialog {
                             });
                             
                             // 显示当前等级名称
-                            let level_name = match current_level {
-                                1 => t!("game.ai_level_1"),
-                                2 => t!("game.ai_level_2"),
-                                3 => t!("game.ai_level_3"),
-                                4 => t!("game.ai_level_4"),
-                                5 => t!("game.ai_level_5"),
-                                _ => t!("game.ai_level_3"),
-                            };
-                            ui.label(format!("{}: {}", t!("game.ai_level_name"), level_name));
+                            ui.label(format!("{}: {}", t!("game.ai_level_name"), level_name(current_level)));
+                            ui.checkbox(&mut current_adaptive, t!("game.adaptive_level"))
+                                .on_hover_text(t!("game.adaptive_level_hint"));
+                            ui.add_space(10.0);
+
+                            // 限时模式
+                            ui.checkbox(&mut timed, t!("game.time_control"))
+                                .on_hover_text(t!("game.time_control_hint"));
+                            if timed {
+                                let mut minutes = control.base_secs / 60;
+                                ui.add(egui::Slider::new(&mut minutes, 1..=30).text(t!("game.base_minutes")));
+                                control.base_secs = minutes * 60;
+                                ui.add(egui::Slider::new(&mut control.increment_secs, 0..=30).text(t!("game.increment_secs")));
+                            }
                             ui.add_space(20.0);
 
                             // 先行/后行选择
@@ -76,6 +104,8 @@ impl NewGameDialog {
                                     result = Some(NewGameResult {
                                         player_first: true,
                                         ai_level: current_level,
+                                        adaptive: current_adaptive,
+                                        time_control: timed.then_some(control),
                                     });
                                     *self = NewGameDialog::Closed;
                                 }
@@ -84,6 +114,19 @@ impl NewGameDialog {
                                     result = Some(NewGameResult {
                                         player_first: false,
                                         ai_level: current_level,
+                                        adaptive: current_adaptive,
+                                        time_control: timed.then_some(control),
+                                    });
+                                    *self = NewGameDialog::Closed;
+                                }
+                                ui.add_space(20.0);
+                                // 随机决定先后手
+                                if ui.button(format!("🎲 {}", t!("game.play_random"))).clicked() {
+                                    result = Some(NewGameResult {
+                                        player_first: rand::random(),
+                                        ai_level: current_level,
+                                        adaptive: current_adaptive,
+                                        time_control: timed.then_some(control),
                                     });
                                     *self = NewGameDialog::Closed;
                                 }
@@ -93,7 +136,11 @@ impl NewGameDialog {
 
                 // 更新AI等级状态
                 if matches!(*self, NewGameDialog::Open { .. }) {
-                    *self = NewGameDialog::Open { ai_level: current_level };
+                    *self = NewGameDialog::Open {
+                        ai_level: current_level,
+                        adaptive: current_adaptive,
+                        time_control: timed.then_some(control),
+                    };
                 }
 
                 if !open {
@@ -110,21 +157,23 @@ impl NewGameDialog {
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum GameOverDialog {
     Closed,
-    Open(GameResult),
+    /// 对局结果、结束原因（未知时为 None）与统计
+    Open(GameResult, Option<GameEndReason>, GameStats),
 }
 
 impl GameOverDialog {
-    pub fn show(&mut self, ctx: &Context) -> Option<GameOverAction> {
+    /// can_undo 为 false 时（如超时判负）不显示悔棋按钮
+    pub fn show(&mut self, ctx: &Context, can_undo: bool) -> Option<GameOverAction> {
         match self {
             GameOverDialog::Closed => return None,
-            GameOverDialog::Open(_) => {}
+            GameOverDialog::Open(..) => {}
         }
 
         let mut result = None;
         let mut open = true;
-        let result_text = match self {
-            GameOverDialog::Open(r) => r.display_text(),
-            _ => String::new(),
+        let (result_text, reason, stats, lost) = match self {
+            GameOverDialog::Open(r, reason, stats) => (r.display_text(), *reason, *stats, *r == GameResult::AiWin),
+            _ => (String::new(), None, GameStats::default(), false),
         };
 
         Window::new(t!("dialog.game_over"))
@@ -135,23 +184,46 @@ impl GameOverDialog {
             .show(ctx, |ui| {
                 ui.vertical_centered(|ui| {
                     ui.heading(&result_text);
+                    if let Some(reason) = reason {
+                        ui.label(reason.display_text());
+                    }
+                    ui.add_space(10.0);
+                    show_game_stats(ui, &stats);
                     ui.add_space(20.0);
 
                     ui.horizontal(|ui| {
-                        if ui.button(format!("🔄 {}", t!("game.undo"))).clicked() {
-                            result = Some(GameOverAction::Undo);
+                        if can_undo {
+                            if ui.button(format!("🔄 {}", t!("game.undo"))).clicked() {
+                                result = Some(GameOverAction::Undo);
+                            }
+                            ui.add_space(10.0);
                         }
-                        ui.add_space(10.0);
                         if ui.button(format!("🎮 {}", t!("game.new_game_btn"))).clicked() {
                             result = Some(GameOverAction::NewGame);
                             *self = GameOverDialog::Closed;
                         }
                         ui.add_space(10.0);
+                        if ui.button(format!("🔁 {}", t!("game.swap_sides"))).clicked() {
+                            result = Some(GameOverAction::SwapSides);
+                            *self = GameOverDialog::Closed;
+                        }
+                        ui.add_space(10.0);
                         if ui.button(format!("🏠 {}", t!("game.back_to_menu"))).clicked() {
                             result = Some(GameOverAction::BackToMenu);
                             *self = GameOverDialog::Closed;
                         }
                     });
+
+                    // 失利时可复盘失误（回放结束后对话框仍保留）
+                    if lost {
+                        ui.add_space(10.0);
+                        if ui.button(format!("🔍 {}", t!("replay.review_blunder")))
+                            .on_hover_text(t!("replay.review_blunder_hint"))
+                            .clicked()
+                        {
+                            result = Some(GameOverAction::ReviewBlunder);
+                        }
+                    }
                 });
             });
 
@@ -163,12 +235,156 @@ impl GameOverDialog {
     }
 }
 
+/// 显示对局统计：总步数、双方吃子数与用时
+fn show_game_stats(ui: &mut egui::Ui, stats: &GameStats) {
+    ui.label(t!("dialog.stats_moves", count = stats.moves));
+    ui.label(t!(
+        "dialog.stats_captures",
+        black = stats.black_captures,
+        white = stats.white_captures
+    ));
+    ui.label(t!("dialog.stats_time", time = format_clock(stats.elapsed)));
+}
+
 /// 游戏结束后的操作
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum GameOverAction {
     Undo,
     NewGame,
+    /// 交换先后手再来一局
+    SwapSides,
     BackToMenu,
+    /// 回放最近几步并标出导致失利的失误
+    ReviewBlunder,
+}
+
+/// 演示模式设置：黑白双方的AI等级
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DemoSetup {
+    pub black_level: AiLevel,
+    pub white_level: AiLevel,
+}
+
+impl Default for DemoSetup {
+    fn default() -> Self {
+        Self { black_level: 3, white_level: 3 }
+    }
+}
+
+impl DemoSetup {
+    /// 指定一方的AI等级
+    pub fn level_for(&self, side: Side) -> AiLevel {
+        match side {
+            Side::Black => self.black_level,
+            Side::White => self.white_level,
+        }
+    }
+}
+
+/// 演示模式设置对话框
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DemoDialog {
+    Closed,
+    Open(DemoSetup),
+}
+
+impl DemoDialog {
+    /// 显示对话框，点击开始时返回双方等级
+    pub fn show(&mut self, ctx: &Context) -> Option<DemoSetup> {
+        let DemoDialog::Open(setup) = self else {
+            return None;
+        };
+
+        let mut result = None;
+        let mut open = true;
+
+        Window::new(t!("demo.title"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.vertical_centered(|ui| {
+                    egui::Grid::new("demo_levels").num_columns(3).spacing([10.0, 6.0]).show(ui, |ui| {
+                        for (side, level) in [(t!("game.black"), &mut setup.black_level), (t!("game.white"), &mut setup.white_level)] {
+                            ui.label(side);
+                            ui.add(egui::Slider::new(level, 1..=5));
+                            ui.label(level_name(*level));
+                            ui.end_row();
+                        }
+                    });
+                    ui.add_space(10.0);
+                    if ui.button(format!("▶ {}", t!("demo.start"))).clicked() {
+                        result = Some(*setup);
+                    }
+                });
+            });
+
+        if !open || result.is_some() {
+            *self = DemoDialog::Closed;
+        }
+
+        result
+    }
+}
+
+/// 演示结束对话框
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DemoOverDialog {
+    Closed,
+    Open(GameResult),
+}
+
+/// 演示结束后的操作
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DemoOverAction {
+    /// 以相同等级重新演示
+    Restart,
+    /// 退出演示，回到正常对局
+    Exit,
+}
+
+impl DemoOverDialog {
+    pub fn show(&mut self, ctx: &Context) -> Option<DemoOverAction> {
+        let DemoOverDialog::Open(game_result) = *self else {
+            return None;
+        };
+
+        // 演示中黑方占用玩家一方的位置
+        let result_text = match game_result {
+            GameResult::PlayerWin => t!("demo.black_win"),
+            GameResult::AiWin => t!("demo.white_win"),
+            GameResult::Draw => t!("game.draw"),
+        };
+        let mut result = None;
+
+        Window::new(t!("dialog.game_over"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.heading(result_text);
+                    ui.add_space(20.0);
+
+                    ui.horizontal(|ui| {
+                        if ui.button(format!("🔄 {}", t!("demo.restart"))).clicked() {
+                            result = Some(DemoOverAction::Restart);
+                        }
+                        ui.add_space(10.0);
+                        if ui.button(format!("🏠 {}", t!("demo.exit"))).clicked() {
+                            result = Some(DemoOverAction::Exit);
+                        }
+                    });
+                });
+            });
+
+        if result.is_some() {
+            *self = DemoOverDialog::Closed;
+        }
+
+        result
+    }
 }
 
 /// 关于对话框
@@ -207,6 +423,89 @@ impl AboutDialog {
     }
 }
 
+/// 快捷键设置对话框
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShortcutsDialog {
+    Closed,
+    /// `capturing` 为正在等待按下新快捷键的操作
+    Open { capturing: Option<ShortcutAction> },
+}
+
+impl ShortcutsDialog {
+    /// 显示对话框，快捷键有改动时返回 true
+    pub fn show(&mut self, ctx: &Context, shortcuts: &mut Shortcuts) -> bool {
+        let ShortcutsDialog::Open { capturing } = self else {
+            return false;
+        };
+        let mut changed = false;
+
+        // 等待按键：Esc 取消，其他按键连同修饰键作为新的快捷键
+        if let Some(action) = *capturing {
+            let pressed = ctx.input(|i| {
+                i.events.iter().find_map(|event| match event {
+                    egui::Event::Key { key, pressed: true, repeat: false, modifiers, .. } => Some((*key, *modifiers)),
+                    _ => None,
+                })
+            });
+            if let Some((key, modifiers)) = pressed {
+                if key != Key::Escape {
+                    shortcuts.set(action, KeyBinding {
+                        key: key.name().to_string(),
+                        ctrl: modifiers.ctrl,
+                        shift: modifiers.shift,
+                        alt: modifiers.alt,
+                    });
+                    changed = true;
+                }
+                *capturing = None;
+            }
+        }
+
+        let conflicts = shortcuts.conflicts();
+        let mut open = true;
+        Window::new(t!("shortcuts.title"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(t!("shortcuts.hint"));
+                ui.add_space(10.0);
+                egui::Grid::new("shortcut_bindings").num_columns(2).spacing([20.0, 6.0]).show(ui, |ui| {
+                    for action in ShortcutAction::ALL {
+                        ui.label(action.display_text());
+                        let text = if *capturing == Some(action) {
+                            RichText::new(t!("shortcuts.press_key")).italics()
+                        } else if conflicts.contains(&action) {
+                            RichText::new(shortcuts.get(action).label()).color(ui.visuals().error_fg_color)
+                        } else {
+                            RichText::new(shortcuts.get(action).label())
+                        };
+                        if ui.add(egui::Button::new(text).min_size(egui::vec2(110.0, 0.0))).clicked() {
+                            *capturing = Some(action);
+                        }
+                        ui.end_row();
+                    }
+                });
+                if !conflicts.is_empty() {
+                    ui.add_space(6.0);
+                    ui.colored_label(ui.visuals().error_fg_color, t!("shortcuts.conflict"));
+                }
+                ui.add_space(10.0);
+                if ui.button(t!("shortcuts.reset")).clicked() {
+                    *shortcuts = Shortcuts::default();
+                    *capturing = None;
+                    changed = true;
+                }
+            });
+
+        if !open {
+            *self = ShortcutsDialog::Closed;
+        }
+        changed
+    }
+}
+
 /// 规则对话框
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum RulesDialog {
@@ -247,3 +546,95 @@ impl RulesDialog {
         }
     }
 }
+
+/// 存档管理对话框
+#[derive(Debug, Clone, PartialEq)]
+pub enum SaveSlotsDialog {
+    Closed,
+    Open {
+        /// 存档槽索引
+        index: SlotIndex,
+        /// 各槽正在编辑的名称
+        names: Vec<String>,
+        /// 当前是否允许保存（初始局面不可保存）
+        can_save: bool,
+    },
+}
+
+/// 存档管理对话框中的操作
+#[derive(Debug, Clone, PartialEq)]
+pub enum SlotAction {
+    /// 保存到指定槽
+    Save { slot: usize, name: String },
+    /// 从指定槽加载
+    Load(usize),
+}
+
+impl SaveSlotsDialog {
+    /// 打开对话框，未命名的槽使用默认名称
+    pub fn open(index: SlotIndex, can_save: bool) -> Self {
+        let names = index
+            .slots
+            .iter()
+            .enumerate()
+            .map(|(i, info)| match info {
+                Some(info) => info.name.clone(),
+                None => t!("slots.default_name", n = i + 1),
+            })
+            .collect();
+        SaveSlotsDialog::Open { index, names, can_save }
+    }
+
+    pub fn show(&mut self, ctx: &Context) -> Option<SlotAction> {
+        let SaveSlotsDialog::Open { index, names, can_save } = self else {
+            return None;
+        };
+
+        let mut result = None;
+        let mut open = true;
+
+        Window::new(t!("slots.title"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .open(&mut open)
+            .show(ctx, |ui| {
+                egui::Grid::new("save_slots").num_columns(5).spacing([10.0, 6.0]).show(ui, |ui| {
+                    for (slot, (info, name)) in index.slots.iter().zip(names.iter_mut()).enumerate() {
+                        ui.label(format!("#{}", slot + 1));
+                        ui.add(egui::TextEdit::singleline(name).desired_width(120.0));
+                        match info {
+                            Some(info) => {
+                                let mut text = format!(
+                                    "{}  {}",
+                                    info.saved_at.format("%Y-%m-%d %H:%M"),
+                                    t!("slots.pieces", black = info.black_count, white = info.white_count)
+                                );
+                                if let Some(level) = info.ai_level {
+                                    text.push_str(&format!("  {}", level_name(level)));
+                                }
+                                if let Some(result) = info.result {
+                                    text.push_str(&format!("  {}", result.display_text()));
+                                }
+                                ui.label(text)
+                            }
+                            None => ui.weak(t!("slots.empty")),
+                        };
+                        if ui.add_enabled(*can_save, egui::Button::new(t!("slots.save"))).clicked() {
+                            result = Some(SlotAction::Save { slot, name: name.trim().to_string() });
+                        }
+                        if ui.add_enabled(info.is_some(), egui::Button::new(t!("slots.load"))).clicked() {
+                            result = Some(SlotAction::Load(slot));
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+
+        if !open || matches!(result, Some(SlotAction::Load(_))) {
+            *self = SaveSlotsDialog::Closed;
+        }
+
+        result
+    }
+}
@@ -0,0 +1,231 @@
+//! 局面编辑器
+
+use rust_i18n::t;
+use egui::Ui;
+
+use crate::game::board::Board;
+use crate::game::piece::{pieces_per_side, Piece, Side};
+use crate::game::rules::{validate_setup, SetupError};
+use crate::game::settings::GameSettings;
+
+/// 编辑画笔：点击交叉点时放置的内容
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Brush {
+    Empty,
+    Black,
+    White,
+}
+
+/// 编辑面板中的操作
+#[derive(Debug, Clone, PartialEq)]
+pub enum EditorAction {
+    /// 从当前编辑的局面开始对局（棋子ID已规范化）
+    Start { board: Board, player_side: Side, side_to_move: Side },
+    /// 放弃编辑，回到原对局
+    Cancel,
+}
+
+/// 局面编辑器状态
+#[derive(Debug, Clone)]
+pub struct PositionEditor {
+    /// 正在编辑的棋盘（只包含在盘棋子）
+    pub board: Board,
+    /// 当前画笔
+    brush: Brush,
+    /// 玩家执子方
+    player_side: Side,
+    /// 先行方
+    side_to_move: Side,
+    /// 局面记谱输入框内容
+    notation: String,
+    /// 最近一次校验或导入失败的提示
+    error: Option<String>,
+}
+
+impl PositionEditor {
+    /// 以指定局面为起点打开编辑器
+    pub fn new(board: &Board, player_side: Side, side_to_move: Side) -> Self {
+        let mut board = board.clone();
+        board.pieces.retain(|p| p.active);
+        let notation = board.to_notation();
+        Self {
+            board,
+            brush: Brush::Black,
+            player_side,
+            side_to_move,
+            notation,
+            error: None,
+        }
+    }
+
+    /// 用当前画笔修改指定交叉点
+    pub fn paint(&mut self, pos: (u8, u8)) {
+        self.board.pieces.retain(|p| p.position != pos);
+        let side = match self.brush {
+            Brush::Empty => None,
+            Brush::Black => Some(Side::Black),
+            Brush::White => Some(Side::White),
+        };
+        if let Some(side) = side {
+            // 取最小的未用ID，反复放置与移除时ID不会一直增长；开始对局前会重新分配
+            let id = (1..=u8::MAX)
+                .find(|&id| self.board.piece_by_id(id).is_none())
+                .expect("棋盘交叉点数少于可用的棋子ID数");
+            self.board.pieces.push(Piece::new(id, side, pos.0, pos.1));
+        }
+        self.notation = self.board.to_notation();
+        self.error = None;
+    }
+
+    /// 显示编辑面板，开始对局前按 `settings` 中的规则校验局面
+    pub fn show(&mut self, ui: &mut Ui, settings: &GameSettings) -> Option<EditorAction> {
+        let mut action = None;
+
+        ui.heading(t!("editor.title"));
+        ui.add_space(10.0);
+
+        ui.label(t!("editor.brush"));
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.brush, Brush::Black, t!("game.black"));
+            ui.radio_value(&mut self.brush, Brush::White, t!("game.white"));
+            ui.radio_value(&mut self.brush, Brush::Empty, t!("editor.empty"));
+        });
+        ui.add_space(10.0);
+
+        ui.label(t!("editor.side_to_move"));
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.side_to_move, Side::Black, t!("game.black"));
+            ui.radio_value(&mut self.side_to_move, Side::White, t!("game.white"));
+        });
+        ui.label(t!("editor.player_side"));
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.player_side, Side::Black, t!("game.black"));
+            ui.radio_value(&mut self.player_side, Side::White, t!("game.white"));
+        });
+        ui.add_space(10.0);
+
+        // 局面记谱：可复制当前局面，或粘贴后导入
+        ui.label(t!("editor.notation"));
+        ui.text_edit_singleline(&mut self.notation);
+        ui.horizontal(|ui| {
+            if ui.button(t!("editor.import")).clicked() {
+                self.import_notation();
+            }
+            if ui.button(t!("editor.clear")).clicked() {
+                self.board.pieces.clear();
+                self.notation = self.board.to_notation();
+                self.error = None;
+            }
+        });
+        ui.add_space(10.0);
+
+        if let Some(ref error) = self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+            ui.add_space(10.0);
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button(format!("▶ {}", t!("editor.start"))).clicked() {
+                action = self.try_start(settings);
+            }
+            if ui.button(t!("dialog.cancel")).clicked() {
+                action = Some(EditorAction::Cancel);
+            }
+        });
+
+        action
+    }
+
+    /// 在面板中显示开始对局失败的原因
+    pub fn show_setup_error(&mut self, error: SetupError) {
+        self.error = Some(setup_error_text(error, pieces_per_side(self.board.size)));
+    }
+
+    /// 从记谱输入框导入局面（棋盘大小须与当前一致）
+    fn import_notation(&mut self) {
+        match Board::from_notation(&self.notation) {
+            Ok(board) if board.size == self.board.size => {
+                self.board = board;
+                self.error = None;
+            }
+            Ok(_) => self.error = Some(t!("editor.error_size", size = self.board.size)),
+            Err(e) => self.error = Some(t!("editor.error_notation", reason = e)),
+        }
+    }
+
+    /// 校验局面，合法时返回开始对局的操作
+    fn try_start(&mut self, settings: &GameSettings) -> Option<EditorAction> {
+        if let Err(e) = validate_setup(&self.board, self.side_to_move, settings) {
+            self.error = Some(setup_error_text(e, pieces_per_side(self.board.size)));
+            return None;
+        }
+        // 经记谱往返一次，按先黑后白、自下而上重新分配棋子ID
+        match Board::from_notation(&self.board.to_notation()) {
+            Ok(board) => Some(EditorAction::Start {
+                board,
+                player_side: self.player_side,
+                side_to_move: self.side_to_move,
+            }),
+            Err(e) => {
+                self.error = Some(t!("editor.error_notation", reason = e));
+                None
+            }
+        }
+    }
+}
+
+/// 局面不合法的提示文本，`max_pieces` 为每方棋子数上限
+fn setup_error_text(error: SetupError, max_pieces: usize) -> String {
+    let side_name = |side: Side| match side {
+        Side::Black => t!("game.black"),
+        Side::White => t!("game.white"),
+    };
+    match error {
+        SetupError::NoPieces(side) => t!("editor.error_no_pieces", side = side_name(side)),
+        SetupError::TooManyPieces(side) => t!("editor.error_too_many", side = side_name(side), max = max_pieces),
+        SetupError::NoLegalMoves => t!("editor.error_no_moves"),
+        SetupError::AlreadyOver => t!("editor.error_over"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_painting_reuses_piece_ids() {
+        let mut editor = PositionEditor::new(&Board::initial(), Side::Black, Side::Black);
+        let max_id = |editor: &PositionEditor| editor.board.pieces.iter().map(|p| p.id).max().unwrap();
+        let initial_max = max_id(&editor);
+
+        // 反复放置、移除两个交叉点，ID 不会越来越大
+        for _ in 0..300 {
+            for (brush, pos) in [(Brush::Black, (1, 1)), (Brush::White, (2, 2)), (Brush::Empty, (1, 1)), (Brush::Empty, (2, 2))] {
+                editor.brush = brush;
+                editor.paint(pos);
+            }
+        }
+        assert_eq!(max_id(&editor), initial_max);
+
+        editor.brush = Brush::Black;
+        editor.paint((1, 1));
+        editor.brush = Brush::White;
+        editor.paint((2, 2));
+        let mut ids: Vec<u8> = editor.board.pieces.iter().map(|p| p.id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), editor.board.pieces.len());
+        assert_eq!(editor.board.to_notation(), "WWWW/W.WW/BB.B/BBBB");
+    }
+
+    #[test]
+    fn setup_error_is_shown_in_the_panel() {
+        let mut editor = PositionEditor::new(&Board::initial(), Side::Black, Side::Black);
+        assert!(editor.error.is_none());
+        editor.show_setup_error(SetupError::NoLegalMoves);
+        assert_eq!(editor.error, Some(setup_error_text(SetupError::NoLegalMoves, 6)));
+        // 修改局面后提示消失
+        editor.paint((1, 1));
+        assert!(editor.error.is_none());
+    }
+}
@@ -3,5 +3,7 @@
 pub mod app;
 pub mod board_view;
 pub mod dialogs;
+pub mod editor;
+pub mod theme;
 
 pub use app::MainApp;
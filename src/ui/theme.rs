@@ -0,0 +1,106 @@
+//! 棋盘主题配色
+
+use egui::Color32;
+
+use crate::game::piece::Side;
+use crate::game::settings::Theme;
+
+/// 主题调色板
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThemePalette {
+    /// 是否使用木纹背景图（否则使用纯色背景）
+    pub use_board_texture: bool,
+    /// 棋盘背景色（纯色背景或背景图加载失败时使用）
+    pub board_background: Color32,
+    /// 网格线颜色
+    pub grid_color: Color32,
+    /// 网格线宽度
+    pub grid_width: f32,
+    /// 合法目标点提示色
+    pub hint_color: Color32,
+    /// 选中棋子高亮色
+    pub selection_color: Color32,
+    /// 最近一步标记色
+    pub last_move_color: Color32,
+    /// 吃子预览标记色
+    pub capture_preview_color: Color32,
+    /// 黑子颜色（棋子图片加载失败时使用）
+    pub black_piece: Color32,
+    /// 白子颜色（棋子图片加载失败时使用）
+    pub white_piece: Color32,
+    /// 黑子边框色（棋子图片加载失败时使用）
+    pub black_piece_outline: Color32,
+    /// 白子边框色（棋子图片加载失败时使用）
+    pub white_piece_outline: Color32,
+}
+
+impl ThemePalette {
+    /// 获取指定主题的调色板
+    pub fn of(theme: Theme) -> Self {
+        match theme {
+            Theme::Wood => Self {
+                use_board_texture: true,
+                board_background: Color32::from_rgb(240, 217, 181),
+                grid_color: Color32::from_rgb(60, 40, 20),
+                grid_width: 2.5,
+                hint_color: Color32::from_rgba_unmultiplied(0, 128, 0, 64),
+                selection_color: Color32::from_rgba_unmultiplied(192, 192, 192, 128),
+                last_move_color: Color32::from_rgba_unmultiplied(200, 120, 0, 72),
+                capture_preview_color: Color32::from_rgba_unmultiplied(200, 30, 30, 200),
+                black_piece: Color32::from_rgb(30, 30, 30),
+                white_piece: Color32::from_rgb(240, 240, 240),
+                black_piece_outline: Color32::from_rgb(80, 80, 80),
+                white_piece_outline: Color32::from_rgb(180, 180, 180),
+            },
+            Theme::Dark => Self {
+                use_board_texture: false,
+                board_background: Color32::from_rgb(48, 52, 58),
+                grid_color: Color32::from_rgb(150, 155, 165),
+                grid_width: 2.0,
+                hint_color: Color32::from_rgba_unmultiplied(80, 200, 120, 96),
+                selection_color: Color32::from_rgba_unmultiplied(120, 160, 220, 128),
+                last_move_color: Color32::from_rgba_unmultiplied(230, 190, 90, 64),
+                capture_preview_color: Color32::from_rgba_unmultiplied(240, 90, 90, 220),
+                black_piece: Color32::from_rgb(15, 15, 18),
+                white_piece: Color32::from_rgb(220, 220, 225),
+                black_piece_outline: Color32::from_rgb(110, 110, 120),
+                white_piece_outline: Color32::from_rgb(150, 150, 160),
+            },
+            Theme::HighContrast => Self {
+                use_board_texture: false,
+                board_background: Color32::from_rgb(255, 235, 59),
+                grid_color: Color32::BLACK,
+                grid_width: 4.0,
+                hint_color: Color32::from_rgba_unmultiplied(0, 90, 255, 160),
+                selection_color: Color32::from_rgba_unmultiplied(255, 0, 128, 160),
+                last_move_color: Color32::from_rgba_unmultiplied(255, 80, 0, 140),
+                capture_preview_color: Color32::from_rgb(220, 0, 0),
+                black_piece: Color32::BLACK,
+                white_piece: Color32::WHITE,
+                black_piece_outline: Color32::WHITE,
+                white_piece_outline: Color32::BLACK,
+            },
+        }
+    }
+
+    /// 获取某方棋子的颜色（图片加载失败时使用）
+    pub fn piece_color(&self, side: Side) -> Color32 {
+        match side {
+            Side::Black => self.black_piece,
+            Side::White => self.white_piece,
+        }
+    }
+
+    /// 获取某方棋子的边框色（图片加载失败时使用）
+    pub fn piece_outline(&self, side: Side) -> Color32 {
+        match side {
+            Side::Black => self.black_piece_outline,
+            Side::White => self.white_piece_outline,
+        }
+    }
+}
+
+/// 为颜色设置透明度
+pub fn with_alpha(color: Color32, alpha: u8) -> Color32 {
+    Color32::from_rgba_unmultiplied(color.r(), color.g(), color.b(), alpha)
+}
@@ -1,8 +1,9 @@
 //! 工具函数模块
 
+use std::ops::{Add, Mul, Sub};
 use std::time::{Duration, Instant};
 
-/// 动画插值函数
+// 动画插值函数
 
 /// easeOutQuad - 二次方缓出
 pub fn ease_out_quad(t: f32) -> f32 {
@@ -53,11 +54,88 @@ pub fn ease_out_bounce(t: f32) -> f32 {
     }
 }
 
+/// easeOutElastic - 弹性缓出（越过终点后来回振荡）
+pub fn ease_out_elastic(t: f32) -> f32 {
+    const C4: f32 = 2.0 * std::f32::consts::PI / 3.0;
+
+    if t <= 0.0 {
+        0.0
+    } else if t >= 1.0 {
+        1.0
+    } else {
+        2.0_f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * C4).sin() + 1.0
+    }
+}
+
+/// easeOutBack - 回弹缓出（略微越过终点后回到终点）
+pub fn ease_out_back(t: f32) -> f32 {
+    const C1: f32 = 1.70158;
+    const C3: f32 = C1 + 1.0;
+
+    1.0 + C3 * (t - 1.0).powi(3) + C1 * (t - 1.0).powi(2)
+}
+
+/// 缓动曲线
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Easing {
+    /// 线性
+    Linear,
+    /// 二次方缓入
+    InQuad,
+    /// 二次方缓出
+    #[default]
+    OutQuad,
+    /// 二次方缓入缓出
+    InOutQuad,
+    /// 三次方缓入缓出
+    InOutCubic,
+    /// 弹跳缓出
+    OutBounce,
+    /// 弹性缓出
+    OutElastic,
+    /// 回弹缓出
+    OutBack,
+}
+
+impl Easing {
+    /// 将线性进度 t（0..=1）映射为缓动后的进度
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::InQuad => ease_in_quad(t),
+            Easing::OutQuad => ease_out_quad(t),
+            Easing::InOutQuad => ease_in_out_quad(t),
+            Easing::InOutCubic => ease_in_out_cubic(t),
+            Easing::OutBounce => ease_out_bounce(t),
+            Easing::OutElastic => ease_out_elastic(t),
+            Easing::OutBack => ease_out_back(t),
+        }
+    }
+}
+
+/// 平滑闪烁的不透明度：在进度 0..=1 内按余弦曲线完成 count 次明暗交替，
+/// 从完全不透明开始，最暗为 min_alpha
+pub fn flash_alpha(progress: f32, count: u32, min_alpha: f32) -> f32 {
+    let phase = progress.clamp(0.0, 1.0) * count as f32 * std::f32::consts::TAU;
+    lerp(min_alpha, 1.0, 0.5 + 0.5 * phase.cos())
+}
+
 /// 线性插值
 pub fn lerp(a: f32, b: f32, t: f32) -> f32 {
     a + (b - a) * t.clamp(0.0, 1.0)
 }
 
+/// 不限制 t 的线性插值：保留回弹、弹性等缓动越过终点的部分
+pub fn lerp_unclamped(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// 屏幕坐标线性插值（t 限制在 0..=1）
+#[cfg(feature = "gui")]
+pub fn lerp_pos2(a: egui::Pos2, b: egui::Pos2, t: f32) -> egui::Pos2 {
+    egui::Pos2::new(lerp(a.x, b.x, t), lerp(a.y, b.y, t))
+}
+
 /// 动画状态
 #[derive(Debug, Clone)]
 pub struct Animation {
@@ -65,6 +143,8 @@ pub struct Animation {
     pub duration: Duration,
     pub start_value: f32,
     pub end_value: f32,
+    /// 缓动曲线
+    pub easing: Easing,
 }
 
 impl Animation {
@@ -74,9 +154,16 @@ impl Animation {
             duration: Duration::from_millis(duration_ms),
             start_value: start,
             end_value: end,
+            easing: Easing::default(),
         }
     }
 
+    /// 指定缓动曲线
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
     pub fn progress(&self) -> f32 {
         let elapsed = self.start_time.elapsed();
         if elapsed >= self.duration {
@@ -87,13 +174,113 @@ impl Animation {
     }
 
     pub fn value(&self) -> f32 {
-        let t = ease_out_quad(self.progress());
-        lerp(self.start_value, self.end_value, t)
+        self.value_at(self.progress())
+    }
+
+    /// 线性进度为 progress 时按缓动曲线插值的值（可越过起止值，如回弹缓出）
+    pub fn value_at(&self, progress: f32) -> f32 {
+        lerp_unclamped(self.start_value, self.end_value, self.easing.apply(progress))
     }
 
     pub fn is_finished(&self) -> bool {
         self.start_time.elapsed() >= self.duration
     }
+
+    /// 推迟动画的开始时刻（暂停期间调用，使恢复后从暂停时的进度继续）
+    pub fn postpone(&mut self, by: Duration) {
+        self.start_time += by;
+    }
+}
+
+/// 多阶段动画序列：按顺序依次播放各阶段动画，每个阶段带一个标签
+///
+/// 各阶段只使用其时长、起止值与缓动曲线，开始时刻由序列统一推算
+/// （自身的 `start_time` 被忽略）；时长为 0 的阶段会被直接跳过
+#[derive(Debug, Clone)]
+pub struct AnimationSequence<T> {
+    stages: Vec<(Animation, T)>,
+    start_time: Instant,
+}
+
+impl<T> AnimationSequence<T> {
+    /// 从当前时刻开始播放
+    pub fn new(stages: Vec<(Animation, T)>) -> Self {
+        Self { stages, start_time: Instant::now() }
+    }
+
+    /// 所有阶段的总时长
+    pub fn total_duration(&self) -> Duration {
+        self.stages.iter().map(|(anim, _)| anim.duration).sum()
+    }
+
+    /// 自开始经过 elapsed 后所处的阶段下标及该阶段内的线性进度（0..=1）
+    ///
+    /// 全部播放完毕后停在最后一个阶段、进度为 1；序列为空时返回 None
+    pub fn stage_at(&self, elapsed: Duration) -> Option<(usize, f32)> {
+        let mut stage_start = Duration::ZERO;
+        for (index, (anim, _)) in self.stages.iter().enumerate() {
+            let stage_end = stage_start + anim.duration;
+            if elapsed < stage_end {
+                let progress = (elapsed - stage_start).as_secs_f32() / anim.duration.as_secs_f32();
+                return Some((index, progress));
+            }
+            stage_start = stage_end;
+        }
+        self.stages.len().checked_sub(1).map(|last| (last, 1.0))
+    }
+
+    /// 当前阶段的下标及标签
+    pub fn current_stage(&self) -> Option<(usize, &T)> {
+        let (index, _) = self.stage_at(self.start_time.elapsed())?;
+        Some((index, &self.stages[index].1))
+    }
+
+    /// 当前阶段的标签
+    pub fn current_tag(&self) -> Option<&T> {
+        self.current_stage().map(|(_, tag)| tag)
+    }
+
+    /// 当前阶段内的线性进度（0..=1）
+    pub fn stage_progress(&self) -> f32 {
+        self.stage_at(self.start_time.elapsed()).map_or(1.0, |(_, progress)| progress)
+    }
+
+    /// 当前阶段按其缓动曲线插值后的值
+    pub fn stage_value(&self) -> f32 {
+        match self.stage_at(self.start_time.elapsed()) {
+            Some((index, progress)) => self.stages[index].0.value_at(progress),
+            None => 0.0,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.start_time.elapsed() >= self.total_duration()
+    }
+
+    /// 推迟序列的开始时刻（暂停期间调用）
+    pub fn postpone(&mut self, by: Duration) {
+        self.start_time += by;
+    }
+}
+
+/// 上升沿检测：记录上一次的取值，只在由 false 变为 true 时触发
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RisingEdge {
+    previous: bool,
+}
+
+impl RisingEdge {
+    /// 更新取值，返回是否刚由 false 变为 true
+    pub fn update(&mut self, value: bool) -> bool {
+        let rising = value && !self.previous;
+        self.previous = value;
+        rising
+    }
+
+    /// 直接记下当前取值而不触发（如加载棋局后）
+    pub fn reset(&mut self, value: bool) {
+        self.previous = value;
+    }
 }
 
 /// 2D向量
@@ -111,4 +298,183 @@ impl Vec2 {
     pub fn distance(&self, other: &Vec2) -> f32 {
         ((self.x - other.x).powi(2) + (self.y - other.y).powi(2)).sqrt()
     }
+
+    /// 线性插值（t 限制在 0..=1）
+    pub fn lerp(self, other: Vec2, t: f32) -> Vec2 {
+        Vec2::new(lerp(self.x, other.x, t), lerp(self.y, other.y, t))
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Vec2;
+
+    fn add(self, rhs: Vec2) -> Vec2 {
+        Vec2::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+
+    fn sub(self, rhs: Vec2) -> Vec2 {
+        Vec2::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Mul<f32> for Vec2 {
+    type Output = Vec2;
+
+    fn mul(self, rhs: f32) -> Vec2 {
+        Vec2::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_EASINGS: [Easing; 8] = [
+        Easing::Linear,
+        Easing::InQuad,
+        Easing::OutQuad,
+        Easing::InOutQuad,
+        Easing::InOutCubic,
+        Easing::OutBounce,
+        Easing::OutElastic,
+        Easing::OutBack,
+    ];
+
+    #[test]
+    fn every_easing_maps_endpoints_to_themselves() {
+        for easing in ALL_EASINGS {
+            assert!(easing.apply(0.0).abs() < 1e-6, "{easing:?}(0) = {}", easing.apply(0.0));
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-6, "{easing:?}(1) = {}", easing.apply(1.0));
+        }
+    }
+
+    #[test]
+    fn overshooting_easings_are_not_clamped() {
+        let back = Animation::new(100, 0.0, 10.0).with_easing(Easing::OutBack);
+        assert!(back.value_at(0.5) > 10.0);
+        assert_eq!(back.value_at(1.0), 10.0);
+        let elastic = Animation::new(100, 0.0, 10.0).with_easing(Easing::OutElastic);
+        assert!((0..100).map(|i| elastic.value_at(i as f32 / 100.0)).any(|value| value > 10.0));
+
+        // 多阶段序列同样保留越过终点的部分：回弹阶段播放到约一半时
+        let stage = Animation::new(10_000, 0.0, 10.0).with_easing(Easing::OutBack);
+        let mut sequence = AnimationSequence::new(vec![(Animation::new(0, 0.0, 1.0), ()), (stage, ())]);
+        sequence.start_time -= Duration::from_secs(5);
+        assert_eq!(sequence.current_stage().map(|(index, _)| index), Some(1));
+        assert!(sequence.stage_value() > 10.0);
+
+        // 普通插值仍限制在起止值之间
+        assert_eq!(lerp(0.0, 10.0, 1.5), 10.0);
+        assert_eq!(lerp_unclamped(0.0, 10.0, 1.5), 15.0);
+    }
+
+    #[test]
+    fn postponing_keeps_progress_across_a_pause() {
+        let pause = Duration::from_secs(3);
+        // 10 秒的动画播放了 2 秒（进度约 0.2）
+        let mut anim = Animation::new(10_000, 0.0, 1.0);
+        anim.start_time -= Duration::from_secs(2);
+        let before = anim.progress();
+
+        // 暂停 3 秒：时钟照常前进，恢复时推迟相同时长
+        anim.start_time -= pause;
+        assert!(anim.progress() > before + 0.25);
+        anim.postpone(pause);
+        assert!((anim.progress() - before).abs() < 0.01, "{} vs {before}", anim.progress());
+        assert!(!anim.is_finished());
+
+        // 多阶段序列同样从暂停时的阶段和进度继续
+        let stages = vec![(Animation::new(4_000, 0.0, 1.0), 'a'), (Animation::new(4_000, 0.0, 1.0), 'b')];
+        let mut sequence = AnimationSequence::new(stages);
+        sequence.start_time -= Duration::from_secs(2);
+        let before = sequence.stage_progress();
+        sequence.start_time -= pause;
+        assert_eq!(sequence.current_tag(), Some(&'b'));
+        sequence.postpone(pause);
+        assert_eq!(sequence.current_tag(), Some(&'a'));
+        assert!((sequence.stage_progress() - before).abs() < 0.01);
+    }
+
+    #[test]
+    fn sequence_plays_stages_in_order_and_finishes() {
+        let ms = Duration::from_millis;
+        let stages = vec![
+            (Animation::new(100, 0.0, 1.0), 'a'),
+            (Animation::new(0, 0.0, 1.0), 'x'),
+            (Animation::new(200, 0.0, 1.0), 'b'),
+        ];
+        let mut sequence = AnimationSequence::new(stages);
+        assert_eq!(sequence.total_duration(), ms(300));
+
+        assert_eq!(sequence.stage_at(ms(0)), Some((0, 0.0)));
+        assert_eq!(sequence.stage_at(ms(50)), Some((0, 0.5)));
+        // 时长为 0 的阶段被跳过
+        assert_eq!(sequence.stage_at(ms(100)), Some((2, 0.0)));
+        assert_eq!(sequence.stage_at(ms(200)), Some((2, 0.5)));
+        // 播放完毕后停在最后一个阶段
+        assert_eq!(sequence.stage_at(ms(300)), Some((2, 1.0)));
+        assert_eq!(sequence.stage_at(ms(10_000)), Some((2, 1.0)));
+
+        assert_eq!(sequence.current_tag(), Some(&'a'));
+        assert!(!sequence.is_finished());
+        sequence.start_time -= ms(300);
+        assert!(sequence.is_finished());
+        assert_eq!(sequence.current_stage(), Some((2, &'b')));
+        assert_eq!(sequence.stage_progress(), 1.0);
+        assert_eq!(sequence.stage_value(), 1.0);
+
+        // 空序列立即结束
+        let empty = AnimationSequence::<()>::new(Vec::new());
+        assert_eq!(empty.stage_at(ms(0)), None);
+        assert!(empty.is_finished());
+        assert_eq!(empty.current_tag(), None);
+    }
+
+    #[test]
+    fn vec2_arithmetic_and_lerp() {
+        let a = Vec2::new(1.0, 2.0);
+        let b = Vec2::new(4.0, 6.0);
+        assert_eq!(a + b, Vec2::new(5.0, 8.0));
+        assert_eq!(b - a, Vec2::new(3.0, 4.0));
+        assert_eq!(a * 2.0, Vec2::new(2.0, 4.0));
+        assert_eq!(a.distance(&b), 5.0);
+
+        assert_eq!(a.lerp(b, 0.0), a);
+        assert_eq!(a.lerp(b, 0.5), Vec2::new(2.5, 4.0));
+        assert_eq!(a.lerp(b, 1.0), b);
+        // t 超出 0..=1 时限制在端点
+        assert_eq!(a.lerp(b, -1.0), a);
+        assert_eq!(a.lerp(b, 2.0), b);
+    }
+
+    #[cfg(feature = "gui")]
+    #[test]
+    fn lerp_pos2_clamps_t() {
+        let a = egui::pos2(0.0, 10.0);
+        let b = egui::pos2(20.0, 30.0);
+        assert_eq!(lerp_pos2(a, b, 0.25), egui::pos2(5.0, 15.0));
+        assert_eq!(lerp_pos2(a, b, -0.5), a);
+        assert_eq!(lerp_pos2(a, b, 1.5), b);
+    }
+
+    #[test]
+    fn rising_edge_fires_once_per_transition() {
+        let mut edge = RisingEdge::default();
+        assert!(!edge.update(false));
+        assert!(edge.update(true));
+        // 保持为 true 时不再触发
+        assert!(!edge.update(true));
+        assert!(!edge.update(false));
+        assert!(edge.update(true));
+
+        // reset 记下当前取值而不触发
+        edge.reset(false);
+        assert!(edge.update(true));
+        edge.reset(true);
+        assert!(!edge.update(true));
+    }
 }
@@ -0,0 +1,49 @@
+//! AI强度测试：高等级AI对低等级AI应赢下大多数对局
+
+mod common;
+
+use common::MAX_MOVES;
+
+use six_rush::game::ai::AiPlayer;
+use six_rush::game::piece::Side;
+use six_rush::game::GameResult;
+
+/// 对局种子数，每个种子双方各执黑一次
+const SEEDS: u64 = 10;
+
+#[test]
+fn level_five_beats_level_one() {
+    let (mut wins, mut losses) = (0, 0);
+    for seed in 0..SEEDS {
+        for strong_side in [Side::Black, Side::White] {
+            let mut game = common::demo_game();
+            // 与演示模式下的 `Game::ai_for_turn` 相同，AI知道己方走过的局面，占优时避免重复局面判和
+            let result = common::self_play(
+                &mut game,
+                |game, side, ply| {
+                    let level = if side == strong_side { 5 } else { 1 };
+                    AiPlayer::with_seed(level, seed * 1000 + ply as u64)
+                        .with_seen_positions(game.seen_positions_for_turn())
+                },
+                MAX_MOVES,
+            )
+            .unwrap();
+
+            // 演示对局中 PlayerWin 表示黑方胜
+            let winner = match result {
+                GameResult::PlayerWin => Some(Side::Black),
+                GameResult::AiWin => Some(Side::White),
+                GameResult::Draw => None,
+            };
+            match winner {
+                Some(side) if side == strong_side => wins += 1,
+                Some(_) => losses += 1,
+                None => {}
+            }
+        }
+    }
+
+    let games = SEEDS * 2;
+    assert_eq!(losses, 0, "5级输给了1级");
+    assert!(wins * 2 > games, "5级只赢了 {wins}/{games} 局");
+}
@@ -0,0 +1,48 @@
+//! 集成测试共用的对局工具
+
+use anyhow::Result;
+use six_rush::game::ai::AiPlayer;
+use six_rush::game::piece::Side;
+use six_rush::game::{Game, GameResult};
+
+/// 自对弈的步数上限；演示对局的无吃子判和上限为100步，远小于此上限
+pub const MAX_MOVES: usize = 1000;
+
+/// 从初始局面开始一局演示对局（双方均由电脑行棋，黑方先行）
+pub fn demo_game() -> Game {
+    let mut game = Game::new();
+    game.start_demo();
+    game
+}
+
+/// 无界面自对弈：黑白双方由 `ai` 按行棋方和步数构造的AI交替行棋，直至分出胜负
+///
+/// 每一步都校验棋子数与吃子记录一致、回合正确切换；
+/// 超过 `max_moves` 步仍未结束则返回错误（用于发现死循环）
+pub fn self_play(game: &mut Game, ai: impl Fn(&Game, Side, usize) -> AiPlayer, max_moves: usize) -> Result<GameResult> {
+    for ply in 0..max_moves {
+        if let Some(result) = game.last_result {
+            return Ok(result);
+        }
+
+        let side = game.current_turn;
+        let before = (game.board.count_active(Side::Black), game.board.count_active(Side::White));
+        let outcome = game.ai_turn_with(&ai(game, side, ply))?;
+
+        // 被吃的只能是对方棋子，且数量与吃子记录一致
+        let after = (game.board.count_active(Side::Black), game.board.count_active(Side::White));
+        let (own_before, own_after, opp_before, opp_after) = match side {
+            Side::Black => (before.0, after.0, before.1, after.1),
+            Side::White => (before.1, after.1, before.0, after.0),
+        };
+        if own_after != own_before || opp_before.checked_sub(opp_after) != Some(outcome.captured.len()) {
+            anyhow::bail!("棋子数与吃子记录不一致: {:?} -> {:?}, 吃子 {:?}", before, after, outcome.captured);
+        }
+        if game.last_result.is_none() && game.current_turn == side {
+            anyhow::bail!("行棋后回合未切换");
+        }
+    }
+
+    game.last_result
+        .ok_or_else(|| anyhow::anyhow!("超过 {} 步仍未结束", max_moves))
+}
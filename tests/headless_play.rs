@@ -0,0 +1,56 @@
+//! 无界面行棋接口的集成测试
+
+use six_rush::game::piece::Side;
+use six_rush::game::rules::get_valid_moves;
+use six_rush::game::{Game, GameState};
+
+/// 两个不同等级的AI通过 `ai_for_turn` / `ai_turn_with` 对弈至终局
+#[test]
+fn two_ais_play_to_completion() {
+    let mut game = Game::new();
+    game.start_demo();
+
+    for _ in 0..500 {
+        if game.last_result.is_some() {
+            break;
+        }
+        let side = game.current_turn;
+        let level = match side {
+            Side::Black => 1,
+            Side::White => 2,
+        };
+        let legal = get_valid_moves(&game.board, side);
+        let history = game.move_history.len();
+
+        game.ai_turn_with(&game.ai_for_turn(level)).unwrap();
+
+        let record = game.move_history.last().unwrap();
+        assert_eq!(game.move_history.len(), history + 1);
+        assert_eq!(record.side, side);
+        assert!(legal.contains(&(record.from, record.to)));
+        if game.last_result.is_none() {
+            assert_eq!(game.current_turn, side.opposite());
+            assert_eq!(game.state, GameState::AiThinking);
+        }
+    }
+
+    let result = game.last_result.expect("500步内应结束对局");
+    assert_eq!(game.state, GameState::GameOverDialog(result));
+    assert!(game.end_reason.is_some());
+    assert!(game.play_move((0, 0), (0, 1)).is_err());
+}
+
+/// 对局结束后 `ai_turn` 报错且不改变棋局
+#[test]
+fn ai_turn_after_game_over_is_rejected() {
+    let mut game = Game::new();
+    game.start_demo();
+    while game.last_result.is_none() {
+        game.ai_turn_with(&game.ai_for_turn(1)).unwrap();
+    }
+    let notation = game.board.to_notation();
+    let history = game.move_history.len();
+    assert!(game.ai_turn().is_err());
+    assert_eq!(game.board.to_notation(), notation);
+    assert_eq!(game.move_history.len(), history);
+}
@@ -0,0 +1,37 @@
+//! 确定性的整局自对弈测试：双方为带种子的AI，覆盖 Game、AiPlayer、rules 与 board
+
+mod common;
+
+use common::MAX_MOVES;
+
+use six_rush::game::ai::AiPlayer;
+use six_rush::game::{GameResult, GameState};
+
+#[test]
+fn every_level_plays_to_a_legal_result() {
+    for level in 1..=5 {
+        for seed in 0..2 {
+            let mut game = common::demo_game();
+            let result = common::self_play(
+                &mut game,
+                |_, _, ply| AiPlayer::with_seed(level, seed * 1000 + ply as u64),
+                MAX_MOVES,
+            )
+            .unwrap_or_else(|e| panic!("等级 {level} 种子 {seed}: {e}"));
+
+            assert!(matches!(result, GameResult::PlayerWin | GameResult::AiWin | GameResult::Draw));
+            assert_eq!(game.state, GameState::GameOverDialog(result));
+            assert_eq!(game.check_game_end().map(|(result, _)| result), Some(result));
+        }
+    }
+}
+
+#[test]
+fn seeded_self_play_is_deterministic() {
+    let play = || {
+        let mut game = common::demo_game();
+        common::self_play(&mut game, |_, _, ply| AiPlayer::with_seed(2, ply as u64), MAX_MOVES).unwrap();
+        game.move_history.iter().map(|record| (record.from, record.to)).collect::<Vec<_>>()
+    };
+    assert_eq!(play(), play());
+}